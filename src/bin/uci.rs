@@ -0,0 +1,6 @@
+//! Chessio as a UCI engine for chess GUIs and tournament tools, without the
+//! raylib front end.
+
+fn main() {
+    chessio::chess::uci::run();
+}
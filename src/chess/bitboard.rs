@@ -0,0 +1,322 @@
+use std::sync::OnceLock;
+
+use super::{constants::CHESSBOARD_SIZE, piece::Side};
+
+/// Set of squares, bit `n` stands for square `n`.
+pub type Bitboard = u64;
+/// Index of a square: 0 is a1, 7 is h1 and 63 is h8.
+pub type Square = usize;
+
+pub const FILE_A: Bitboard = 0x0101_0101_0101_0101;
+pub const FILE_H: Bitboard = FILE_A << 7;
+pub const RANK_1: Bitboard = 0xff;
+pub const RANK_8: Bitboard = RANK_1 << 56;
+
+const ROOK_DIRECTIONS: [(i32, i32); 4] = [(1, 0), (-1, 0), (0, 1), (0, -1)];
+const BISHOP_DIRECTIONS: [(i32, i32); 4] = [(1, 1), (1, -1), (-1, 1), (-1, -1)];
+const KNIGHT_OFFSETS: [(i32, i32); 8] = [
+    (1, 2),
+    (2, 1),
+    (2, -1),
+    (1, -2),
+    (-1, -2),
+    (-2, -1),
+    (-2, 1),
+    (-1, 2),
+];
+const KING_OFFSETS: [(i32, i32); 8] = [
+    (1, 0),
+    (1, 1),
+    (0, 1),
+    (-1, 1),
+    (-1, 0),
+    (-1, -1),
+    (0, -1),
+    (1, -1),
+];
+
+/// Seeds that find the magics of each rank quickly.
+const MAGIC_SEEDS: [u64; 8] = [728, 10316, 55013, 32803, 12281, 15100, 16645, 255];
+
+/// Square of the tile on `(x, y)`, where `y == 0` is the 8th rank.
+pub fn get_square((x, y): (usize, usize)) -> Square {
+    (CHESSBOARD_SIZE - 1 - y) * CHESSBOARD_SIZE + x
+}
+
+/// Tile coordinates of `square`, the inverse of [`get_square`].
+pub fn get_square_coords(square: Square) -> (usize, usize) {
+    (
+        square % CHESSBOARD_SIZE,
+        CHESSBOARD_SIZE - 1 - square / CHESSBOARD_SIZE,
+    )
+}
+
+pub fn square_bitboard(square: Square) -> Bitboard {
+    1 << square
+}
+
+/// Iterates over the squares of `bitboard` from a1 to h8.
+pub fn get_squares(bitboard: Bitboard) -> Squares {
+    Squares(bitboard)
+}
+
+pub struct Squares(Bitboard);
+
+impl Iterator for Squares {
+    type Item = Square;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        if self.0 == 0 {
+            return None;
+        }
+
+        let square = self.0.trailing_zeros() as Square;
+        self.0 &= self.0 - 1;
+        Some(square)
+    }
+}
+
+/// Magic bitboard of one square: the relevant occupancy is hashed into an
+/// index of the shared slider attack table.
+#[derive(Debug, Default, Copy, Clone)]
+struct Magic {
+    mask: Bitboard,
+    magic: u64,
+    shift: u32,
+    offset: usize,
+}
+
+impl Magic {
+    #[cfg(all(target_arch = "x86_64", target_feature = "bmi2"))]
+    fn get_index(&self, occupancy: Bitboard) -> usize {
+        // SAFETY: only compiled for targets with BMI2.
+        let index = unsafe { std::arch::x86_64::_pext_u64(occupancy, self.mask) };
+        self.offset + index as usize
+    }
+    #[cfg(not(all(target_arch = "x86_64", target_feature = "bmi2")))]
+    fn get_index(&self, occupancy: Bitboard) -> usize {
+        self.offset + ((occupancy & self.mask).wrapping_mul(self.magic) >> self.shift) as usize
+    }
+}
+
+struct AttackTables {
+    pawn: [[Bitboard; 64]; 2],
+    knight: [Bitboard; 64],
+    king: [Bitboard; 64],
+    rook_magics: [Magic; 64],
+    bishop_magics: [Magic; 64],
+    slider_attacks: Vec<Bitboard>,
+    /// Squares strictly between two squares on a common line.
+    between: Vec<[Bitboard; 64]>,
+    /// Whole line through two squares, both included.
+    line: Vec<[Bitboard; 64]>,
+}
+
+static ATTACK_TABLES: OnceLock<AttackTables> = OnceLock::new();
+
+fn get_tables() -> &'static AttackTables {
+    ATTACK_TABLES.get_or_init(AttackTables::new)
+}
+
+/// Squares attacked by a pawn of `side` standing on `square`.
+pub fn get_pawn_attacks(side: Side, square: Square) -> Bitboard {
+    get_tables().pawn[side as usize][square]
+}
+
+pub fn get_knight_attacks(square: Square) -> Bitboard {
+    get_tables().knight[square]
+}
+
+pub fn get_king_attacks(square: Square) -> Bitboard {
+    get_tables().king[square]
+}
+
+pub fn get_rook_attacks(square: Square, occupancy: Bitboard) -> Bitboard {
+    let tables = get_tables();
+    tables.slider_attacks[tables.rook_magics[square].get_index(occupancy)]
+}
+
+pub fn get_bishop_attacks(square: Square, occupancy: Bitboard) -> Bitboard {
+    let tables = get_tables();
+    tables.slider_attacks[tables.bishop_magics[square].get_index(occupancy)]
+}
+
+pub fn get_queen_attacks(square: Square, occupancy: Bitboard) -> Bitboard {
+    get_rook_attacks(square, occupancy) | get_bishop_attacks(square, occupancy)
+}
+
+/// Squares strictly between `from` and `to`, empty when they do not share a
+/// rank, file or diagonal.
+pub fn get_between(from: Square, to: Square) -> Bitboard {
+    get_tables().between[from][to]
+}
+
+/// Rank, file or diagonal going through both squares, empty when there is none.
+pub fn get_line(from: Square, to: Square) -> Bitboard {
+    get_tables().line[from][to]
+}
+
+/// Steps from `square` by the offset, `None` when that leaves the board.
+fn get_offset_square(square: Square, (file_offset, rank_offset): (i32, i32)) -> Option<Square> {
+    let file = (square % 8) as i32 + file_offset;
+    let rank = (square / 8) as i32 + rank_offset;
+
+    if (0..8).contains(&file) && (0..8).contains(&rank) {
+        Some((rank * 8 + file) as Square)
+    } else {
+        None
+    }
+}
+
+fn get_offset_attacks(square: Square, offsets: &[(i32, i32)]) -> Bitboard {
+    offsets
+        .iter()
+        .filter_map(|offset| get_offset_square(square, *offset))
+        .fold(0, |attacks, target| attacks | square_bitboard(target))
+}
+
+/// Slider attacks computed ray by ray, used to fill the magic tables.
+fn get_sliding_attacks(square: Square, occupancy: Bitboard, directions: &[(i32, i32)]) -> Bitboard {
+    let mut attacks = 0;
+    for direction in directions {
+        let mut current = square;
+        while let Some(target) = get_offset_square(current, *direction) {
+            attacks |= square_bitboard(target);
+            if occupancy & square_bitboard(target) != 0 {
+                break;
+            }
+            current = target;
+        }
+    }
+
+    attacks
+}
+
+/// xorshift64* generator, only used to search for magics.
+struct MagicRng(u64);
+
+impl MagicRng {
+    fn next(&mut self) -> u64 {
+        self.0 ^= self.0 >> 12;
+        self.0 ^= self.0 << 25;
+        self.0 ^= self.0 >> 27;
+        self.0.wrapping_mul(2685821657736338717)
+    }
+    /// Random number with few bits set, those make better magics.
+    fn next_sparse(&mut self) -> u64 {
+        self.next() & self.next() & self.next()
+    }
+}
+
+/// Finds the magics of every square for the slider moving in `directions` and
+/// appends their attacks to `attacks`.
+fn init_magics(directions: &[(i32, i32)], attacks: &mut Vec<Bitboard>) -> [Magic; 64] {
+    let mut magics = [Magic::default(); 64];
+    let mut occupancies = Vec::with_capacity(4096);
+    let mut references = Vec::with_capacity(4096);
+    let mut epochs = vec![0u32; 4096];
+    let mut epoch = 0;
+
+    for (square, magic) in magics.iter_mut().enumerate() {
+        let rank = square / 8;
+        let file = square % 8;
+        let edges =
+            ((RANK_1 | RANK_8) & !(RANK_1 << (rank * 8))) | ((FILE_A | FILE_H) & !(FILE_A << file));
+
+        magic.mask = get_sliding_attacks(square, 0, directions) & !edges;
+        magic.shift = 64 - magic.mask.count_ones();
+        magic.offset = attacks.len();
+
+        // Every subset of the mask, enumerated with the Carry-Rippler trick.
+        occupancies.clear();
+        references.clear();
+        let mut occupancy: Bitboard = 0;
+        loop {
+            occupancies.push(occupancy);
+            references.push(get_sliding_attacks(square, occupancy, directions));
+            occupancy = occupancy.wrapping_sub(magic.mask) & magic.mask;
+            if occupancy == 0 {
+                break;
+            }
+        }
+        attacks.resize(magic.offset + occupancies.len(), 0);
+
+        if cfg!(all(target_arch = "x86_64", target_feature = "bmi2")) {
+            for (occupancy, reference) in occupancies.iter().zip(&references) {
+                attacks[magic.get_index(*occupancy)] = *reference;
+            }
+            continue;
+        }
+
+        let mut rng = MagicRng(MAGIC_SEEDS[rank]);
+        'search: loop {
+            magic.magic = rng.next_sparse();
+            if (magic.magic.wrapping_mul(magic.mask) >> 56).count_ones() < 6 {
+                continue;
+            }
+
+            // Epochs mark the entries written by this attempt, so the table
+            // does not have to be cleared between attempts.
+            epoch += 1;
+            for (occupancy, reference) in occupancies.iter().zip(&references) {
+                let index = magic.get_index(*occupancy);
+                if epochs[index - magic.offset] < epoch {
+                    epochs[index - magic.offset] = epoch;
+                    attacks[index] = *reference;
+                } else if attacks[index] != *reference {
+                    continue 'search;
+                }
+            }
+            break;
+        }
+    }
+
+    magics
+}
+
+impl AttackTables {
+    fn new() -> Self {
+        let mut pawn = [[0; 64]; 2];
+        let mut knight = [0; 64];
+        let mut king = [0; 64];
+        for square in 0..64 {
+            pawn[Side::White as usize][square] = get_offset_attacks(square, &[(-1, 1), (1, 1)]);
+            pawn[Side::Black as usize][square] = get_offset_attacks(square, &[(-1, -1), (1, -1)]);
+            knight[square] = get_offset_attacks(square, &KNIGHT_OFFSETS);
+            king[square] = get_offset_attacks(square, &KING_OFFSETS);
+        }
+
+        let mut slider_attacks = Vec::new();
+        let rook_magics = init_magics(&ROOK_DIRECTIONS, &mut slider_attacks);
+        let bishop_magics = init_magics(&BISHOP_DIRECTIONS, &mut slider_attacks);
+
+        let mut between = vec![[0; 64]; 64];
+        let mut line = vec![[0; 64]; 64];
+        for from in 0..64 {
+            for (directions, magics) in [
+                (&ROOK_DIRECTIONS, &rook_magics),
+                (&BISHOP_DIRECTIONS, &bishop_magics),
+            ] {
+                let from_attacks = get_sliding_attacks(from, 0, directions);
+                for to in get_squares(from_attacks) {
+                    let to_attacks = get_sliding_attacks(to, 0, directions);
+                    line[from][to] =
+                        (from_attacks & to_attacks) | square_bitboard(from) | square_bitboard(to);
+                    between[from][to] = slider_attacks[magics[from].get_index(square_bitboard(to))]
+                        & slider_attacks[magics[to].get_index(square_bitboard(from))];
+                }
+            }
+        }
+
+        AttackTables {
+            pawn,
+            knight,
+            king,
+            rook_magics,
+            bishop_magics,
+            slider_attacks,
+            between,
+            line,
+        }
+    }
+}
@@ -0,0 +1,338 @@
+use std::time::{Duration, Instant};
+
+use super::piece::Side;
+
+/// Time added for each move played in a stage of a [`TimeControl`].
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+pub enum Increment {
+    None,
+    /// Added after every move.
+    Fischer(Duration),
+    /// The time used for a move is given back, up to this much.
+    Bronstein(Duration),
+    /// The clock only starts running after this much of every move.
+    Delay(Duration),
+}
+
+/// Part of a time control, e.g. `40/90+30` is 90 minutes for 40 moves with
+/// 30 seconds added for every move.
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+pub struct TimeStage {
+    /// Moves to play in the stage, `None` for the rest of the game.
+    pub moves: Option<u32>,
+    pub time: Duration,
+    pub increment: Increment,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum TimeControlError {
+    /// The time control has no stages.
+    Empty,
+    /// The stage is not `[moves/]minutes[(+|d|b)seconds]`.
+    InvalidStage(String),
+    /// Only the last stage can be for the rest of the game.
+    StageAfterSuddenDeath(String),
+}
+
+impl std::fmt::Display for TimeControlError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            TimeControlError::Empty => write!(f, "the time control is empty"),
+            TimeControlError::InvalidStage(stage) => {
+                write!(f, "'{}' is not a valid time control stage", stage)
+            }
+            TimeControlError::StageAfterSuddenDeath(stage) => write!(
+                f,
+                "stage '{}' follows a stage for the rest of the game",
+                stage
+            ),
+        }
+    }
+}
+
+impl std::error::Error for TimeControlError {}
+
+/// Stages of time both sides get. When a side has played the moves of a stage,
+/// the time of the next one is added to its clock. A last stage with a number
+/// of moves repeats.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct TimeControl {
+    pub stages: Vec<TimeStage>,
+}
+
+impl TimeControl {
+    /// Parses stages separated by `:`, each written as
+    /// `[moves/]minutes[(+|d|b)seconds]` with `+` for a Fischer increment, `d`
+    /// for a delay and `b` for Bronstein. For example `5+3`, `15d5` or
+    /// `40/90+30:30+30`.
+    pub fn parse(text: &str) -> Result<Self, TimeControlError> {
+        if text.trim().is_empty() {
+            return Err(TimeControlError::Empty);
+        }
+
+        let mut stages = Vec::new();
+        for stage in text.trim().split(':') {
+            if matches!(stages.last(), Some(TimeStage { moves: None, .. })) {
+                return Err(TimeControlError::StageAfterSuddenDeath(stage.to_string()));
+            }
+            stages.push(
+                parse_stage(stage)
+                    .ok_or_else(|| TimeControlError::InvalidStage(stage.to_string()))?,
+            );
+        }
+
+        Ok(TimeControl { stages })
+    }
+}
+
+fn parse_stage(stage: &str) -> Option<TimeStage> {
+    let (moves, rest) = match stage.split_once('/') {
+        Some((moves, rest)) => (
+            Some(moves.parse::<u32>().ok().filter(|moves| *moves > 0)?),
+            rest,
+        ),
+        None => (None, stage),
+    };
+    let (minutes, increment) = match rest.find(['+', 'd', 'b']) {
+        Some(index) => {
+            let seconds = Duration::from_secs_f64(parse_non_negative(&rest[index + 1..])?);
+            let increment = match &rest[index..index + 1] {
+                "+" => Increment::Fischer(seconds),
+                "d" => Increment::Delay(seconds),
+                _ => Increment::Bronstein(seconds),
+            };
+            (&rest[..index], increment)
+        }
+        None => (rest, Increment::None),
+    };
+
+    Some(TimeStage {
+        moves,
+        time: Duration::from_secs_f64(parse_non_negative(minutes)? * 60.0),
+        increment,
+    })
+}
+
+fn parse_non_negative(number: &str) -> Option<f64> {
+    number
+        .parse::<f64>()
+        .ok()
+        .filter(|number| number.is_finite() && *number >= 0.0)
+}
+
+/// Writes `duration` in whole `unit`s when it can, in decimals otherwise.
+fn write_in_units(
+    f: &mut std::fmt::Formatter<'_>,
+    duration: Duration,
+    unit: Duration,
+) -> std::fmt::Result {
+    if duration.as_nanos().is_multiple_of(unit.as_nanos()) {
+        write!(f, "{}", duration.as_nanos() / unit.as_nanos())
+    } else {
+        write!(f, "{}", duration.as_secs_f64() / unit.as_secs_f64())
+    }
+}
+
+impl std::fmt::Display for TimeControl {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        for (index, stage) in self.stages.iter().enumerate() {
+            if index > 0 {
+                write!(f, ":")?;
+            }
+            if let Some(moves) = stage.moves {
+                write!(f, "{}/", moves)?;
+            }
+            write_in_units(f, stage.time, Duration::from_secs(60))?;
+
+            let (sign, seconds) = match stage.increment {
+                Increment::None => continue,
+                Increment::Fischer(seconds) => ('+', seconds),
+                Increment::Delay(seconds) => ('d', seconds),
+                Increment::Bronstein(seconds) => ('b', seconds),
+            };
+            write!(f, "{}", sign)?;
+            write_in_units(f, seconds, Duration::from_secs(1))?;
+        }
+
+        Ok(())
+    }
+}
+
+/// Where a [`ChessClock`] gets the current time from.
+pub trait TimeSource {
+    /// Time since some fixed point, it never goes back.
+    fn now(&self) -> Duration;
+}
+
+/// Real time, measured from when the source was created.
+#[derive(Debug, Copy, Clone)]
+pub struct WallTime {
+    start: Instant,
+}
+
+impl Default for WallTime {
+    fn default() -> Self {
+        WallTime {
+            start: Instant::now(),
+        }
+    }
+}
+
+impl TimeSource for WallTime {
+    fn now(&self) -> Duration {
+        self.start.elapsed()
+    }
+}
+
+fn get_side_index(side: Side) -> usize {
+    match side {
+        Side::White => 0,
+        Side::Black => 1,
+    }
+}
+
+/// Clocks of both sides. Only the clock of the side whose turn it is runs,
+/// [`ChessClock::press`] ends the turn and starts the clock of the other side.
+#[derive(Debug, Clone)]
+pub struct ChessClock<T: TimeSource = WallTime> {
+    control: TimeControl,
+    time_source: T,
+    /// Time left of White and Black, without the turn that is running.
+    remaining: [Duration; 2],
+    /// Stage of the time control each side is in.
+    stages: [usize; 2],
+    /// Moves each side has played in its stage.
+    stage_moves: [u32; 2],
+    /// Side whose clock runs and when its turn started.
+    running: Option<(Side, Duration)>,
+}
+
+impl<T: TimeSource> ChessClock<T> {
+    /// Stopped clocks with the time of the first stage on both sides.
+    pub fn new(control: TimeControl, time_source: T) -> Self {
+        let time = control
+            .stages
+            .first()
+            .map(|stage| stage.time)
+            .unwrap_or_default();
+
+        ChessClock {
+            control,
+            time_source,
+            remaining: [time; 2],
+            stages: [0; 2],
+            stage_moves: [0; 2],
+            running: None,
+        }
+    }
+    pub fn get_control(&self) -> &TimeControl {
+        &self.control
+    }
+    pub fn get_running_side(&self) -> Option<Side> {
+        self.running.map(|(side, _)| side)
+    }
+    fn get_stage(&self, side: Side) -> Option<&TimeStage> {
+        self.control.stages.get(self.stages[get_side_index(side)])
+    }
+    /// Time the running turn has used up so far and how long it has taken.
+    fn get_turn_usage(&self, side: Side, started: Duration) -> (Duration, Duration) {
+        let elapsed = self.time_source.now().saturating_sub(started);
+        let used = match self.get_stage(side).map(|stage| stage.increment) {
+            Some(Increment::Delay(delay)) => elapsed.saturating_sub(delay),
+            _ => elapsed,
+        };
+
+        (used, elapsed)
+    }
+    /// Time `side` has left, counting the turn that is running.
+    pub fn get_remaining(&self, side: Side) -> Duration {
+        let remaining = self.remaining[get_side_index(side)];
+        match self.running {
+            Some((running, started)) if running == side => {
+                remaining.saturating_sub(self.get_turn_usage(side, started).0)
+            }
+            _ => remaining,
+        }
+    }
+    /// Increment of the stage `side` is in.
+    pub fn get_increment(&self, side: Side) -> Increment {
+        self.get_stage(side)
+            .map(|stage| stage.increment)
+            .unwrap_or(Increment::None)
+    }
+    /// Side that has run out of time.
+    pub fn get_flagged_side(&self) -> Option<Side> {
+        [Side::White, Side::Black]
+            .into_iter()
+            .find(|side| self.get_remaining(*side).is_zero())
+    }
+    /// Ends the running turn without counting it as a move.
+    pub fn stop(&mut self) {
+        if let Some((side, started)) = self.running.take() {
+            let (used, _) = self.get_turn_usage(side, started);
+            let remaining = &mut self.remaining[get_side_index(side)];
+            *remaining = remaining.saturating_sub(used);
+        }
+    }
+    /// Starts the clock of `side`, the running turn ends without counting it
+    /// as a move.
+    pub fn start(&mut self, side: Side) {
+        self.stop();
+        self.running = Some((side, self.time_source.now()));
+    }
+    /// The running side has played its move: its time is updated by the time
+    /// control and the clock of the other side starts. Returns whether the move
+    /// was played in time, a side out of time gets nothing added.
+    pub fn press(&mut self) -> bool {
+        let (side, started) = match self.running {
+            Some(running) => running,
+            None => return false,
+        };
+        let (_, elapsed) = self.get_turn_usage(side, started);
+        self.start(side.opposite());
+
+        let index = get_side_index(side);
+        if self.remaining[index].is_zero() {
+            return false;
+        }
+        let stage = match self.get_stage(side) {
+            Some(stage) => *stage,
+            None => return true,
+        };
+
+        let bonus = match stage.increment {
+            Increment::Fischer(increment) => increment,
+            Increment::Bronstein(delay) => elapsed.min(delay),
+            Increment::None | Increment::Delay(_) => Duration::ZERO,
+        };
+        self.remaining[index] += bonus;
+
+        self.stage_moves[index] += 1;
+        if Some(self.stage_moves[index]) == stage.moves {
+            self.stage_moves[index] = 0;
+            // The last stage repeats.
+            self.stages[index] = (self.stages[index] + 1).min(self.control.stages.len() - 1);
+            self.remaining[index] += self.control.stages[self.stages[index]].time;
+        }
+
+        true
+    }
+}
+
+/// Clock time as `h:mm:ss`, `m:ss` under an hour and `s.t` with tenths under
+/// ten seconds.
+pub fn format_clock_time(time: Duration) -> String {
+    let seconds = time.as_secs();
+    if seconds >= 3600 {
+        format!(
+            "{}:{:02}:{:02}",
+            seconds / 3600,
+            seconds / 60 % 60,
+            seconds % 60
+        )
+    } else if seconds >= 10 {
+        format!("{}:{:02}", seconds / 60, seconds % 60)
+    } else {
+        format!("{}.{}", seconds, time.subsec_millis() / 100)
+    }
+}
@@ -0,0 +1,704 @@
+use std::ops::{Add, AddAssign, Mul, Neg, Sub, SubAssign};
+
+use super::{bitboard::*, piece::*, position::Position};
+
+/// Phase of a position with all the pieces on the board.
+pub const MAX_PHASE: i32 = 24;
+
+/// How much each piece adds to the phase, indexed by `PieceType as usize`.
+const PHASE_WEIGHTS: [i32; 6] = [0, 2, 1, 1, 4, 0];
+
+/// Names of the pieces in the config file, indexed by `PieceType as usize`.
+const PIECE_NAMES: [&str; 6] = ["pawn", "rook", "knight", "bishop", "queen", "king"];
+
+/// Middlegame and endgame value of a term, blended by the phase of the game.
+#[derive(Debug, Default, Copy, Clone, PartialEq, Eq)]
+pub struct Score {
+    pub mg: i32,
+    pub eg: i32,
+}
+
+impl Score {
+    pub const fn new(mg: i32, eg: i32) -> Self {
+        Score { mg, eg }
+    }
+    /// Value at `phase`, from the endgame at 0 to the middlegame at [`MAX_PHASE`].
+    pub fn taper(&self, phase: i32) -> i32 {
+        (self.mg * phase + self.eg * (MAX_PHASE - phase)) / MAX_PHASE
+    }
+}
+
+impl Add for Score {
+    type Output = Score;
+
+    fn add(self, other: Score) -> Score {
+        Score::new(self.mg + other.mg, self.eg + other.eg)
+    }
+}
+
+impl AddAssign for Score {
+    fn add_assign(&mut self, other: Score) {
+        *self = *self + other;
+    }
+}
+
+impl Sub for Score {
+    type Output = Score;
+
+    fn sub(self, other: Score) -> Score {
+        Score::new(self.mg - other.mg, self.eg - other.eg)
+    }
+}
+
+impl SubAssign for Score {
+    fn sub_assign(&mut self, other: Score) {
+        *self = *self - other;
+    }
+}
+
+impl Neg for Score {
+    type Output = Score;
+
+    fn neg(self) -> Score {
+        Score::new(-self.mg, -self.eg)
+    }
+}
+
+impl Mul<i32> for Score {
+    type Output = Score;
+
+    fn mul(self, count: i32) -> Score {
+        Score::new(self.mg * count, self.eg * count)
+    }
+}
+
+/// Weights of the evaluation. Every value is a bonus of the side having the
+/// feature, penalties are negative.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct EvalParams {
+    /// Indexed by `PieceType as usize`.
+    pub material: [Score; 6],
+    /// Indexed by `PieceType as usize` and the square of a White piece, Black
+    /// pieces use the square mirrored to White's side.
+    pub piece_squares: [[Score; 64]; 6],
+    /// For every pawn behind another pawn of the same side on its file.
+    pub doubled_pawn: Score,
+    /// Pawn without pawns of its side on the neighbouring files.
+    pub isolated_pawn: Score,
+    /// Pawn no enemy pawn can stop, indexed by its rank counted from its side,
+    /// 0 being the first rank.
+    pub passed_pawn: [Score; 8],
+    /// Pawn of the king's side one or two ranks in front of it, on its file or
+    /// a neighbouring one.
+    pub king_pawn_shield: Score,
+    /// File of the king, or one next to it, without pawns of the king's side.
+    pub king_open_file: Score,
+    /// Square next to the king attacked by an enemy knight, bishop, rook or queen.
+    pub king_zone_attack: Score,
+    /// Per square a piece can go to that no enemy pawn attacks, indexed by
+    /// `PieceType as usize`.
+    pub mobility: [Score; 6],
+}
+
+/// Piece-square tables, as a board is drawn: the 8th rank first. Based on
+/// Tomasz Michniewski's Simplified Evaluation Function.
+#[rustfmt::skip]
+const PAWN_MG: [i32; 64] = [
+     0,  0,  0,  0,  0,  0,  0,  0,
+    50, 50, 50, 50, 50, 50, 50, 50,
+    10, 10, 20, 30, 30, 20, 10, 10,
+     5,  5, 10, 25, 25, 10,  5,  5,
+     0,  0,  0, 20, 20,  0,  0,  0,
+     5, -5,-10,  0,  0,-10, -5,  5,
+     5, 10, 10,-20,-20, 10, 10,  5,
+     0,  0,  0,  0,  0,  0,  0,  0,
+];
+#[rustfmt::skip]
+const PAWN_EG: [i32; 64] = [
+     0,  0,  0,  0,  0,  0,  0,  0,
+    40, 40, 40, 40, 40, 40, 40, 40,
+    25, 25, 25, 25, 25, 25, 25, 25,
+    15, 15, 15, 15, 15, 15, 15, 15,
+     8,  8,  8,  8,  8,  8,  8,  8,
+     4,  4,  4,  4,  4,  4,  4,  4,
+     0,  0,  0,  0,  0,  0,  0,  0,
+     0,  0,  0,  0,  0,  0,  0,  0,
+];
+#[rustfmt::skip]
+const ROOK: [i32; 64] = [
+     0,  0,  0,  0,  0,  0,  0,  0,
+     5, 10, 10, 10, 10, 10, 10,  5,
+    -5,  0,  0,  0,  0,  0,  0, -5,
+    -5,  0,  0,  0,  0,  0,  0, -5,
+    -5,  0,  0,  0,  0,  0,  0, -5,
+    -5,  0,  0,  0,  0,  0,  0, -5,
+    -5,  0,  0,  0,  0,  0,  0, -5,
+     0,  0,  0,  5,  5,  0,  0,  0,
+];
+#[rustfmt::skip]
+const KNIGHT: [i32; 64] = [
+    -50,-40,-30,-30,-30,-30,-40,-50,
+    -40,-20,  0,  0,  0,  0,-20,-40,
+    -30,  0, 10, 15, 15, 10,  0,-30,
+    -30,  5, 15, 20, 20, 15,  5,-30,
+    -30,  0, 15, 20, 20, 15,  0,-30,
+    -30,  5, 10, 15, 15, 10,  5,-30,
+    -40,-20,  0,  5,  5,  0,-20,-40,
+    -50,-40,-30,-30,-30,-30,-40,-50,
+];
+#[rustfmt::skip]
+const BISHOP: [i32; 64] = [
+    -20,-10,-10,-10,-10,-10,-10,-20,
+    -10,  0,  0,  0,  0,  0,  0,-10,
+    -10,  0,  5, 10, 10,  5,  0,-10,
+    -10,  5,  5, 10, 10,  5,  5,-10,
+    -10,  0, 10, 10, 10, 10,  0,-10,
+    -10, 10, 10, 10, 10, 10, 10,-10,
+    -10,  5,  0,  0,  0,  0,  5,-10,
+    -20,-10,-10,-10,-10,-10,-10,-20,
+];
+#[rustfmt::skip]
+const QUEEN: [i32; 64] = [
+    -20,-10,-10, -5, -5,-10,-10,-20,
+    -10,  0,  0,  0,  0,  0,  0,-10,
+    -10,  0,  5,  5,  5,  5,  0,-10,
+     -5,  0,  5,  5,  5,  5,  0, -5,
+      0,  0,  5,  5,  5,  5,  0, -5,
+    -10,  5,  5,  5,  5,  5,  0,-10,
+    -10,  0,  5,  0,  0,  0,  0,-10,
+    -20,-10,-10, -5, -5,-10,-10,-20,
+];
+#[rustfmt::skip]
+const KING_MG: [i32; 64] = [
+    -30,-40,-40,-50,-50,-40,-40,-30,
+    -30,-40,-40,-50,-50,-40,-40,-30,
+    -30,-40,-40,-50,-50,-40,-40,-30,
+    -30,-40,-40,-50,-50,-40,-40,-30,
+    -20,-30,-30,-40,-40,-30,-30,-20,
+    -10,-20,-20,-20,-20,-20,-20,-10,
+     20, 20,  0,  0,  0,  0, 20, 20,
+     20, 30, 10,  0,  0, 10, 30, 20,
+];
+#[rustfmt::skip]
+const KING_EG: [i32; 64] = [
+    -50,-40,-30,-20,-20,-30,-40,-50,
+    -30,-20,-10,  0,  0,-10,-20,-30,
+    -30,-10, 20, 30, 30, 20,-10,-30,
+    -30,-10, 30, 40, 40, 30,-10,-30,
+    -30,-10, 30, 40, 40, 30,-10,-30,
+    -30,-10, 20, 30, 30, 20,-10,-30,
+    -30,-30,  0,  0,  0,  0,-30,-30,
+    -50,-30,-30,-30,-30,-30,-30,-50,
+];
+
+/// Square of the `index`th value of a table written the way a board is drawn.
+fn get_table_square(index: usize) -> Square {
+    index ^ 56
+}
+
+fn get_piece_square_table(mg: &[i32; 64], eg: &[i32; 64]) -> [Score; 64] {
+    let mut table = [Score::default(); 64];
+    for index in 0..64 {
+        table[get_table_square(index)] = Score::new(mg[index], eg[index]);
+    }
+
+    table
+}
+
+impl Default for EvalParams {
+    fn default() -> Self {
+        EvalParams {
+            material: [
+                Score::new(100, 120),
+                Score::new(500, 520),
+                Score::new(320, 300),
+                Score::new(330, 320),
+                Score::new(950, 950),
+                Score::new(0, 0),
+            ],
+            piece_squares: [
+                get_piece_square_table(&PAWN_MG, &PAWN_EG),
+                get_piece_square_table(&ROOK, &ROOK),
+                get_piece_square_table(&KNIGHT, &KNIGHT),
+                get_piece_square_table(&BISHOP, &BISHOP),
+                get_piece_square_table(&QUEEN, &QUEEN),
+                get_piece_square_table(&KING_MG, &KING_EG),
+            ],
+            doubled_pawn: Score::new(-10, -20),
+            isolated_pawn: Score::new(-12, -15),
+            passed_pawn: [
+                Score::new(0, 0),
+                Score::new(5, 10),
+                Score::new(10, 20),
+                Score::new(15, 35),
+                Score::new(25, 60),
+                Score::new(40, 100),
+                Score::new(60, 150),
+                Score::new(0, 0),
+            ],
+            king_pawn_shield: Score::new(10, 0),
+            king_open_file: Score::new(-20, 0),
+            king_zone_attack: Score::new(-6, -1),
+            mobility: [
+                Score::new(0, 0),
+                Score::new(2, 4),
+                Score::new(4, 4),
+                Score::new(4, 5),
+                Score::new(1, 2),
+                Score::new(0, 0),
+            ],
+        }
+    }
+}
+
+/// Evaluation split into its terms, each one White's score minus Black's.
+#[derive(Debug, Default, Copy, Clone, PartialEq, Eq)]
+pub struct EvalBreakdown {
+    pub material: Score,
+    pub piece_squares: Score,
+    pub pawn_structure: Score,
+    pub king_safety: Score,
+    pub mobility: Score,
+    /// From 0 in a bare endgame to [`MAX_PHASE`] with all the pieces on the board.
+    pub phase: i32,
+}
+
+impl EvalBreakdown {
+    pub fn get_terms(&self) -> [(&'static str, Score); 5] {
+        [
+            ("Material", self.material),
+            ("Piece squares", self.piece_squares),
+            ("Pawn structure", self.pawn_structure),
+            ("King safety", self.king_safety),
+            ("Mobility", self.mobility),
+        ]
+    }
+    /// Sum of the terms tapered by the phase, in centipawns from White's view.
+    pub fn get_total(&self) -> i32 {
+        self.get_terms()
+            .iter()
+            .fold(Score::default(), |total, (_, score)| total + *score)
+            .taper(self.phase)
+    }
+}
+
+/// Table of the terms with their middlegame, endgame and tapered values.
+impl std::fmt::Display for EvalBreakdown {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        writeln!(f, "{:<16}{:>8}{:>8}{:>8}", "Term", "MG", "EG", "Total")?;
+        for (name, score) in self.get_terms() {
+            writeln!(
+                f,
+                "{:<16}{:>8}{:>8}{:>8}",
+                name,
+                score.mg,
+                score.eg,
+                score.taper(self.phase)
+            )?;
+        }
+        writeln!(f, "{:<16}{:>24}", "Phase", self.phase)?;
+        write!(f, "{:<16}{:>24}", "Total", self.get_total())
+    }
+}
+
+impl EvalParams {
+    /// Score of `position` in centipawns from the view of the side on move.
+    pub fn evaluate(&self, position: &Position) -> i32 {
+        let total = self.get_breakdown(position).get_total();
+        match position.get_side_on_move() {
+            Side::White => total,
+            Side::Black => -total,
+        }
+    }
+    pub fn get_breakdown(&self, position: &Position) -> EvalBreakdown {
+        let mut breakdown = EvalBreakdown::default();
+
+        for side in [Side::White, Side::Black] {
+            let mut own = EvalBreakdown::default();
+            for kind in PieceType::ALL {
+                for square in get_squares(position.get_pieces(kind, side)) {
+                    own.material += self.material[kind as usize];
+                    own.piece_squares +=
+                        self.piece_squares[kind as usize][get_relative_square(side, square)];
+                    breakdown.phase += PHASE_WEIGHTS[kind as usize];
+                }
+            }
+            own.pawn_structure = self.get_pawn_structure(position, side);
+            own.king_safety = self.get_king_safety(position, side);
+            own.mobility = self.get_mobility(position, side);
+
+            let sign = if side == Side::White { 1 } else { -1 };
+            breakdown.material += own.material * sign;
+            breakdown.piece_squares += own.piece_squares * sign;
+            breakdown.pawn_structure += own.pawn_structure * sign;
+            breakdown.king_safety += own.king_safety * sign;
+            breakdown.mobility += own.mobility * sign;
+        }
+        // Promotions can push the phase past the start position.
+        breakdown.phase = breakdown.phase.min(MAX_PHASE);
+
+        breakdown
+    }
+    fn get_pawn_structure(&self, position: &Position, side: Side) -> Score {
+        let pawns = position.get_pieces(PieceType::Pawn, side);
+        let enemy_pawns = position.get_pieces(PieceType::Pawn, side.opposite());
+        let mut score = Score::default();
+
+        for square in get_squares(pawns) {
+            let file = square % 8;
+            let ahead = get_squares_ahead(side, square);
+            let is_doubled = ahead & FILE_A << file & pawns != 0;
+            if is_doubled {
+                score += self.doubled_pawn;
+            }
+            if get_adjacent_files(file) & pawns == 0 {
+                score += self.isolated_pawn;
+            }
+            // Only the front pawn of a file can be passed.
+            let front_span = ahead & (get_adjacent_files(file) | FILE_A << file);
+            if !is_doubled && front_span & enemy_pawns == 0 {
+                score += self.passed_pawn[get_relative_square(side, square) / 8];
+            }
+        }
+
+        score
+    }
+    fn get_king_safety(&self, position: &Position, side: Side) -> Score {
+        let king = match position.get_king_square(side) {
+            Some(king) => king,
+            None => return Score::default(),
+        };
+        let pawns = position.get_pieces(PieceType::Pawn, side);
+        let files = get_adjacent_files(king % 8) | FILE_A << (king % 8);
+        let mut score = Score::default();
+
+        // The two ranks in front of the king.
+        let shield_ranks = match side {
+            Side::White => (RANK_1 << 8 | RANK_1 << 16) << (king / 8 * 8),
+            Side::Black => (RANK_8 >> 8 | RANK_8 >> 16) >> ((7 - king / 8) * 8),
+        };
+        score += self.king_pawn_shield * (pawns & files & shield_ranks).count_ones() as i32;
+
+        for file in [king % 8, king % 8 + 1, (king % 8).wrapping_sub(1)] {
+            if file < 8 && pawns & FILE_A << file == 0 {
+                score += self.king_open_file;
+            }
+        }
+
+        let zone = get_king_attacks(king);
+        let enemy = side.opposite();
+        let occupancy = position.get_occupancy();
+        for kind in [
+            PieceType::Knight,
+            PieceType::Bishop,
+            PieceType::Rook,
+            PieceType::Queen,
+        ] {
+            for square in get_squares(position.get_pieces(kind, enemy)) {
+                let attacks = get_piece_attacks(kind, square, occupancy);
+                score += self.king_zone_attack * (attacks & zone).count_ones() as i32;
+            }
+        }
+
+        score
+    }
+    fn get_mobility(&self, position: &Position, side: Side) -> Score {
+        let enemy_pawns = position.get_pieces(PieceType::Pawn, side.opposite());
+        let enemy_pawn_attacks = get_squares(enemy_pawns).fold(0, |attacks, square| {
+            attacks | get_pawn_attacks(side.opposite(), square)
+        });
+        let available = !position.get_side_pieces(side) & !enemy_pawn_attacks;
+        let occupancy = position.get_occupancy();
+        let mut score = Score::default();
+
+        for kind in [
+            PieceType::Knight,
+            PieceType::Bishop,
+            PieceType::Rook,
+            PieceType::Queen,
+        ] {
+            for square in get_squares(position.get_pieces(kind, side)) {
+                let attacks = get_piece_attacks(kind, square, occupancy);
+                score += self.mobility[kind as usize] * (attacks & available).count_ones() as i32;
+            }
+        }
+
+        score
+    }
+}
+
+/// Square seen from White's side, so tables only have to be written for White.
+fn get_relative_square(side: Side, square: Square) -> Square {
+    match side {
+        Side::White => square,
+        Side::Black => square ^ 56,
+    }
+}
+
+/// Squares in front of `square` from the view of `side`, on every file.
+fn get_squares_ahead(side: Side, square: Square) -> Bitboard {
+    let rank = square / 8;
+    match side {
+        Side::White if rank < 7 => !0 << ((rank + 1) * 8),
+        Side::Black => (1 << (rank * 8)) - 1,
+        _ => 0,
+    }
+}
+
+fn get_adjacent_files(file: usize) -> Bitboard {
+    let mut files = 0;
+    if file > 0 {
+        files |= FILE_A << (file - 1);
+    }
+    if file < 7 {
+        files |= FILE_A << (file + 1);
+    }
+
+    files
+}
+
+fn get_piece_attacks(kind: PieceType, square: Square, occupancy: Bitboard) -> Bitboard {
+    match kind {
+        PieceType::Knight => get_knight_attacks(square),
+        PieceType::Bishop => get_bishop_attacks(square, occupancy),
+        PieceType::Rook => get_rook_attacks(square, occupancy),
+        PieceType::Queen => get_queen_attacks(square, occupancy),
+        PieceType::King => get_king_attacks(square),
+        PieceType::Pawn => 0,
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum EvalConfigError {
+    Io(String),
+    /// A line that is neither `key = values`, more values of the key above
+    /// nor a comment.
+    InvalidLine {
+        line: usize,
+    },
+    InvalidNumber {
+        line: usize,
+        value: String,
+    },
+    UnknownKey {
+        line: usize,
+        key: String,
+    },
+    WrongValueCount {
+        line: usize,
+        key: String,
+        expected: usize,
+        found: usize,
+    },
+}
+
+impl std::fmt::Display for EvalConfigError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            EvalConfigError::Io(err) => write!(f, "{}", err),
+            EvalConfigError::InvalidLine { line } => {
+                write!(f, "line {}: expected 'key = values'", line)
+            }
+            EvalConfigError::InvalidNumber { line, value } => {
+                write!(f, "line {}: '{}' is not a whole number", line, value)
+            }
+            EvalConfigError::UnknownKey { line, key } => {
+                write!(f, "line {}: unknown key '{}'", line, key)
+            }
+            EvalConfigError::WrongValueCount {
+                line,
+                key,
+                expected,
+                found,
+            } => write!(
+                f,
+                "line {}: '{}' needs {} values, found {}",
+                line, key, expected, found
+            ),
+        }
+    }
+}
+
+impl std::error::Error for EvalConfigError {}
+
+/// Values of a config key: a middlegame and endgame pair, or a table.
+enum ConfigValue<'a> {
+    Score(&'a mut Score),
+    /// Middlegame or endgame values of a table, `true` for the middlegame.
+    Table(&'a mut [Score], bool),
+}
+
+impl EvalParams {
+    /// Weights read from the config file on `path`, see [`EvalParams::from_config`].
+    pub fn load(path: &str) -> Result<Self, EvalConfigError> {
+        let text =
+            std::fs::read_to_string(path).map_err(|err| EvalConfigError::Io(err.to_string()))?;
+        Self::from_config(&text)
+    }
+    /// Weights from a config in the format of [`EvalParams::to_config`]. Keys
+    /// that are left out keep their default value.
+    pub fn from_config(text: &str) -> Result<Self, EvalConfigError> {
+        let mut params = EvalParams::default();
+        // Key, the line it is on and its values, which may continue on the
+        // following lines.
+        let mut entries: Vec<(&str, usize, Vec<i32>)> = Vec::new();
+
+        for (index, line) in text.lines().enumerate() {
+            let line_number = index + 1;
+            let line = line.split('#').next().unwrap_or_default().trim();
+            if line.is_empty() {
+                continue;
+            }
+
+            let values = match line.split_once('=') {
+                Some((key, values)) => {
+                    entries.push((key.trim(), line_number, Vec::new()));
+                    values
+                }
+                None if !entries.is_empty() => line,
+                None => return Err(EvalConfigError::InvalidLine { line: line_number }),
+            };
+            let entry = entries.last_mut().expect("an entry was pushed");
+            for value in values.split_whitespace() {
+                entry
+                    .2
+                    .push(value.parse().map_err(|_| EvalConfigError::InvalidNumber {
+                        line: line_number,
+                        value: value.to_string(),
+                    })?);
+            }
+        }
+
+        for (key, line, values) in entries {
+            let unknown_key = || EvalConfigError::UnknownKey {
+                line,
+                key: key.to_string(),
+            };
+            let target = params.get_config_value(key).ok_or_else(unknown_key)?;
+            let expected = match &target {
+                ConfigValue::Score(_) => 2,
+                ConfigValue::Table(table, _) => table.len(),
+            };
+            if values.len() != expected {
+                return Err(EvalConfigError::WrongValueCount {
+                    line,
+                    key: key.to_string(),
+                    expected,
+                    found: values.len(),
+                });
+            }
+
+            match target {
+                ConfigValue::Score(score) => *score = Score::new(values[0], values[1]),
+                ConfigValue::Table(table, is_mg) => {
+                    for (index, value) in values.into_iter().enumerate() {
+                        // Piece-square tables are written like a board is drawn.
+                        let index = if table.len() == 64 {
+                            get_table_square(index)
+                        } else {
+                            index
+                        };
+                        if is_mg {
+                            table[index].mg = value;
+                        } else {
+                            table[index].eg = value;
+                        }
+                    }
+                }
+            }
+        }
+
+        Ok(params)
+    }
+    fn get_config_value(&mut self, key: &str) -> Option<ConfigValue<'_>> {
+        let mut parts = key.split('.');
+        let (group, name, phase) = (parts.next()?, parts.next()?, parts.next());
+        if parts.next().is_some() {
+            return None;
+        }
+        let piece = PIECE_NAMES.iter().position(|piece| *piece == name);
+        let table_phase = match phase {
+            Some("mg") => Some(true),
+            Some("eg") => Some(false),
+            _ => None,
+        };
+
+        let value = match (group, name, phase) {
+            ("material", _, None) => ConfigValue::Score(&mut self.material[piece?]),
+            ("mobility", _, None) => ConfigValue::Score(&mut self.mobility[piece?]),
+            ("pst", _, Some(_)) => {
+                ConfigValue::Table(&mut self.piece_squares[piece?], table_phase?)
+            }
+            ("pawns", "doubled", None) => ConfigValue::Score(&mut self.doubled_pawn),
+            ("pawns", "isolated", None) => ConfigValue::Score(&mut self.isolated_pawn),
+            ("pawns", "passed", Some(_)) => ConfigValue::Table(&mut self.passed_pawn, table_phase?),
+            ("king", "pawn_shield", None) => ConfigValue::Score(&mut self.king_pawn_shield),
+            ("king", "open_file", None) => ConfigValue::Score(&mut self.king_open_file),
+            ("king", "zone_attack", None) => ConfigValue::Score(&mut self.king_zone_attack),
+            _ => return None,
+        };
+
+        Some(value)
+    }
+    /// Config holding every weight, which [`EvalParams::from_config`] reads back.
+    /// Scores are a middlegame and an endgame value.
+    pub fn to_config(&self) -> String {
+        let mut config =
+            String::from("# Chessio evaluation weights: middlegame and endgame values.\n");
+        let score = |key: String, score: Score| format!("{} = {} {}\n", key, score.mg, score.eg);
+
+        config.push('\n');
+        for (kind, name) in PIECE_NAMES.iter().enumerate() {
+            config.push_str(&score(format!("material.{}", name), self.material[kind]));
+        }
+
+        config.push('\n');
+        for (kind, name) in PIECE_NAMES.iter().enumerate() {
+            config.push_str(&score(format!("mobility.{}", name), self.mobility[kind]));
+        }
+
+        config.push_str("\n# Piece-square tables of White, the 8th rank first.\n");
+        for (kind, name) in PIECE_NAMES.iter().enumerate() {
+            for phase in ["mg", "eg"] {
+                config.push_str(&format!("pst.{}.{} =\n", name, phase));
+                for rank in (0..8).rev() {
+                    let row: Vec<String> = self.piece_squares[kind][rank * 8..rank * 8 + 8]
+                        .iter()
+                        .map(|score| {
+                            let value = if phase == "mg" { score.mg } else { score.eg };
+                            format!("{:>4}", value)
+                        })
+                        .collect();
+                    config.push_str(&row.join(""));
+                    config.push('\n');
+                }
+            }
+        }
+
+        config.push('\n');
+        config.push_str(&score("pawns.doubled".to_string(), self.doubled_pawn));
+        config.push_str(&score("pawns.isolated".to_string(), self.isolated_pawn));
+        config.push_str("# Passed pawns by rank, the first rank first.\n");
+        for phase in ["mg", "eg"] {
+            let values: Vec<String> = self
+                .passed_pawn
+                .iter()
+                .map(|score| if phase == "mg" { score.mg } else { score.eg }.to_string())
+                .collect();
+            config.push_str(&format!("pawns.passed.{} = {}\n", phase, values.join(" ")));
+        }
+
+        config.push('\n');
+        config.push_str(&score(
+            "king.pawn_shield".to_string(),
+            self.king_pawn_shield,
+        ));
+        config.push_str(&score("king.open_file".to_string(), self.king_open_file));
+        config.push_str(&score(
+            "king.zone_attack".to_string(),
+            self.king_zone_attack,
+        ));
+
+        config
+    }
+}
@@ -0,0 +1,357 @@
+use super::{
+    constants::*,
+    piece::*,
+    tile::{get_tile_coords, get_tile_name, Tile},
+    Board,
+};
+
+pub const STARTING_POSITION_FEN: &str = "rnbqkbnr/pppppppp/8/8/8/8/PPPPPPPP/RNBQKBNR w KQkq - 0 1";
+
+/// Castling right letters in the order used by [`Board::get_castling_rights`].
+const CASTLING_RIGHTS_CHARS: [char; 4] = ['K', 'Q', 'k', 'q'];
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum FenError {
+    WrongFieldCount(usize),
+    WrongRowCount(usize),
+    WrongRowLength {
+        row: usize,
+        length: usize,
+    },
+    InvalidPiece(char),
+    InvalidSideOnMove(String),
+    InvalidCastlingRights(String),
+    InvalidEnPassantTarget(String),
+    InvalidHalfmoveClock(String),
+    InvalidFullmoveNumber(String),
+    WrongKingCount {
+        side: Side,
+        count: usize,
+    },
+    /// A pawn on the first or last row, where it can never stand.
+    PawnOnBackRow {
+        row: usize,
+    },
+    SideNotOnMoveInCheck,
+}
+
+impl std::fmt::Display for FenError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            FenError::WrongFieldCount(count) => write!(
+                f,
+                "expected 6 space separated fields (or 4 without the move counters), found {}",
+                count
+            ),
+            FenError::WrongRowCount(count) => {
+                write!(f, "piece placement has {} rows instead of 8", count)
+            }
+            FenError::WrongRowLength { row, length } => write!(
+                f,
+                "row {} of the piece placement describes {} tiles instead of 8",
+                row, length
+            ),
+            FenError::InvalidPiece(c) => write!(f, "'{}' is not a valid piece", c),
+            FenError::InvalidSideOnMove(side) => {
+                write!(f, "side on move must be 'w' or 'b', found '{}'", side)
+            }
+            FenError::InvalidCastlingRights(rights) => write!(
+                f,
+                "castling rights must be '-' or a combination of 'KQkq', found '{}'",
+                rights
+            ),
+            FenError::InvalidEnPassantTarget(target) => write!(
+                f,
+                "en passant target must be '-' or the tile a pawn has just skipped, found '{}'",
+                target
+            ),
+            FenError::InvalidHalfmoveClock(clock) => {
+                write!(f, "halfmove clock '{}' is not a number", clock)
+            }
+            FenError::InvalidFullmoveNumber(number) => {
+                write!(f, "fullmove number '{}' is not a positive number", number)
+            }
+            FenError::WrongKingCount { side, count } => {
+                write!(f, "{:?} has {} kings instead of 1", side, count)
+            }
+            FenError::PawnOnBackRow { row } => write!(f, "row {} has a pawn on it", row),
+            FenError::SideNotOnMoveInCheck => write!(f, "the side not on move is in check"),
+        }
+    }
+}
+
+impl std::error::Error for FenError {}
+
+impl Board {
+    pub fn from_fen(fen: &str) -> Result<Self, FenError> {
+        let mut board = Board::new();
+        board.load_fen(fen)?;
+
+        Ok(board)
+    }
+    /// Replaces the whole game with the position described by `fen`. The board
+    /// is left untouched when the FEN is invalid.
+    pub fn load_fen(&mut self, fen: &str) -> Result<(), FenError> {
+        let fields: Vec<&str> = fen.split_whitespace().collect();
+        if fields.len() != 6 && fields.len() != 4 {
+            return Err(FenError::WrongFieldCount(fields.len()));
+        }
+
+        let mut tiles = Self::parse_fen_pieces(fields[0])?;
+
+        let side_on_move = match fields[1] {
+            "w" => Side::White,
+            "b" => Side::Black,
+            side => return Err(FenError::InvalidSideOnMove(side.to_string())),
+        };
+
+        let castling_rights = Self::parse_fen_castling_rights(fields[2])?;
+
+        let en_passant_target = match fields[3] {
+            "-" => None,
+            target => match get_tile_coords(target) {
+                Some(coords) if Self::is_en_passant_target_valid(&tiles, side_on_move, coords) => {
+                    Some(coords)
+                }
+                _ => return Err(FenError::InvalidEnPassantTarget(target.to_string())),
+            },
+        };
+
+        let (halfmove_clock, fullmove_number) = if fields.len() == 6 {
+            let halfmove_clock = fields[4]
+                .parse::<u32>()
+                .map_err(|_| FenError::InvalidHalfmoveClock(fields[4].to_string()))?;
+            let fullmove_number = match fields[5].parse::<u32>() {
+                Ok(number) if number > 0 => number,
+                _ => return Err(FenError::InvalidFullmoveNumber(fields[5].to_string())),
+            };
+            (halfmove_clock, fullmove_number)
+        } else {
+            (0, 1)
+        };
+
+        for side in [Side::White, Side::Black] {
+            let count = tiles
+                .iter()
+                .flatten()
+                .filter_map(|tile| tile.piece)
+                .filter(|p| p.kind == PieceType::King && p.side == side)
+                .count();
+            if count != 1 {
+                return Err(FenError::WrongKingCount { side, count });
+            }
+        }
+
+        // Every castling right needs its king and rook on their home tiles.
+        for (index, has_right) in castling_rights.iter().enumerate() {
+            let (side, home_row) = if index < 2 {
+                (Side::White, CHESSBOARD_SIZE - 1)
+            } else {
+                (Side::Black, 0)
+            };
+            let rook_x = if index % 2 == 0 { 7 } else { 0 };
+            let is_home = |x: usize, kind: PieceType| {
+                tiles[home_row][x]
+                    .piece
+                    .is_some_and(|piece| piece.kind == kind && piece.side == side)
+            };
+            if *has_right && !(is_home(4, PieceType::King) && is_home(rook_x, PieceType::Rook)) {
+                return Err(FenError::InvalidCastlingRights(fields[2].to_string()));
+            }
+        }
+
+        // Only pieces that still have all their options get `did_move: false`:
+        // pawns on their starting row, and kings and rooks that can still castle.
+        for (y, row) in tiles.iter_mut().enumerate() {
+            for (x, tile) in row.iter_mut().enumerate() {
+                if let Some(piece) = tile.piece.as_mut() {
+                    let home_row = if piece.side == Side::White {
+                        CHESSBOARD_SIZE - 1
+                    } else {
+                        0
+                    };
+                    let rights_offset = if piece.side == Side::White { 0 } else { 2 };
+
+                    piece.did_move = match piece.kind {
+                        PieceType::Pawn => {
+                            y != if piece.side == Side::White {
+                                CHESSBOARD_SIZE - 2
+                            } else {
+                                1
+                            }
+                        }
+                        PieceType::King => {
+                            !(y == home_row
+                                && x == 4
+                                && (castling_rights[rights_offset]
+                                    || castling_rights[rights_offset + 1]))
+                        }
+                        PieceType::Rook => {
+                            !(y == home_row
+                                && ((x == 7 && castling_rights[rights_offset])
+                                    || (x == 0 && castling_rights[rights_offset + 1])))
+                        }
+                        _ => true,
+                    };
+                }
+            }
+        }
+
+        let mut board = Board {
+            tiles,
+            move_records: Vec::new(),
+            is_check: None,
+            result: None,
+            side_on_move,
+            en_passant_target,
+            halfmove_clock,
+            fullmove_number,
+            position_history: Vec::new(),
+            starting_fen: String::new(),
+            undo_states: Vec::new(),
+        };
+
+        if board.is_check(side_on_move.opposite()) {
+            return Err(FenError::SideNotOnMoveInCheck);
+        }
+
+        board.starting_fen = board.to_fen();
+        board.position_history.push(board.get_hash());
+        board.update_result();
+        *self = board;
+
+        Ok(())
+    }
+    fn parse_fen_pieces(
+        placement: &str,
+    ) -> Result<[[Tile; CHESSBOARD_SIZE]; CHESSBOARD_SIZE], FenError> {
+        let mut tiles = [[Tile::new(); CHESSBOARD_SIZE]; CHESSBOARD_SIZE];
+
+        let rows: Vec<&str> = placement.split('/').collect();
+        if rows.len() != CHESSBOARD_SIZE {
+            return Err(FenError::WrongRowCount(rows.len()));
+        }
+
+        for (y, row) in rows.iter().enumerate() {
+            let mut x = 0;
+            for c in row.chars() {
+                if let Some(empty_tiles) = c.to_digit(10).filter(|n| (1..=8).contains(n)) {
+                    x += empty_tiles as usize;
+                    continue;
+                }
+
+                let piece = Piece::from_fen_char(c).ok_or(FenError::InvalidPiece(c))?;
+                if piece.kind == PieceType::Pawn && (y == 0 || y == CHESSBOARD_SIZE - 1) {
+                    return Err(FenError::PawnOnBackRow {
+                        row: CHESSBOARD_SIZE - y,
+                    });
+                }
+                if x < CHESSBOARD_SIZE {
+                    tiles[y][x].piece = Some(piece);
+                }
+                x += 1;
+            }
+
+            if x != CHESSBOARD_SIZE {
+                return Err(FenError::WrongRowLength {
+                    row: CHESSBOARD_SIZE - y,
+                    length: x,
+                });
+            }
+        }
+
+        Ok(tiles)
+    }
+    /// Whether a pawn of the side not on move can have just advanced two tiles
+    /// over `(x, y)`: the tile and the one the pawn came from are empty and the
+    /// pawn stands in front of them.
+    fn is_en_passant_target_valid(
+        tiles: &[[Tile; CHESSBOARD_SIZE]; CHESSBOARD_SIZE],
+        side_on_move: Side,
+        (x, y): (usize, usize),
+    ) -> bool {
+        // Rows of the pawn, the skipped tile and the tile the pawn came from.
+        let (pawn_row, target_row, start_row) = match side_on_move {
+            Side::White => (3, 2, 1),
+            Side::Black => (4, 5, 6),
+        };
+
+        y == target_row
+            && tiles[target_row][x].piece.is_none()
+            && tiles[start_row][x].piece.is_none()
+            && matches!(
+                tiles[pawn_row][x].piece,
+                Some(piece) if piece.kind == PieceType::Pawn && piece.side != side_on_move
+            )
+    }
+    fn parse_fen_castling_rights(rights: &str) -> Result<[bool; 4], FenError> {
+        let mut castling_rights = [false; 4];
+        if rights == "-" {
+            return Ok(castling_rights);
+        }
+
+        for c in rights.chars() {
+            match CASTLING_RIGHTS_CHARS.iter().position(|right| *right == c) {
+                Some(index) if !castling_rights[index] => castling_rights[index] = true,
+                _ => return Err(FenError::InvalidCastlingRights(rights.to_string())),
+            }
+        }
+
+        Ok(castling_rights)
+    }
+    pub fn to_fen(&self) -> String {
+        let mut placement = String::new();
+        for (y, row) in self.tiles.iter().enumerate() {
+            let mut empty_tiles = 0;
+            for tile in row {
+                match tile.piece {
+                    Some(piece) => {
+                        if empty_tiles > 0 {
+                            placement.push_str(&empty_tiles.to_string());
+                            empty_tiles = 0;
+                        }
+                        placement.push(piece.to_fen_char());
+                    }
+                    None => empty_tiles += 1,
+                }
+            }
+            if empty_tiles > 0 {
+                placement.push_str(&empty_tiles.to_string());
+            }
+            if y < CHESSBOARD_SIZE - 1 {
+                placement.push('/');
+            }
+        }
+
+        let side_on_move = match self.get_side_on_move() {
+            Side::White => "w",
+            Side::Black => "b",
+        };
+
+        let mut castling_rights: String = self
+            .get_castling_rights()
+            .iter()
+            .zip(CASTLING_RIGHTS_CHARS)
+            .filter(|(right, _)| **right)
+            .map(|(_, c)| c)
+            .collect();
+        if castling_rights.is_empty() {
+            castling_rights.push('-');
+        }
+
+        let en_passant_target = match self.get_en_passant_target() {
+            Some(coords) => get_tile_name(coords),
+            None => "-".to_string(),
+        };
+
+        format!(
+            "{} {} {} {} {} {}",
+            placement,
+            side_on_move,
+            castling_rights,
+            en_passant_target,
+            self.halfmove_clock,
+            self.fullmove_number
+        )
+    }
+}
@@ -1,161 +1,121 @@
+pub mod bitboard;
+pub mod clock;
 pub mod constants;
+pub mod evaluation;
+pub mod fen;
+pub mod moves;
+pub mod network;
+pub mod perft;
+pub mod pgn;
 pub mod piece;
+pub mod position;
+pub mod result;
+pub mod san;
+pub mod search;
 pub mod tile;
-use std::collections::HashMap;
+pub mod uci;
+pub mod uci_client;
+pub mod zobrist;
 
+use bitboard::get_square;
 use constants::*;
+use moves::Move;
 use piece::*;
-use raylib::prelude::*;
+use result::*;
 use tile::*;
 
-type PiecesImagesType = HashMap<(PieceType, Side), Texture2D>;
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+pub enum CastlingSide {
+    KingSide,
+    QueenSide,
+}
+
+impl CastlingSide {
+    /// Column of the rook taking part in the castle and the column it ends up on.
+    pub fn rook_columns(&self) -> (usize, usize) {
+        match self {
+            CastlingSide::KingSide => (7, 5),
+            CastlingSide::QueenSide => (0, 3),
+        }
+    }
+}
 
+#[derive(Debug, Clone)]
 pub struct GameMoveRecord {
     pub kind: PieceType,
     pub side: Side,
     pub from: (usize, usize),
     pub to: (usize, usize),
     pub taken_piece: Option<PieceType>,
+    pub castle: Option<CastlingSide>,
+    pub en_passant: bool,
+    pub promotion: Option<PieceType>,
+    /// The move in Standard Algebraic Notation.
+    pub san: String,
+    /// Zobrist hash of the position after the move.
+    pub hash: u64,
 }
 
-pub struct Game {
+/// Part of the board state a [`GameMoveRecord`] is not enough to restore.
+#[derive(Debug, Copy, Clone)]
+struct UndoState {
+    tiles: [[Tile; CHESSBOARD_SIZE]; CHESSBOARD_SIZE],
+    is_check: Option<Side>,
+    result: Option<GameResult>,
+    en_passant_target: Option<(usize, usize)>,
+    halfmove_clock: u32,
+}
+
+#[derive(Debug, Clone)]
+pub struct Board {
     pub tiles: [[Tile; CHESSBOARD_SIZE]; CHESSBOARD_SIZE],
-    pub pieces_images: PiecesImagesType,
-    pub hovered_piece_coords: Option<(usize, usize)>,
     pub move_records: Vec<GameMoveRecord>,
     pub is_check: Option<Side>,
-    pub victor: Option<Side>,
+    pub result: Option<GameResult>,
+    pub side_on_move: Side,
+    /// Tile a pawn can move to in order to capture the pawn that has just
+    /// advanced two tiles, if the last move was such an advance.
+    pub en_passant_target: Option<(usize, usize)>,
+    /// Number of moves since the last capture or pawn move.
+    pub halfmove_clock: u32,
+    /// Starts at 1 and is incremented after every move of Black.
+    pub fullmove_number: u32,
+    /// Zobrist hash of every position reached in the game, the current one
+    /// included.
+    pub position_history: Vec<u64>,
+    /// FEN of the position the game started from, `move_records` replay from it.
+    pub starting_fen: String,
+    /// State before each of `move_records`, used to take the moves back.
+    undo_states: Vec<UndoState>,
 }
 
-impl Game {
-    pub fn new(
-        rl: &mut RaylibHandle,
-        thread: &RaylibThread,
-        custom_imgs: Option<PiecesImagesType>,
-        custom_tile_color_schema: Option<TileColorSchema>,
-    ) -> Self {
-        let mut tiles = [[Tile::new(); CHESSBOARD_SIZE]; CHESSBOARD_SIZE];
-
-        let color_schema = custom_tile_color_schema.unwrap_or(DEFAULT_TILE_COLOR_SCHEMA);
-        for y in 0..CHESSBOARD_SIZE {
-            for x in 0..CHESSBOARD_SIZE {
-                if (x + y % 2) % 2 == 0 {
-                    tiles[y][x].color = color_schema.0;
-                } else {
-                    tiles[y][x].color = color_schema.1;
-                }
-            }
-        }
+impl Default for Board {
+    fn default() -> Self {
+        Self::new()
+    }
+}
 
-        let mut game = Game {
-            tiles,
-            pieces_images: custom_imgs.unwrap_or(getDefaultPieceImages(rl, thread)),
-            hovered_piece_coords: None,
+impl Board {
+    pub fn new() -> Self {
+        let mut board = Board {
+            tiles: [[Tile::new(); CHESSBOARD_SIZE]; CHESSBOARD_SIZE],
             move_records: Vec::new(),
             is_check: None,
-            victor: None,
+            result: None,
+            side_on_move: Side::White,
+            en_passant_target: None,
+            halfmove_clock: 0,
+            fullmove_number: 1,
+            position_history: Vec::new(),
+            starting_fen: fen::STARTING_POSITION_FEN.to_string(),
+            undo_states: Vec::new(),
         };
-        game.reset();
-
-        game
-    }
-    pub fn render(&self, d: &mut RaylibDrawHandle) {
-        let size = Self::get_tile_actuall_size() as usize;
+        board.reset();
 
-        for (x, y, _) in self.tiles_iter() {
-            d.draw_rectangle(
-                LEFT_SIDE_PADDING + (x * size) as i32,
-                (y * size) as i32,
-                size as i32,
-                size as i32,
-                self.tiles[y][x].bg.unwrap_or(self.tiles[y][x].color),
-            );
-        }
-    }
-    fn get_tile_actuall_size() -> i32 {
-        CHESSBOARD_WIDTH / (CHESSBOARD_SIZE as i32)
+        board
     }
     pub fn get_side_on_move(&self) -> Side {
-        match self.move_records.last() {
-            Some(mr) => {
-                if mr.side == Side::White {
-                    Side::Black
-                } else {
-                    Side::White
-                }
-            }
-            _ => Side::White,
-        }
-    }
-    //TODO:
-    pub fn render_available_moves(&mut self, d: &mut RaylibDrawHandle) {
-        match self.hovered_piece_coords {
-            Some(coords) => {
-                let tile_size = CHESSBOARD_WIDTH / (CHESSBOARD_SIZE as i32);
-
-                match self.is_check {
-                    Some(_) => {
-                        let moves = self.get_piece_available_moves_with_check((
-                            coords.0 as i32,
-                            coords.1 as i32,
-                        ));
-                        for mov in moves {
-                            d.draw_circle(
-                                LEFT_SIDE_PADDING + (mov.0 as i32) * tile_size + tile_size / 2,
-                                (mov.1 as i32) * tile_size + tile_size / 2,
-                                10.0,
-                                Color::GRAY,
-                            );
-                        }
-                    }
-                    None => {
-                        let moves =
-                            self.get_piece_available_moves((coords.0 as i32, coords.1 as i32));
-
-                        for mov in moves {
-                            let board_copy = self.tiles.clone();
-                            self.tiles[mov.1][mov.0].piece = self.tiles[coords.1][coords.0].piece;
-                            self.tiles[coords.1][coords.0].piece = None;
-                            if self.is_check(self.get_side_on_move()) {
-                                self.tiles = board_copy;
-                                continue;
-                            }
-                            self.tiles = board_copy;
-
-                            d.draw_circle(
-                                LEFT_SIDE_PADDING + (mov.0 as i32) * tile_size + tile_size / 2,
-                                (mov.1 as i32) * tile_size + tile_size / 2,
-                                10.0,
-                                Color::GRAY,
-                            );
-                        }
-                    }
-                }
-            }
-            _ => {}
-        }
-    }
-    pub fn render_piece_at_coords(
-        &mut self,
-        d: &mut RaylibDrawHandle,
-        (piece, side): (PieceType, Side),
-        (x, y): (f32, f32),
-    ) {
-        let piece_img = self.pieces_images.get(&(piece, side));
-        let tile_size = Self::get_tile_actuall_size();
-        match piece_img {
-            Some(img) => d.draw_texture_ex(
-                img,
-                Vector2 {
-                    x: x - (tile_size / 2) as f32,
-                    y: y - (tile_size / 2) as f32,
-                },
-                0.0,
-                1.0,
-                Color::WHITE,
-            ),
-            None => {}
-        }
+        self.side_on_move
     }
     pub fn reset(&mut self) {
         use PieceType::*;
@@ -163,6 +123,18 @@ impl Game {
             [Rook, Knight, Bishop, Queen, King, Bishop, Knight, Rook];
         let frontrow: [PieceType; CHESSBOARD_SIZE] = [Pawn; CHESSBOARD_SIZE];
 
+        self.tiles = [[Tile::new(); CHESSBOARD_SIZE]; CHESSBOARD_SIZE];
+        self.move_records.clear();
+        self.is_check = None;
+        self.result = None;
+        self.side_on_move = Side::White;
+        self.en_passant_target = None;
+        self.halfmove_clock = 0;
+        self.fullmove_number = 1;
+        self.position_history.clear();
+        self.starting_fen = fen::STARTING_POSITION_FEN.to_string();
+        self.undo_states.clear();
+
         for (index, piece) in backrow.iter().enumerate() {
             self.tiles[0][index].piece = Some(Piece::new(*piece, Side::Black));
         }
@@ -178,555 +150,460 @@ impl Game {
         for (index, piece) in backrow.iter().enumerate() {
             self.tiles[CHESSBOARD_SIZE - 1][index].piece = Some(Piece::new(*piece, Side::White));
         }
-    }
-    pub fn get_tile_on_coords_mut(
-        &mut self,
-        (x, y): (f32, f32),
-    ) -> Option<(&mut Tile, (usize, usize))> {
-        let tile_x = (x / Self::get_tile_actuall_size() as f32) as i32;
-        let tile_y = (y / Self::get_tile_actuall_size() as f32) as i32;
-
-        if tile_x >= 0
-            && tile_x < CHESSBOARD_SIZE as i32
-            && tile_y >= 0
-            && tile_y < CHESSBOARD_SIZE as i32
-        {
-            return Some((
-                &mut self.tiles[tile_y as usize][tile_x as usize],
-                (tile_x as usize, tile_y as usize),
-            ));
-        }
 
-        None
+        self.position_history.push(self.get_hash());
     }
-    pub fn get_tile_on_coords(&self, (x, y): (f32, f32)) -> Option<(&Tile, (usize, usize))> {
-        let tile_x = (x / Self::get_tile_actuall_size() as f32) as i32;
-        let tile_y = (y / Self::get_tile_actuall_size() as f32) as i32;
-
-        if Self::is_tile_in_board((tile_x, tile_y)) {
-            return Some((
-                &self.tiles[tile_y as usize][tile_x as usize],
-                (tile_x as usize, tile_y as usize),
-            ));
-        }
-
-        None
-    }
-    //TODO:
-    pub fn highlight_tile_by_coords(&mut self, (x, y): (f32, f32)) {
-        let tile = self.get_tile_on_coords_mut((x, y));
-
-        match tile {
-            Some(t) => {
-                t.0.bg = Some(Color::RED);
-            }
-            None => {}
-        }
-    }
-    //TODO:
-    pub fn start_drag_event(&mut self, (x, y): (f32, f32)) {
-        let tile = self.get_tile_on_coords((x, y));
-
-        let t = if let Some(tile) = tile {
-            tile
+    /// Moves the piece on `from` to `to` if it is a legal move for the side on move.
+    /// A pawn reaching the last row needs a `promotion` piece from [`PROMOTION_PIECES`],
+    /// for every other move it has to be `None`. Returns whether the move was played.
+    pub fn move_piece(
+        &mut self,
+        from: (usize, usize),
+        to: (usize, usize),
+        promotion: Option<PieceType>,
+    ) -> bool {
+        let moved_piece = if let Some(p) = self.tiles[from.1][from.0].piece {
+            p
         } else {
-            self.hovered_piece_coords = None;
-            return;
+            return false;
         };
 
-        let piece = if let Some(piece) = t.0.piece {
-            piece
-        } else {
-            self.hovered_piece_coords = None;
-            return;
-        };
+        if self.result.is_some() || moved_piece.side != self.get_side_on_move() {
+            return false;
+        }
 
-        let last_move = self.move_records.last();
-        match last_move {
-            Some(lm) => {
-                if piece.side != lm.side {
-                    self.hovered_piece_coords = Some(t.1);
-                } else {
-                    self.hovered_piece_coords = None;
-                }
-            }
-            None => {
-                if piece.side == Side::White {
-                    self.hovered_piece_coords = Some(t.1)
-                } else {
-                    self.hovered_piece_coords = None;
-                }
+        match promotion {
+            Some(kind)
+                if !self.is_promotion_move(from, to) || !PROMOTION_PIECES.contains(&kind) =>
+            {
+                return false
             }
+            None if self.is_promotion_move(from, to) => return false,
+            _ => {}
         }
-    }
 
-    pub fn end_drag_event(&mut self, (x, y): (f32, f32)) {
-        let starting_tile = if let Some(coords) = self.hovered_piece_coords {
-            (self.tiles[coords.1][coords.0].piece, coords)
-        } else {
-            return;
-        };
+        let available_moves =
+            self.get_piece_available_moves_with_check((from.0 as i32, from.1 as i32));
+        if !available_moves.contains(&to) {
+            return false;
+        }
 
-        let piece = if let Some(p) = starting_tile.0 {
-            p
-        } else {
-            return;
+        let en_passant = self.is_en_passant(from, to);
+        let move_record = GameMoveRecord {
+            from,
+            to,
+            kind: moved_piece.kind,
+            side: moved_piece.side,
+            taken_piece: if en_passant {
+                Some(PieceType::Pawn)
+            } else {
+                self.tiles[to.1][to.0].piece.map(|p| p.kind)
+            },
+            castle: Self::get_castling_side(moved_piece, from, to),
+            en_passant,
+            promotion,
+            san: san::get_san_without_suffix(self, from, to, promotion),
+            // Known once the move is made.
+            hash: 0,
         };
 
-        let tile_to_drop = self.get_tile_on_coords((x, y));
-        let to = if let Some(tile) = tile_to_drop {
-            tile.1
+        self.undo_states.push(UndoState {
+            tiles: self.tiles,
+            is_check: self.is_check,
+            result: self.result,
+            en_passant_target: self.en_passant_target,
+            halfmove_clock: self.halfmove_clock,
+        });
+        self.apply_move_to_tiles(from, to, promotion);
+
+        if move_record.kind == PieceType::Pawn || move_record.taken_piece.is_some() {
+            self.halfmove_clock = 0;
         } else {
-            return;
-        };
+            self.halfmove_clock += 1;
+        }
 
-        let available_moves =
-            self.get_piece_available_moves((starting_tile.1 .0 as i32, starting_tile.1 .1 as i32));
-        if available_moves.contains(&to) {
-            let taken_piece_type: Option<PieceType> = if let Some((t, _)) = tile_to_drop {
-                match t.piece {
-                    Some(p) => Some(p.kind),
-                    None => None,
-                }
+        self.en_passant_target =
+            if move_record.kind == PieceType::Pawn && from.1.abs_diff(to.1) == 2 {
+                Some((to.0, (from.1 + to.1) / 2))
             } else {
                 None
             };
+        if move_record.side == Side::Black {
+            self.fullmove_number += 1;
+        }
+        self.side_on_move = move_record.side.opposite();
 
-            let mut moved_piece = if let Some(p) = starting_tile.0 {
-                p
-            } else {
-                return;
-            };
-
-            let move_record = GameMoveRecord {
-                from: (starting_tile.1 .0, starting_tile.1 .0),
-                to,
-                kind: piece.kind,
-                side: piece.side,
-                taken_piece: taken_piece_type,
-            };
-
-            let sides = if move_record.side == Side::Black {
-                (Side::White, Side::Black)
-            } else {
-                (Side::Black, Side::White)
-            };
+        let hash = self.get_hash();
+        self.position_history.push(hash);
+        self.update_result();
 
-            let board_copy = self.tiles.clone();
-            self.tiles[to.1][to.0].piece = Some(moved_piece);
-            self.tiles[starting_tile.1 .1][starting_tile.1 .0].piece = None;
-            if self.is_check(sides.1) {
-                self.tiles = board_copy;
-                self.hovered_piece_coords = None;
-                return;
-            }
+        let mut move_record = move_record;
+        move_record.san.push_str(san::get_san_suffix(self));
+        move_record.hash = hash;
+        self.move_records.push(move_record);
 
-            moved_piece.did_move = true;
-            self.move_records.push(move_record);
-
-            if self.is_check(sides.0) {
-                self.is_check = Some(sides.0);
-
-                let mut can_continue_playing = false;
-                'a: for y in 0..self.tiles.len() {
-                    for x in 0..self.tiles.len() {
-                        match self.tiles[y][x].piece {
-                            Some(piece) => {
-                                if piece.side == sides.0 {
-                                    let moves = self
-                                        .get_piece_available_moves_with_check((x as i32, y as i32));
-                                    if moves.len() > 0 {
-                                        can_continue_playing = true;
-                                        break 'a;
-                                    }
-                                }
-                            }
-                            _ => {}
-                        }
-                    }
-                }
-                if !can_continue_playing {
-                    self.victor = Some(sides.0);
-                }
-            } else {
-                self.is_check = None;
-            }
+        true
+    }
+    /// Plays a [`Move`] of the side on move. Returns whether it was legal.
+    pub fn play_move(&mut self, mv: Move) -> bool {
+        let (from, to) = mv.get_coords();
+        self.move_piece(from, to, mv.get_promotion())
+    }
+    /// Takes back the last move and returns its record, the position is restored
+    /// exactly as it was before the move.
+    pub fn undo_move(&mut self) -> Option<GameMoveRecord> {
+        let undo_state = self.undo_states.pop()?;
+        let move_record = self.move_records.pop()?;
+
+        self.tiles = undo_state.tiles;
+        self.is_check = undo_state.is_check;
+        self.result = undo_state.result;
+        self.en_passant_target = undo_state.en_passant_target;
+        self.halfmove_clock = undo_state.halfmove_clock;
+        self.side_on_move = move_record.side;
+        if move_record.side == Side::Black {
+            self.fullmove_number -= 1;
         }
+        self.position_history.pop();
 
-        self.hovered_piece_coords = None;
+        Some(move_record)
     }
-    fn get_pieces_linear_moves<'a>(
-        &'a self,
-        available_moves: &'a mut Vec<(usize, usize)>,
-        (temp_x, temp_y): (i32, i32),
-        (move_x, move_y): (i32, i32),
-        piece_side: Side,
+    /// Takes back moves onto `redo_moves` until a side that `is_skipped` does
+    /// not hold for is on move, so that the replies of a computer opponent are
+    /// taken back together with the move they answered.
+    pub fn undo_turn(
+        &mut self,
+        redo_moves: &mut Vec<GameMoveRecord>,
+        is_skipped: impl Fn(Side) -> bool,
     ) {
-        if Self::is_tile_in_board((temp_x as i32, temp_y as i32)) {
-            let p = self.is_piece_on_coords((temp_x, temp_y));
-            match p.1 {
-                Some(side) => {
-                    if side != piece_side {
-                        available_moves.push((temp_x as usize, temp_y as usize));
-                    }
-                }
-                None => {
-                    available_moves.push((temp_x as usize, temp_y as usize));
-                    Self::get_pieces_linear_moves(
-                        self,
-                        available_moves,
-                        ((temp_x + move_x), (temp_y + move_y)),
-                        (move_x, move_y),
-                        piece_side,
-                    )
-                }
+        while let Some(record) = self.undo_move() {
+            redo_moves.push(record);
+            if !is_skipped(self.side_on_move) {
+                break;
             }
         }
     }
-    pub fn is_check(&self, check_for: Side) -> bool {
-        let mut king_coords: Option<(usize, usize)> = None;
-
-        for (x, y, tile) in self.tiles_iter() {
-            let piece = if let Some(p) = tile.piece {
-                p
-            } else {
-                continue;
-            };
-            if piece.kind == PieceType::King && piece.side == check_for {
-                king_coords = Some((x, y));
+    /// Plays the last moves of `redo_moves` again, the same way
+    /// [`Board::undo_turn`] took them back. A move that is not legal anymore
+    /// throws away all of them. Returns whether the moves were played.
+    pub fn redo_turn(
+        &mut self,
+        redo_moves: &mut Vec<GameMoveRecord>,
+        is_skipped: impl Fn(Side) -> bool,
+    ) -> bool {
+        while let Some(record) = redo_moves.pop() {
+            if !self.move_piece(record.from, record.to, record.promotion) {
+                redo_moves.clear();
+                return false;
+            }
+            if !is_skipped(self.side_on_move) {
+                break;
             }
         }
 
-        let king_coords = if let Some(coords) = king_coords {
-            coords
+        true
+    }
+    /// Updates `is_check` and `result` for the side that is now on move.
+    fn update_result(&mut self) {
+        let side = self.get_side_on_move();
+        self.is_check = if self.is_check(side) {
+            Some(side)
         } else {
-            return false;
+            None
         };
 
+        self.result = if !self.has_legal_moves() {
+            match self.is_check {
+                Some(_) => Some(GameResult::Checkmate {
+                    winner: side.opposite(),
+                }),
+                None => Some(GameResult::Stalemate),
+            }
+        } else if self.is_insufficient_material() {
+            Some(GameResult::InsufficientMaterial)
+        } else if self.halfmove_clock >= 150 {
+            Some(GameResult::SeventyFiveMoveRule)
+        } else if self.get_repetition_count() >= 5 {
+            Some(GameResult::FivefoldRepetition)
+        } else {
+            None
+        };
+    }
+    /// Every legal `(from, to)` move of the side on move. Pawns reaching the last
+    /// row are listed once and still need a promotion piece to be played.
+    pub fn get_legal_moves(&self) -> Vec<((usize, usize), (usize, usize))> {
+        let mut legal_moves: Vec<((usize, usize), (usize, usize))> = self
+            .get_position()
+            .legal_moves()
+            .iter()
+            .map(Move::get_coords)
+            .collect();
+        // Promotions come one after another, once for every piece.
+        legal_moves.dedup();
+
+        legal_moves
+    }
+    fn has_legal_moves(&self) -> bool {
+        !self.get_position().legal_moves().is_empty()
+    }
+    /// Returns whether neither side has enough pieces left to ever give checkmate.
+    pub fn is_insufficient_material(&self) -> bool {
+        !self.has_mating_material(Side::White) && !self.has_mating_material(Side::Black)
+    }
+    /// Returns whether `side` could checkmate with some series of legal moves,
+    /// as FIDE Article 6.9 asks when the other side runs out of time. A lone
+    /// minor piece can when the other side has anything to block its own king
+    /// with, bishops cannot when they all stand on tiles of one color.
+    pub fn has_mating_material(&self, side: Side) -> bool {
+        let mut own_pieces = Vec::new();
+        let mut other_pieces = Vec::new();
         for (x, y, tile) in self.tiles_iter() {
-            if let Some(p) = tile.piece {
-                if p.side == check_for {
-                    continue;
-                }
-            } else {
-                continue;
-            };
-
-            if self
-                .get_piece_available_moves((x as i32, y as i32))
-                .contains(&(king_coords.0, king_coords.1))
-            {
-                return true;
+            match tile.piece {
+                Some(piece) if piece.kind == PieceType::King => {}
+                Some(piece) if piece.side == side => own_pieces.push((piece.kind, (x + y) % 2)),
+                Some(piece) => other_pieces.push((piece.kind, (x + y) % 2)),
+                None => {}
             }
         }
 
-        false
-    }
-    fn get_distance_between_direct_coords(
-        (x1, y1): (usize, usize),
-        (x2, y2): (usize, usize),
-    ) -> i64 {
-        let a: i64 = (x2 as i64) - (x1 as i64);
-        let b: i64 = (y2 as i64) - (y1 as i64);
-        let c = ((a.pow(2) + b.pow(2)) as f64) / f64::sqrt(2.);
-        if c.fract() != 0.0 {
-            return c as i64;
+        if own_pieces
+            .iter()
+            .any(|(kind, _)| matches!(kind, PieceType::Pawn | PieceType::Rook | PieceType::Queen))
+        {
+            return true;
         }
-        c as i64
-    }
-    pub fn get_piece_available_moves(&self, (x, y): (i32, i32)) -> Vec<(usize, usize)> {
-        let mut available_moves: Vec<(usize, usize)> = Vec::new();
 
-        if !Self::is_tile_in_board((x, y)) {
-            return available_moves;
-        }
+        // Only minor pieces are left on this side.
+        let mut pieces = own_pieces.iter().chain(&other_pieces);
+        let bishops_color = match pieces.next() {
+            Some((PieceType::Bishop, color)) => Some(*color),
+            _ => None,
+        };
+        let bishops_of_one_color = bishops_color.is_some_and(|bishops_color| {
+            pieces.all(|(kind, color)| *kind == PieceType::Bishop && *color == bishops_color)
+        });
 
-        let piece = self.tiles[y as usize][x as usize].piece;
+        !own_pieces.is_empty()
+            && !bishops_of_one_color
+            && (own_pieces.len() > 1 || !other_pieces.is_empty())
+    }
+    /// Pieces `side` has captured in the game, the least valuable first. A
+    /// promotion is not a capture, so a promoted piece only appears here once
+    /// it is captured itself.
+    pub fn get_captured_pieces(&self, side: Side) -> Vec<PieceType> {
+        let mut captured: Vec<PieceType> = self
+            .move_records
+            .iter()
+            .filter(|record| record.side == side)
+            .filter_map(|record| record.taken_piece)
+            .collect();
+        captured.sort_by_key(|kind| (kind.get_value(), *kind == PieceType::Bishop));
+
+        captured
+    }
+    /// Value of White's pieces on the board minus the value of Black's, in
+    /// pawns.
+    pub fn get_material_balance(&self) -> i32 {
+        self.tiles_iter()
+            .filter_map(|(_, _, tile)| tile.piece)
+            .map(|piece| match piece.side {
+                Side::White => piece.kind.get_value(),
+                Side::Black => -piece.kind.get_value(),
+            })
+            .sum()
+    }
+    /// Ends the game with `side` out of time. It is a draw when the other side
+    /// has no mating material. Returns whether the game was still going.
+    pub fn lose_on_time(&mut self, side: Side) -> bool {
+        if self.result.is_some() {
+            return false;
+        }
 
-        let piece: Piece = if let Some(piece) = piece {
-            piece
+        self.result = if self.has_mating_material(side.opposite()) {
+            Some(GameResult::Timeout {
+                winner: side.opposite(),
+            })
         } else {
-            return available_moves;
+            Some(GameResult::TimeoutVsInsufficientMaterial)
         };
+        true
+    }
+    /// Number of times the current position has appeared in the game.
+    pub fn get_repetition_count(&self) -> usize {
+        match self.position_history.last() {
+            Some(current) => self
+                .position_history
+                .iter()
+                .filter(|key| *key == current)
+                .count(),
+            None => 0,
+        }
+    }
+    /// Draw that the side on move is allowed to claim, if there is one.
+    pub fn get_claimable_draw(&self) -> Option<GameResult> {
+        if self.result.is_some() {
+            return None;
+        }
 
-        match piece.kind {
-            PieceType::Pawn => match piece.side {
-                Side::Black => {
-                    if Self::is_tile_in_board((x, y + 1)) && !self.is_piece_on_coords((x, y + 1)).0
-                    {
-                        available_moves.push((x as usize, (y + 1) as usize));
-
-                        if piece.did_move == false
-                            && Self::is_tile_in_board((x, y + 2))
-                            && !self.is_piece_on_coords((x, y + 2)).0
-                        {
-                            available_moves.push((x as usize, (y + 2) as usize));
-                        }
-                    }
-
-                    let piece_on_coords = if Self::is_tile_in_board((x - 1, y + 1)) {
-                        self.is_piece_on_coords((x - 1, y + 1))
-                    } else {
-                        (false, None)
-                    };
-
-                    match piece_on_coords.1 {
-                        Some(p) => {
-                            if Self::is_tile_in_board((x - 1, y + 1))
-                                && self.is_piece_on_coords((x - 1, y + 1)).0
-                                && piece.side != p
-                            {
-                                available_moves.push(((x - 1) as usize, (y + 1) as usize))
-                            }
-                        }
-                        None => {}
-                    }
-
-                    let piece_on_coords = if Self::is_tile_in_board((x + 1, y + 1)) {
-                        self.is_piece_on_coords((x + 1, y + 1))
-                    } else {
-                        (false, None)
-                    };
-
-                    match piece_on_coords.1 {
-                        Some(p) => {
-                            if Self::is_tile_in_board((x + 1, y + 1))
-                                && self.is_piece_on_coords((x + 1, y + 1)).0
-                                && piece.side != p
-                            {
-                                available_moves.push(((x + 1) as usize, (y + 1) as usize))
-                            }
-                        }
-                        None => {}
-                    }
-                }
-                Side::White => {
-                    if Self::is_tile_in_board((x, y - 1)) && !self.is_piece_on_coords((x, y - 1)).0
-                    {
-                        available_moves.push((x as usize, (y - 1) as usize));
-
-                        if piece.did_move == false
-                            && Self::is_tile_in_board((x, y - 2))
-                            && !self.is_piece_on_coords((x, y - 2)).0
-                        {
-                            available_moves.push((x as usize, (y - 2) as usize));
-                        }
-                    }
-
-                    let piece_on_coords = if Self::is_tile_in_board((x - 1, y - 1)) {
-                        self.is_piece_on_coords((x - 1, y - 1))
-                    } else {
-                        (false, None)
-                    };
-
-                    match piece_on_coords.1 {
-                        Some(p) => {
-                            if Self::is_tile_in_board((x - 1, y - 1))
-                                && self.is_piece_on_coords((x - 1, y - 1)).0
-                                && piece.side != p
-                            {
-                                available_moves.push(((x - 1) as usize, (y - 1) as usize))
-                            }
-                        }
-                        None => {}
-                    }
-
-                    let piece_on_coords = if Self::is_tile_in_board((x + 1, y - 1)) {
-                        self.is_piece_on_coords((x + 1, y - 1))
-                    } else {
-                        (false, None)
-                    };
-
-                    match piece_on_coords.1 {
-                        Some(p) => {
-                            if Self::is_tile_in_board((x + 1, y - 1))
-                                && self.is_piece_on_coords((x + 1, y - 1)).0
-                                && piece.side != p
-                            {
-                                available_moves.push(((x + 1) as usize, (y - 1) as usize))
-                            }
-                        }
-                        None => {}
-                    }
-                }
-            },
-            PieceType::Rook => {
-                let moves = [
-                    ((x + 1, y), (1, 0)),
-                    ((x - 1, y), (-1, 0)),
-                    ((x, y + 1), (0, 1)),
-                    ((x, y - 1), (0, -1)),
-                ];
-
-                for (start, multiplier) in moves {
-                    self.get_pieces_linear_moves(
-                        &mut available_moves,
-                        start,
-                        multiplier,
-                        piece.side,
-                    );
-                }
-            }
-            PieceType::Bishop => {
-                let moves = [
-                    ((x + 1, y + 1), (1, 1)),
-                    ((x + 1, y - 1), (1, -1)),
-                    ((x - 1, y + 1), (-1, 1)),
-                    ((x - 1, y - 1), (-1, -1)),
-                ];
-
-                for (start, multiplier) in moves {
-                    self.get_pieces_linear_moves(
-                        &mut available_moves,
-                        start,
-                        multiplier,
-                        piece.side,
-                    );
-                }
-            }
-            PieceType::Queen => {
-                let moves = [
-                    ((x + 1, y + 1), (1, 1)),
-                    ((x + 1, y - 1), (1, -1)),
-                    ((x - 1, y + 1), (-1, 1)),
-                    ((x - 1, y - 1), (-1, -1)),
-                    ((x + 1, y), (1, 0)),
-                    ((x - 1, y), (-1, 0)),
-                    ((x, y + 1), (0, 1)),
-                    ((x, y - 1), (0, -1)),
-                ];
-
-                for (start, multiplier) in moves {
-                    self.get_pieces_linear_moves(
-                        &mut available_moves,
-                        start,
-                        multiplier,
-                        piece.side,
-                    );
-                }
-            }
-            PieceType::Knight => {
-                let coords = [
-                    (x - 1, y - 2),
-                    (x + 1, y - 2),
-                    (x + 2, y - 1),
-                    (x + 2, y + 1),
-                    (x + 2, y + 1),
-                    (x + 1, y + 2),
-                    (x - 1, y + 2),
-                    (x - 2, y + 1),
-                    (x - 2, y - 1),
-                ];
-                for coords in coords {
-                    if Self::is_tile_in_board(coords)
-                        && self.is_piece_on_coords(coords).1 != Some(piece.side)
-                    {
-                        available_moves.push((coords.0 as usize, coords.1 as usize));
-                    }
-                }
-            }
-            PieceType::King => {
-                let coords_around_king = [
-                    (x + 1, y),
-                    (x + 1, y + 1),
-                    (x, y + 1),
-                    (x - 1, y + 1),
-                    (x - 1, y),
-                    (x - 1, y - 1),
-                    (x + 1, y),
-                    (x, y - 1),
-                    (x + 1, y - 1),
-                ];
-                let mut enemy_king_coords = None;
-
-                for (king_x, king_y, tile) in self.tiles_iter() {
-                    let piece = if let Some(p) = tile.piece {
-                        p
-                    } else {
-                        continue;
-                    };
-
-                    if piece.kind == PieceType::King
-                        && piece.side
-                            == if piece.side == Side::White {
-                                Side::Black
-                            } else {
-                                Side::White
-                            }
-                    {
-                        enemy_king_coords = Some((king_x, king_y));
-                        break;
-                    }
-                }
-
-                match enemy_king_coords {
-                    Some(enemy_king_coords) => {
-                        for coords in coords_around_king {
-                            if Self::is_tile_in_board(coords)
-                                && Self::get_distance_between_direct_coords(
-                                    (coords.0 as usize, coords.1 as usize),
-                                    enemy_king_coords,
-                                ) > 1
-                                && self.is_piece_on_coords(coords).1 != Some(piece.side)
-                            {
-                                available_moves.push((coords.0 as usize, coords.1 as usize));
-                            }
-                        }
-                    }
-                    None => {}
-                }
+        if self.halfmove_clock >= 100 {
+            Some(GameResult::FiftyMoveRule)
+        } else if self.get_repetition_count() >= 3 {
+            Some(GameResult::ThreefoldRepetition)
+        } else {
+            None
+        }
+    }
+    /// Ends the game with a claimable draw. Returns whether there was one to claim.
+    pub fn claim_draw(&mut self) -> bool {
+        match self.get_claimable_draw() {
+            Some(draw) => {
+                self.result = Some(draw);
+                true
             }
+            None => false,
         }
+    }
+    pub fn get_castling_rights(&self) -> [bool; 4] {
+        let can_castle = |side: Side, castling_side: CastlingSide| {
+            let row = if side == Side::Black {
+                0
+            } else {
+                CHESSBOARD_SIZE - 1
+            };
+            let is_unmoved = |x: usize, kind: PieceType| match self.tiles[row][x].piece {
+                Some(p) => p.kind == kind && p.side == side && !p.did_move,
+                None => false,
+            };
 
-        available_moves
+            is_unmoved(4, PieceType::King)
+                && is_unmoved(castling_side.rook_columns().0, PieceType::Rook)
+        };
+
+        [
+            can_castle(Side::White, CastlingSide::KingSide),
+            can_castle(Side::White, CastlingSide::QueenSide),
+            can_castle(Side::Black, CastlingSide::KingSide),
+            can_castle(Side::Black, CastlingSide::QueenSide),
+        ]
+    }
+    /// Zobrist hash of the position. Positions that are the same for the
+    /// repetition rules have the same hash.
+    pub fn get_hash(&self) -> u64 {
+        self.get_position().get_hash()
     }
-    pub fn get_piece_available_moves_with_check(
+    /// Moves the piece on `from` to `to` without any validation, including the
+    /// rook when the move is a castle.
+    fn apply_move_to_tiles(
         &mut self,
-        (x, y): (i32, i32),
-    ) -> Vec<(usize, usize)> {
-        let mut available_moves = Vec::new();
-        let moves = self.get_piece_available_moves((x, y));
-
-        for (move_x, move_y) in moves {
-            let board_copy = self.tiles.clone();
-            self.tiles[move_y][move_x].piece = self.tiles[y as usize][x as usize].piece;
-            self.tiles[y as usize][x as usize].piece = None;
-            if !self.is_check(self.get_side_on_move()) {
-                available_moves.push((move_x, move_y));
+        from: (usize, usize),
+        to: (usize, usize),
+        promotion: Option<PieceType>,
+    ) {
+        let mut moved_piece = if let Some(p) = self.tiles[from.1][from.0].piece {
+            p
+        } else {
+            return;
+        };
+
+        if self.is_en_passant(from, to) {
+            self.tiles[from.1][to.0].piece = None;
+        }
+
+        if let Some(castling_side) = Self::get_castling_side(moved_piece, from, to) {
+            let (rook_from, rook_to) = castling_side.rook_columns();
+            let mut rook = self.tiles[from.1][rook_from].piece.take();
+            if let Some(rook) = rook.as_mut() {
+                rook.did_move = true;
             }
-            self.tiles = board_copy;
+            self.tiles[from.1][rook_to].piece = rook;
         }
 
-        available_moves
+        moved_piece.did_move = true;
+        if let Some(kind) = promotion {
+            moved_piece.kind = kind;
+        }
+        self.tiles[to.1][to.0].piece = Some(moved_piece);
+        self.tiles[from.1][from.0].piece = None;
+    }
+    pub fn get_en_passant_target(&self) -> Option<(usize, usize)> {
+        self.en_passant_target
+    }
+    /// Returns whether moving the piece on `from` to `to` is a pawn reaching the last row.
+    pub fn is_promotion_move(&self, from: (usize, usize), to: (usize, usize)) -> bool {
+        match self.tiles[from.1][from.0].piece {
+            Some(piece) => {
+                let last_row = if piece.side == Side::Black {
+                    CHESSBOARD_SIZE - 1
+                } else {
+                    0
+                };
+                piece.kind == PieceType::Pawn && to.1 == last_row
+            }
+            None => false,
+        }
+    }
+    fn is_en_passant(&self, from: (usize, usize), to: (usize, usize)) -> bool {
+        match self.tiles[from.1][from.0].piece {
+            Some(piece) => {
+                piece.kind == PieceType::Pawn
+                    && from.0 != to.0
+                    && self.tiles[to.1][to.0].piece.is_none()
+            }
+            None => false,
+        }
     }
-    fn is_piece_on_coords(&self, (x, y): (i32, i32)) -> (bool, Option<Side>) {
-        let piece = self.tiles[y as usize][x as usize].piece;
+    fn get_castling_side(
+        piece: Piece,
+        from: (usize, usize),
+        to: (usize, usize),
+    ) -> Option<CastlingSide> {
+        if piece.kind != PieceType::King || from.1 != to.1 {
+            return None;
+        }
 
-        match piece {
-            Some(piece) => (true, Some(piece.side)),
-            None => (false, None),
+        match to.0 as i32 - from.0 as i32 {
+            2 => Some(CastlingSide::KingSide),
+            -2 => Some(CastlingSide::QueenSide),
+            _ => None,
         }
     }
-    fn is_tile_in_board((x, y): (i32, i32)) -> bool {
-        return x >= 0 && y >= 0 && x < CHESSBOARD_SIZE as i32 && y < CHESSBOARD_SIZE as i32;
+    pub fn is_check(&self, check_for: Side) -> bool {
+        self.get_position().is_side_in_check(check_for)
     }
+    /// Legal moves of the piece on `(x, y)`, empty when it is not on move.
+    pub fn get_piece_available_moves_with_check(&self, (x, y): (i32, i32)) -> Vec<(usize, usize)> {
+        if !Self::is_tile_in_board((x, y)) {
+            return Vec::new();
+        }
+
+        let from = get_square((x as usize, y as usize));
+        let mut available_moves: Vec<(usize, usize)> = self
+            .get_position()
+            .legal_moves()
+            .iter()
+            .filter(|mv| mv.get_from() == from)
+            .map(|mv| mv.get_coords().1)
+            .collect();
+        available_moves.dedup();
 
-    pub fn tiles_iter(&self) -> TilesIter {
-        let iter = TilesIter {
+        available_moves
+    }
+    pub fn is_tile_in_board((x, y): (i32, i32)) -> bool {
+        x >= 0 && y >= 0 && x < CHESSBOARD_SIZE as i32 && y < CHESSBOARD_SIZE as i32
+    }
+
+    pub fn tiles_iter(&self) -> TilesIter<'_> {
+        TilesIter {
             index_x: 0,
             index_y: 0,
             tiles: &self.tiles,
-        };
-
-        return iter;
+        }
     }
-    pub fn tiles_iter_mut(&mut self) -> TilesIterMut {
-        let iter = TilesIterMut {
+    pub fn tiles_iter_mut(&mut self) -> TilesIterMut<'_> {
+        TilesIterMut {
             index_x: 0,
             index_y: 0,
             tiles: &mut self.tiles,
-        };
-
-        return iter;
+        }
     }
 }
@@ -0,0 +1,123 @@
+use super::{
+    bitboard::{get_square_coords, Square},
+    piece::PieceType,
+    tile::{get_tile_coords, get_tile_name},
+};
+
+/// Move given by the coordinates of its tiles: from, to and the promotion
+/// piece.
+pub type TileMove = ((usize, usize), (usize, usize), Option<PieceType>);
+
+/// Move packed into 16 bits: 6 bits for the from square, 6 for the to square
+/// and 4 bits of flags telling what kind of move it is.
+#[derive(Debug, Copy, Clone, PartialEq, Eq, Hash)]
+pub struct Move(u16);
+
+impl Move {
+    pub const QUIET: u16 = 0;
+    pub const DOUBLE_PUSH: u16 = 1;
+    pub const KING_CASTLE: u16 = 2;
+    pub const QUEEN_CASTLE: u16 = 3;
+    pub const CAPTURE: u16 = 4;
+    pub const EN_PASSANT: u16 = 5;
+    /// Set on promotions, the two lowest bits then pick the piece.
+    pub const PROMOTION: u16 = 8;
+
+    /// Promotion pieces in the order of their flag bits.
+    const PROMOTION_KINDS: [PieceType; 4] = [
+        PieceType::Knight,
+        PieceType::Bishop,
+        PieceType::Rook,
+        PieceType::Queen,
+    ];
+
+    pub fn new(from: Square, to: Square, flags: u16) -> Self {
+        Move(from as u16 | (to as u16) << 6 | flags << 12)
+    }
+    /// `kind` must be a knight, bishop, rook or queen.
+    pub fn new_promotion(from: Square, to: Square, kind: PieceType, is_capture: bool) -> Self {
+        let piece_bits = Self::PROMOTION_KINDS
+            .iter()
+            .position(|promotion| *promotion == kind)
+            .unwrap_or(3) as u16;
+        let capture_bits = if is_capture { Self::CAPTURE } else { 0 };
+
+        Self::new(from, to, Self::PROMOTION | capture_bits | piece_bits)
+    }
+    pub fn get_from(&self) -> Square {
+        (self.0 & 0x3f) as Square
+    }
+    pub fn get_to(&self) -> Square {
+        (self.0 >> 6 & 0x3f) as Square
+    }
+    pub fn get_flags(&self) -> u16 {
+        self.0 >> 12
+    }
+    /// From and to as tile coordinates of a [`Board`](super::Board).
+    pub fn get_coords(&self) -> ((usize, usize), (usize, usize)) {
+        (
+            get_square_coords(self.get_from()),
+            get_square_coords(self.get_to()),
+        )
+    }
+    pub fn get_promotion(&self) -> Option<PieceType> {
+        if self.get_flags() & Self::PROMOTION == 0 {
+            return None;
+        }
+
+        Some(Self::PROMOTION_KINDS[(self.get_flags() & 3) as usize])
+    }
+    pub fn is_capture(&self) -> bool {
+        self.get_flags() & Self::CAPTURE != 0
+    }
+    pub fn is_castle(&self) -> bool {
+        matches!(self.get_flags(), Self::KING_CASTLE | Self::QUEEN_CASTLE)
+    }
+    pub fn is_en_passant(&self) -> bool {
+        self.get_flags() == Self::EN_PASSANT
+    }
+    pub fn is_double_push(&self) -> bool {
+        self.get_flags() == Self::DOUBLE_PUSH
+    }
+}
+
+/// Long algebraic notation as used by UCI, e.g. `e2e4` or `e7e8q`.
+impl std::fmt::Display for Move {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let (from, to) = self.get_coords();
+        write!(f, "{}", get_uci_move_name(from, to, self.get_promotion()))
+    }
+}
+
+/// Move from `from` to `to` in UCI notation, e.g. `e7e8q`.
+pub fn get_uci_move_name(
+    from: (usize, usize),
+    to: (usize, usize),
+    promotion: Option<PieceType>,
+) -> String {
+    let mut mv = format!("{}{}", get_tile_name(from), get_tile_name(to));
+    if let Some(kind) = promotion {
+        mv.push(kind.to_char().to_ascii_lowercase());
+    }
+
+    mv
+}
+
+/// From, to and the promotion piece of a move in UCI notation.
+pub fn parse_uci_move(mv: &str) -> Option<TileMove> {
+    let from = get_tile_coords(mv.get(0..2)?)?;
+    let to = get_tile_coords(mv.get(2..4)?)?;
+    let promotion = match mv.get(4..)? {
+        "" => None,
+        kind => {
+            let mut chars = kind.chars();
+            let kind = PieceType::from_char(chars.next()?.to_ascii_uppercase())?;
+            if chars.next().is_some() || matches!(kind, PieceType::Pawn | PieceType::King) {
+                return None;
+            }
+            Some(kind)
+        }
+    };
+
+    Some((from, to, promotion))
+}
@@ -0,0 +1,564 @@
+use std::{
+    io::{BufRead, BufReader, Write},
+    net::{Shutdown, TcpListener, TcpStream, ToSocketAddrs},
+    sync::mpsc::{self, Receiver, RecvTimeoutError, TryRecvError},
+    thread,
+    time::Duration,
+};
+
+use super::{
+    moves::{get_uci_move_name, parse_uci_move},
+    piece::{PieceType, Side},
+    result::GameResult,
+    Board,
+};
+
+/// Version of the message protocol, both players need the same one.
+pub const PROTOCOL_VERSION: u32 = 2;
+pub const DEFAULT_PORT: u16 = 7878;
+
+/// How long the other player has to answer while connecting.
+const HANDSHAKE_TIMEOUT: Duration = Duration::from_secs(10);
+
+/// One line sent between the two players.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Message {
+    /// First message of both players: `hello <version>`.
+    Hello {
+        version: u32,
+    },
+    /// Game the host sends after the greeting, with the side the receiver
+    /// plays: `game <side> <fen> [moves <move>...]`.
+    Game {
+        side: Side,
+        fen: String,
+        moves: Vec<String>,
+    },
+    /// Move in UCI notation: `move <move>`.
+    Move(String),
+    /// Move of the receiver that arrived after the game had ended, it was not
+    /// played: `stale <move>`.
+    Stale(String),
+    Resign,
+    /// The given side ran out of time: `flag <side>`.
+    Flag(Side),
+    DrawOffer,
+    DrawAccept,
+    DrawDecline,
+    TakebackRequest,
+    /// Takeback to the given number of moves: `takeback accept <moves>`.
+    TakebackAccept {
+        moves: usize,
+    },
+    TakebackDecline,
+}
+
+fn get_side_name(side: Side) -> &'static str {
+    match side {
+        Side::White => "white",
+        Side::Black => "black",
+    }
+}
+
+impl Message {
+    pub fn parse(line: &str) -> Option<Self> {
+        let tokens: Vec<&str> = line.split_whitespace().collect();
+        let message = match tokens.as_slice() {
+            ["hello", version] => Message::Hello {
+                version: version.parse().ok()?,
+            },
+            ["game", side, rest @ ..] => {
+                let side = match *side {
+                    "white" => Side::White,
+                    "black" => Side::Black,
+                    _ => return None,
+                };
+                let moves_index = rest
+                    .iter()
+                    .position(|token| *token == "moves")
+                    .unwrap_or(rest.len());
+                if moves_index == 0 {
+                    return None;
+                }
+                Message::Game {
+                    side,
+                    fen: rest[..moves_index].join(" "),
+                    moves: rest
+                        .iter()
+                        .skip(moves_index + 1)
+                        .map(|mv| mv.to_string())
+                        .collect(),
+                }
+            }
+            ["move", mv] => Message::Move(mv.to_string()),
+            ["stale", mv] => Message::Stale(mv.to_string()),
+            ["resign"] => Message::Resign,
+            ["flag", "white"] => Message::Flag(Side::White),
+            ["flag", "black"] => Message::Flag(Side::Black),
+            ["draw", "offer"] => Message::DrawOffer,
+            ["draw", "accept"] => Message::DrawAccept,
+            ["draw", "decline"] => Message::DrawDecline,
+            ["takeback", "request"] => Message::TakebackRequest,
+            ["takeback", "accept", moves] => Message::TakebackAccept {
+                moves: moves.parse().ok()?,
+            },
+            ["takeback", "decline"] => Message::TakebackDecline,
+            _ => return None,
+        };
+
+        Some(message)
+    }
+}
+
+impl std::fmt::Display for Message {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Message::Hello { version } => write!(f, "hello {}", version),
+            Message::Game { side, fen, moves } => {
+                write!(f, "game {} {}", get_side_name(*side), fen)?;
+                if !moves.is_empty() {
+                    write!(f, " moves {}", moves.join(" "))?;
+                }
+                Ok(())
+            }
+            Message::Move(mv) => write!(f, "move {}", mv),
+            Message::Stale(mv) => write!(f, "stale {}", mv),
+            Message::Resign => write!(f, "resign"),
+            Message::Flag(side) => write!(f, "flag {}", get_side_name(*side)),
+            Message::DrawOffer => write!(f, "draw offer"),
+            Message::DrawAccept => write!(f, "draw accept"),
+            Message::DrawDecline => write!(f, "draw decline"),
+            Message::TakebackRequest => write!(f, "takeback request"),
+            Message::TakebackAccept { moves } => write!(f, "takeback accept {}", moves),
+            Message::TakebackDecline => write!(f, "takeback decline"),
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum NetworkError {
+    Io(String),
+    /// The other player closed the connection.
+    Disconnected,
+    /// The other player did not answer while connecting.
+    Timeout,
+    /// The other player speaks a different version of the protocol.
+    VersionMismatch {
+        theirs: u32,
+    },
+    /// A line that is not a message of the protocol.
+    InvalidMessage(String),
+    /// A message that does not fit the state of the game.
+    UnexpectedMessage(String),
+    /// The other player sent a move that is not legal.
+    IllegalMove(String),
+}
+
+impl std::fmt::Display for NetworkError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            NetworkError::Io(err) => write!(f, "{}", err),
+            NetworkError::Disconnected => write!(f, "the other player has disconnected"),
+            NetworkError::Timeout => write!(f, "the other player did not answer"),
+            NetworkError::VersionMismatch { theirs } => write!(
+                f,
+                "the other player uses protocol version {}, this is version {}",
+                theirs, PROTOCOL_VERSION
+            ),
+            NetworkError::InvalidMessage(line) => write!(f, "'{}' is not a valid message", line),
+            NetworkError::UnexpectedMessage(line) => write!(f, "unexpected message '{}'", line),
+            NetworkError::IllegalMove(mv) => {
+                write!(f, "the other player played '{}', which is not legal", mv)
+            }
+        }
+    }
+}
+
+impl std::error::Error for NetworkError {}
+
+impl From<std::io::Error> for NetworkError {
+    fn from(err: std::io::Error) -> Self {
+        NetworkError::Io(err.to_string())
+    }
+}
+
+/// Something the other player did, already applied to the board.
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+pub enum PeerEvent {
+    Moved,
+    /// The last move of this player reached the other one after the game
+    /// had ended, it is taken back.
+    IgnoredMove,
+    Resigned,
+    /// A side ran out of time on the clock of the other player.
+    Flagged,
+    OfferedDraw,
+    AcceptedDraw,
+    DeclinedDraw,
+    RequestedTakeback,
+    AcceptedTakeback,
+    DeclinedTakeback,
+}
+
+/// Draw offer or takeback request waiting for an answer.
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+pub enum Offer {
+    Draw,
+    Takeback,
+}
+
+/// Connection to the other player of a game over TCP. Both players keep their
+/// own [`Board`] and check every move of the other one against it.
+pub struct Peer {
+    /// Side this player plays.
+    pub side: Side,
+    stream: TcpStream,
+    lines: Receiver<String>,
+    /// Offer of this player the other one has not answered yet.
+    sent_offer: Option<Offer>,
+    /// Offer of the other player waiting for an answer.
+    received_offer: Option<Offer>,
+}
+
+impl Peer {
+    fn new(stream: TcpStream, side: Side) -> Result<Self, NetworkError> {
+        stream.set_nodelay(true)?;
+        let reader = BufReader::new(stream.try_clone()?);
+        let (sender, lines) = mpsc::channel();
+        thread::spawn(move || {
+            for line in reader.lines().map_while(Result::ok) {
+                if sender.send(line).is_err() {
+                    break;
+                }
+            }
+        });
+
+        Ok(Peer {
+            side,
+            stream,
+            lines,
+            sent_offer: None,
+            received_offer: None,
+        })
+    }
+    /// Waits for a player to connect to `listener` and sends them the game on
+    /// `board`. The host plays `side`.
+    pub fn host(listener: &TcpListener, side: Side, board: &Board) -> Result<Self, NetworkError> {
+        let (stream, _) = listener.accept()?;
+        let mut peer = Peer::new(stream, side)?;
+        peer.greet()?;
+        peer.send(&Message::Game {
+            side: side.opposite(),
+            fen: board.starting_fen.clone(),
+            moves: board
+                .move_records
+                .iter()
+                .map(|record| get_uci_move_name(record.from, record.to, record.promotion))
+                .collect(),
+        })?;
+
+        Ok(peer)
+    }
+    /// Connects to a hosted game and returns it with the board it is played on.
+    pub fn join(address: impl ToSocketAddrs) -> Result<(Self, Board), NetworkError> {
+        let stream = TcpStream::connect(address)?;
+        let mut peer = Peer::new(stream, Side::White)?;
+        peer.greet()?;
+
+        let line = peer.receive_blocking()?;
+        let (side, fen, moves) = match Message::parse(&line) {
+            Some(Message::Game { side, fen, moves }) => (side, fen, moves),
+            Some(_) => return Err(NetworkError::UnexpectedMessage(line)),
+            None => return Err(NetworkError::InvalidMessage(line)),
+        };
+        let mut board = Board::from_fen(&fen).map_err(|_| NetworkError::InvalidMessage(line))?;
+        for mv in moves {
+            let is_legal = match parse_uci_move(&mv) {
+                Some((from, to, promotion)) => board.move_piece(from, to, promotion),
+                None => false,
+            };
+            if !is_legal {
+                return Err(NetworkError::IllegalMove(mv));
+            }
+        }
+
+        peer.side = side;
+        Ok((peer, board))
+    }
+    /// Exchanges `hello` and checks both players speak the same protocol.
+    fn greet(&mut self) -> Result<(), NetworkError> {
+        self.send(&Message::Hello {
+            version: PROTOCOL_VERSION,
+        })?;
+
+        let line = self.receive_blocking()?;
+        match Message::parse(&line) {
+            Some(Message::Hello { version }) if version == PROTOCOL_VERSION => Ok(()),
+            Some(Message::Hello { version }) => {
+                Err(NetworkError::VersionMismatch { theirs: version })
+            }
+            Some(_) => Err(NetworkError::UnexpectedMessage(line)),
+            None => Err(NetworkError::InvalidMessage(line)),
+        }
+    }
+    fn receive_blocking(&mut self) -> Result<String, NetworkError> {
+        self.lines
+            .recv_timeout(HANDSHAKE_TIMEOUT)
+            .map_err(|err| match err {
+                RecvTimeoutError::Timeout => NetworkError::Timeout,
+                RecvTimeoutError::Disconnected => NetworkError::Disconnected,
+            })
+    }
+    fn send(&mut self, message: &Message) -> Result<(), NetworkError> {
+        writeln!(self.stream, "{}", message)?;
+        self.stream.flush()?;
+        Ok(())
+    }
+    pub fn get_sent_offer(&self) -> Option<Offer> {
+        self.sent_offer
+    }
+    pub fn get_received_offer(&self) -> Option<Offer> {
+        self.received_offer
+    }
+    /// Plays a move of this player and sends it to the other one. Returns
+    /// whether the move was legal. No moves can be made while a takeback
+    /// request is waiting for an answer.
+    pub fn play_move(
+        &mut self,
+        board: &mut Board,
+        from: (usize, usize),
+        to: (usize, usize),
+        promotion: Option<PieceType>,
+    ) -> Result<bool, NetworkError> {
+        if board.get_side_on_move() != self.side
+            || self.sent_offer == Some(Offer::Takeback)
+            || !board.move_piece(from, to, promotion)
+        {
+            return Ok(false);
+        }
+
+        self.send(&Message::Move(get_uci_move_name(from, to, promotion)))?;
+        Ok(true)
+    }
+    pub fn resign(&mut self, board: &mut Board) -> Result<(), NetworkError> {
+        if board.result.is_some() {
+            return Ok(());
+        }
+
+        board.result = Some(GameResult::Resignation {
+            winner: self.side.opposite(),
+        });
+        self.send(&Message::Resign)
+    }
+    /// Ends the game when `side` ran out of time and tells the other player.
+    /// Returns whether the game ended.
+    pub fn lose_on_time(&mut self, board: &mut Board, side: Side) -> Result<bool, NetworkError> {
+        if !board.lose_on_time(side) {
+            return Ok(false);
+        }
+
+        self.send(&Message::Flag(side))?;
+        Ok(true)
+    }
+    /// Sends an offer, unless there is already one in either direction.
+    fn send_offer(&mut self, board: &Board, offer: Offer) -> Result<bool, NetworkError> {
+        if board.result.is_some() || self.sent_offer.is_some() || self.received_offer.is_some() {
+            return Ok(false);
+        }
+
+        self.send(&match offer {
+            Offer::Draw => Message::DrawOffer,
+            Offer::Takeback => Message::TakebackRequest,
+        })?;
+        self.sent_offer = Some(offer);
+        Ok(true)
+    }
+    /// Returns whether the offer was sent.
+    pub fn offer_draw(&mut self, board: &Board) -> Result<bool, NetworkError> {
+        self.send_offer(board, Offer::Draw)
+    }
+    /// Asks to take back the last move of this player. Returns whether the
+    /// request was sent.
+    pub fn request_takeback(&mut self, board: &Board) -> Result<bool, NetworkError> {
+        if get_takeback_move_count(board, self.side).is_none() {
+            return Ok(false);
+        }
+
+        self.send_offer(board, Offer::Takeback)
+    }
+    /// Accepts or declines the offer of the other player, if there is one.
+    pub fn answer_offer(&mut self, board: &mut Board, accept: bool) -> Result<(), NetworkError> {
+        let offer = match self.received_offer.take() {
+            Some(offer) => offer,
+            None => return Ok(()),
+        };
+
+        let message = match (offer, accept) {
+            (Offer::Draw, true) if board.result.is_none() => {
+                board.result = Some(GameResult::DrawAgreement);
+                Message::DrawAccept
+            }
+            (Offer::Takeback, true) => match get_takeback_move_count(board, self.side.opposite()) {
+                Some(moves) => {
+                    take_back_to(board, moves);
+                    Message::TakebackAccept { moves }
+                }
+                None => Message::TakebackDecline,
+            },
+            (Offer::Draw, _) => Message::DrawDecline,
+            (Offer::Takeback, _) => Message::TakebackDecline,
+        };
+        self.send(&message)
+    }
+    /// Applies the next message of the other player to `board`, if one has
+    /// arrived.
+    pub fn poll(&mut self, board: &mut Board) -> Result<Option<PeerEvent>, NetworkError> {
+        let line = match self.lines.try_recv() {
+            Ok(line) => line,
+            Err(TryRecvError::Empty) => return Ok(None),
+            Err(TryRecvError::Disconnected) => return Err(NetworkError::Disconnected),
+        };
+        let message =
+            Message::parse(&line).ok_or_else(|| NetworkError::InvalidMessage(line.clone()))?;
+        let unexpected = || Err(NetworkError::UnexpectedMessage(line.clone()));
+
+        let event = match message {
+            Message::Move(mv) => {
+                if board.get_side_on_move() == self.side {
+                    return unexpected();
+                }
+                if board.result.is_some() {
+                    // Played before the other player learned that the game
+                    // had ended, they take it back.
+                    self.send(&Message::Stale(mv))?;
+                    return Ok(None);
+                }
+                let is_legal = match parse_uci_move(&mv) {
+                    Some((from, to, promotion)) => board.move_piece(from, to, promotion),
+                    None => false,
+                };
+                if !is_legal {
+                    return Err(NetworkError::IllegalMove(mv));
+                }
+                PeerEvent::Moved
+            }
+            Message::Stale(mv) => {
+                let is_last_move = matches!(
+                    board.move_records.last(),
+                    Some(record) if record.side == self.side
+                        && get_uci_move_name(record.from, record.to, record.promotion) == mv
+                );
+                if !is_last_move || board.result.is_none() {
+                    return unexpected();
+                }
+                // The game ended before the move, with the result the other
+                // player has already sent.
+                let result = board.result;
+                board.undo_move();
+                board.result = result;
+                PeerEvent::IgnoredMove
+            }
+            Message::Resign => {
+                if !is_ended_by_player(board) {
+                    board.result = Some(GameResult::Resignation { winner: self.side });
+                }
+                PeerEvent::Resigned
+            }
+            Message::Flag(side) => {
+                if !is_ended_by_player(board) {
+                    board.result = None;
+                    board.lose_on_time(side);
+                }
+                PeerEvent::Flagged
+            }
+            Message::DrawOffer | Message::TakebackRequest => {
+                if self.received_offer.is_some() {
+                    return unexpected();
+                }
+                if self.sent_offer.is_some() {
+                    // Both offered at once, the offers decline each other.
+                    self.send(&match message {
+                        Message::DrawOffer => Message::DrawDecline,
+                        _ => Message::TakebackDecline,
+                    })?;
+                    return Ok(None);
+                }
+                if message == Message::DrawOffer {
+                    self.received_offer = Some(Offer::Draw);
+                    PeerEvent::OfferedDraw
+                } else {
+                    self.received_offer = Some(Offer::Takeback);
+                    PeerEvent::RequestedTakeback
+                }
+            }
+            Message::DrawAccept | Message::DrawDecline => {
+                if self.sent_offer != Some(Offer::Draw) {
+                    return unexpected();
+                }
+                self.sent_offer = None;
+                if message == Message::DrawDecline {
+                    PeerEvent::DeclinedDraw
+                } else {
+                    if !is_ended_by_player(board) {
+                        board.result = Some(GameResult::DrawAgreement);
+                    }
+                    PeerEvent::AcceptedDraw
+                }
+            }
+            Message::TakebackAccept { moves } => {
+                if self.sent_offer != Some(Offer::Takeback) || moves > board.move_records.len() {
+                    return unexpected();
+                }
+                self.sent_offer = None;
+                take_back_to(board, moves);
+                PeerEvent::AcceptedTakeback
+            }
+            Message::TakebackDecline => {
+                if self.sent_offer != Some(Offer::Takeback) {
+                    return unexpected();
+                }
+                self.sent_offer = None;
+                PeerEvent::DeclinedTakeback
+            }
+            Message::Hello { .. } | Message::Game { .. } => return unexpected(),
+        };
+
+        Ok(Some(event))
+    }
+}
+
+impl Drop for Peer {
+    fn drop(&mut self) {
+        let _ = self.stream.shutdown(Shutdown::Both);
+    }
+}
+
+/// Whether a player ended the game by resigning, agreeing to a draw or on
+/// time. Any other result came from a move of this player that the other one
+/// has not seen yet when they end the game, so theirs wins.
+fn is_ended_by_player(board: &Board) -> bool {
+    matches!(
+        board.result,
+        Some(
+            GameResult::Resignation { .. }
+                | GameResult::DrawAgreement
+                | GameResult::Timeout { .. }
+                | GameResult::TimeoutVsInsufficientMaterial
+        )
+    )
+}
+
+/// Number of moves left after taking back the last move of `side`, so that it
+/// is on move again. `None` when `side` has no move to take back.
+fn get_takeback_move_count(board: &Board, side: Side) -> Option<usize> {
+    board
+        .move_records
+        .iter()
+        .rposition(|record| record.side == side)
+}
+
+fn take_back_to(board: &mut Board, moves: usize) {
+    while board.move_records.len() > moves {
+        board.undo_move();
+    }
+}
@@ -0,0 +1,73 @@
+use super::{moves::Move, position::Position, Board};
+
+impl Position {
+    /// Counts the leaf nodes of the legal move tree `depth` moves deep. Used to
+    /// check move generation against known counts.
+    pub fn perft(&mut self, depth: u32) -> u64 {
+        let moves = self.legal_moves();
+        if depth <= 1 {
+            return if depth == 0 { 1 } else { moves.len() as u64 };
+        }
+
+        let mut nodes = 0;
+        for mv in moves {
+            self.make_move(mv);
+            nodes += self.perft(depth - 1);
+            self.unmake_move();
+        }
+
+        nodes
+    }
+    /// Perft split by the moves of the side on move.
+    pub fn divide(&mut self, depth: u32) -> Vec<(Move, u64)> {
+        let mut divided = Vec::new();
+        for mv in self.legal_moves() {
+            self.make_move(mv);
+            divided.push((mv, self.perft(depth.saturating_sub(1))));
+            self.unmake_move();
+        }
+
+        divided
+    }
+}
+
+impl Board {
+    /// Perft played through [`Board::move_piece`] and [`Board::undo_move`],
+    /// much slower than [`Position::perft`] but checks the whole game logic.
+    pub fn perft(&mut self, depth: u32) -> u64 {
+        if depth == 0 {
+            return 1;
+        }
+
+        // Draw rules end the game, but the move tree goes on.
+        let result = self.result.take();
+        let mut nodes = 0;
+        for mv in self.get_position().legal_moves() {
+            nodes += if depth == 1 {
+                1
+            } else {
+                assert!(self.play_move(mv));
+                let nodes = self.perft(depth - 1);
+                self.undo_move();
+                nodes
+            };
+        }
+        self.result = result;
+
+        nodes
+    }
+    /// Perft split by the moves of the side on move, in long algebraic notation
+    /// like `e2e4` or `e7e8q`.
+    pub fn divide(&mut self, depth: u32) -> Vec<(String, u64)> {
+        let result = self.result.take();
+        let mut divided = Vec::new();
+        for mv in self.get_position().legal_moves() {
+            assert!(self.play_move(mv));
+            divided.push((mv.to_string(), self.perft(depth.saturating_sub(1))));
+            self.undo_move();
+        }
+        self.result = result;
+
+        divided
+    }
+}
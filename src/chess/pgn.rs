@@ -0,0 +1,646 @@
+use super::{
+    fen::{FenError, STARTING_POSITION_FEN},
+    piece::*,
+    result::GameResult,
+    san::parse_san,
+    Board,
+};
+
+pub const SEVEN_TAG_ROSTER: [&str; 7] =
+    ["Event", "Site", "Date", "Round", "White", "Black", "Result"];
+
+/// Lines of exported movetext are wrapped before reaching this length.
+const PGN_LINE_LENGTH: usize = 80;
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct PgnMove {
+    /// Move in Standard Algebraic Notation, as generated by the rules engine.
+    pub san: String,
+    pub move_number: u32,
+    pub side: Side,
+    /// Numeric Annotation Glyphs. Suffix annotations like `!?` are stored as their NAG.
+    pub nags: Vec<u8>,
+    /// Comments following the move.
+    pub comments: Vec<String>,
+    /// Alternatives to this move, each starting from the position before it.
+    pub variations: Vec<PgnVariation>,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct PgnVariation {
+    /// Comments before the first move of the variation.
+    pub comments: Vec<String>,
+    pub moves: Vec<PgnMove>,
+}
+
+#[derive(Debug, Clone)]
+pub struct PgnGame {
+    pub tags: Vec<(String, String)>,
+    /// Comments before the first move.
+    pub comments: Vec<String>,
+    pub moves: Vec<PgnMove>,
+    /// Result token ending the movetext: `1-0`, `0-1`, `1/2-1/2` or `*`.
+    pub result: String,
+    /// Game with every mainline move played.
+    pub board: Board,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum PgnError {
+    UnterminatedTag {
+        line: usize,
+    },
+    UnterminatedComment {
+        line: usize,
+    },
+    UnexpectedCharacter {
+        line: usize,
+        character: char,
+    },
+    UnbalancedVariation {
+        line: usize,
+    },
+    VariationWithoutMove {
+        line: usize,
+    },
+    /// A `--` null move, which cannot be played on a board.
+    NullMove {
+        line: usize,
+    },
+    InvalidFen {
+        game: usize,
+        error: FenError,
+    },
+    IllegalMove {
+        game: usize,
+        move_number: u32,
+        side: Side,
+        san: String,
+    },
+    MissingResult {
+        game: usize,
+    },
+}
+
+impl std::fmt::Display for PgnError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            PgnError::UnterminatedTag { line } => {
+                write!(f, "line {}: tag pair is missing its closing bracket", line)
+            }
+            PgnError::UnterminatedComment { line } => {
+                write!(f, "line {}: comment is missing its closing brace", line)
+            }
+            PgnError::UnexpectedCharacter { line, character } => {
+                write!(f, "line {}: unexpected character '{}'", line, character)
+            }
+            PgnError::UnbalancedVariation { line } => {
+                write!(f, "line {}: unbalanced variation parentheses", line)
+            }
+            PgnError::VariationWithoutMove { line } => {
+                write!(f, "line {}: variation does not follow any move", line)
+            }
+            PgnError::NullMove { line } => write!(f, "line {}: unsupported null move", line),
+            PgnError::InvalidFen { game, error } => {
+                write!(f, "game {}: invalid FEN tag: {}", game, error)
+            }
+            PgnError::IllegalMove {
+                game,
+                move_number,
+                side,
+                san,
+            } => write!(
+                f,
+                "game {}: illegal move {}{} {}",
+                game,
+                move_number,
+                if *side == Side::White { "." } else { "..." },
+                san
+            ),
+            PgnError::MissingResult { game } => {
+                write!(f, "game {}: movetext does not end with a result", game)
+            }
+        }
+    }
+}
+
+impl std::error::Error for PgnError {}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+enum Token {
+    Tag(String, String),
+    MoveNumber,
+    San(String),
+    Nag(u8),
+    Comment(String),
+    VariationStart,
+    VariationEnd,
+    Result(String),
+}
+
+pub fn get_result_token(result: Option<GameResult>) -> &'static str {
+    match result {
+        Some(result) => match result.winner() {
+            Some(Side::White) => "1-0",
+            Some(Side::Black) => "0-1",
+            None => "1/2-1/2",
+        },
+        None => "*",
+    }
+}
+
+impl PgnGame {
+    /// Builds the PGN record of a game played on `board`, with placeholder
+    /// values for the Seven Tag Roster.
+    pub fn from_board(board: &Board) -> Self {
+        let start =
+            Board::from_fen(&board.starting_fen).expect("starting FEN of a board is always valid");
+        let mut move_number = start.fullmove_number;
+
+        let mut moves = Vec::new();
+        for record in &board.move_records {
+            moves.push(PgnMove {
+                san: record.san.clone(),
+                move_number,
+                side: record.side,
+                nags: Vec::new(),
+                comments: Vec::new(),
+                variations: Vec::new(),
+            });
+            if record.side == Side::Black {
+                move_number += 1;
+            }
+        }
+
+        let result = get_result_token(board.result).to_string();
+        let mut tags: Vec<(String, String)> = SEVEN_TAG_ROSTER
+            .iter()
+            .map(|name| {
+                let value = match *name {
+                    "Date" => "????.??.??".to_string(),
+                    "Result" => result.clone(),
+                    _ => "?".to_string(),
+                };
+                (name.to_string(), value)
+            })
+            .collect();
+
+        if board.starting_fen != STARTING_POSITION_FEN {
+            tags.push(("SetUp".to_string(), "1".to_string()));
+            tags.push(("FEN".to_string(), board.starting_fen.clone()));
+        }
+
+        PgnGame {
+            tags,
+            comments: Vec::new(),
+            moves,
+            result,
+            board: board.clone(),
+        }
+    }
+    pub fn get_tag(&self, name: &str) -> Option<&str> {
+        self.tags
+            .iter()
+            .find(|(tag, _)| tag == name)
+            .map(|(_, value)| value.as_str())
+    }
+    pub fn set_tag(&mut self, name: &str, value: &str) {
+        match self.tags.iter_mut().find(|(tag, _)| tag == name) {
+            Some((_, old_value)) => *old_value = value.to_string(),
+            None => self.tags.push((name.to_string(), value.to_string())),
+        }
+    }
+    /// Exports the game, always starting with the Seven Tag Roster in its
+    /// standard order.
+    pub fn to_pgn(&self) -> String {
+        let mut pgn = String::new();
+
+        for name in SEVEN_TAG_ROSTER {
+            let value = match name {
+                "Result" => self.result.as_str(),
+                "Date" => self.get_tag(name).unwrap_or("????.??.??"),
+                _ => self.get_tag(name).unwrap_or("?"),
+            };
+            pgn.push_str(&format_tag(name, value));
+        }
+        for (name, value) in &self.tags {
+            if !SEVEN_TAG_ROSTER.contains(&name.as_str()) {
+                pgn.push_str(&format_tag(name, value));
+            }
+        }
+        pgn.push('\n');
+
+        let mut tokens: Vec<String> = self
+            .comments
+            .iter()
+            .map(|comment| format!("{{{}}}", comment))
+            .collect();
+        write_movetext(&self.moves, &mut tokens);
+        tokens.push(self.result.clone());
+
+        let mut line = String::new();
+        for token in tokens {
+            if !line.is_empty() && line.len() + 1 + token.len() > PGN_LINE_LENGTH {
+                pgn.push_str(&line);
+                pgn.push('\n');
+                line.clear();
+            }
+            if !line.is_empty() {
+                line.push(' ');
+            }
+            line.push_str(&token);
+        }
+        pgn.push_str(&line);
+        pgn.push('\n');
+
+        pgn
+    }
+}
+
+impl Board {
+    pub fn to_pgn(&self) -> String {
+        PgnGame::from_board(self).to_pgn()
+    }
+}
+
+fn format_tag(name: &str, value: &str) -> String {
+    format!(
+        "[{} \"{}\"]\n",
+        name,
+        value.replace('\\', "\\\\").replace('"', "\\\"")
+    )
+}
+
+fn write_movetext(moves: &[PgnMove], tokens: &mut Vec<String>) {
+    let mut needs_move_number = true;
+
+    for mv in moves {
+        // Move numbers share a token with their move so wrapping never separates them.
+        if mv.side == Side::White {
+            tokens.push(format!("{}. {}", mv.move_number, mv.san));
+        } else if needs_move_number {
+            tokens.push(format!("{}... {}", mv.move_number, mv.san));
+        } else {
+            tokens.push(mv.san.clone());
+        }
+        needs_move_number = false;
+
+        for nag in &mv.nags {
+            tokens.push(format!("${}", nag));
+        }
+        for comment in &mv.comments {
+            tokens.push(format!("{{{}}}", comment));
+            needs_move_number = true;
+        }
+        for variation in &mv.variations {
+            let first_token = tokens.len();
+            for comment in &variation.comments {
+                tokens.push(format!("{{{}}}", comment));
+            }
+            write_movetext(&variation.moves, tokens);
+
+            if tokens.len() > first_token {
+                tokens[first_token].insert(0, '(');
+                if let Some(last_token) = tokens.last_mut() {
+                    last_token.push(')');
+                }
+            } else {
+                tokens.push("()".to_string());
+            }
+            needs_move_number = true;
+        }
+    }
+}
+
+/// Parses every game in `text` and replays its moves, variations included,
+/// through the rules engine. Every game has to end with a result token.
+pub fn parse_pgn(text: &str) -> Result<Vec<PgnGame>, PgnError> {
+    let tokens = tokenize(text)?;
+    let mut games = Vec::new();
+    let mut index = 0;
+
+    while index < tokens.len() {
+        let mut tags = Vec::new();
+        while let Some((Token::Tag(name, value), _)) = tokens.get(index) {
+            tags.push((name.clone(), value.clone()));
+            index += 1;
+        }
+
+        let mut result = None;
+        let (comments, mut moves) = parse_line(&tokens, &mut index, 0, &mut result)?;
+        let game_number = games.len() + 1;
+
+        let fen = tags
+            .iter()
+            .find(|(name, _)| name == "FEN")
+            .map(|(_, value)| value.as_str());
+        let mut board = match fen {
+            Some(fen) => Board::from_fen(fen).map_err(|error| PgnError::InvalidFen {
+                game: game_number,
+                error,
+            })?,
+            None => Board::new(),
+        };
+
+        replay_moves(&mut board, &mut moves, game_number)?;
+
+        let result = result.ok_or(PgnError::MissingResult { game: game_number })?;
+
+        games.push(PgnGame {
+            tags,
+            comments,
+            moves,
+            result,
+            board,
+        });
+    }
+
+    Ok(games)
+}
+
+/// Parses moves until the end of the game (`depth == 0`) or of the current
+/// variation. Returns the comments before the first move and the moves.
+fn parse_line(
+    tokens: &[(Token, usize)],
+    index: &mut usize,
+    depth: usize,
+    result: &mut Option<String>,
+) -> Result<(Vec<String>, Vec<PgnMove>), PgnError> {
+    let mut comments = Vec::new();
+    let mut moves: Vec<PgnMove> = Vec::new();
+
+    while let Some((token, line)) = tokens.get(*index) {
+        match token {
+            Token::Tag(_, _) => {
+                if depth > 0 {
+                    return Err(PgnError::UnbalancedVariation { line: *line });
+                }
+                return Ok((comments, moves));
+            }
+            Token::Result(token) => {
+                if depth > 0 {
+                    return Err(PgnError::UnbalancedVariation { line: *line });
+                }
+                *result = Some(token.clone());
+                *index += 1;
+                return Ok((comments, moves));
+            }
+            Token::MoveNumber => {}
+            Token::San(san) => moves.push(PgnMove {
+                san: san.clone(),
+                move_number: 0,
+                side: Side::White,
+                nags: Vec::new(),
+                comments: Vec::new(),
+                variations: Vec::new(),
+            }),
+            Token::Nag(nag) => {
+                if let Some(mv) = moves.last_mut() {
+                    mv.nags.push(*nag);
+                }
+            }
+            Token::Comment(comment) => match moves.last_mut() {
+                Some(mv) => mv.comments.push(comment.clone()),
+                None => comments.push(comment.clone()),
+            },
+            Token::VariationStart => {
+                let line = *line;
+                *index += 1;
+                let (variation_comments, variation_moves) =
+                    parse_line(tokens, index, depth + 1, result)?;
+                match moves.last_mut() {
+                    Some(mv) => mv.variations.push(PgnVariation {
+                        comments: variation_comments,
+                        moves: variation_moves,
+                    }),
+                    None => return Err(PgnError::VariationWithoutMove { line }),
+                }
+                // The variation has already moved past its closing parenthesis.
+                continue;
+            }
+            Token::VariationEnd => {
+                if depth == 0 {
+                    return Err(PgnError::UnbalancedVariation { line: *line });
+                }
+                *index += 1;
+                return Ok((comments, moves));
+            }
+        }
+        *index += 1;
+    }
+
+    if depth > 0 {
+        let line = tokens.last().map(|(_, line)| *line).unwrap_or(1);
+        return Err(PgnError::UnbalancedVariation { line });
+    }
+
+    Ok((comments, moves))
+}
+
+fn replay_moves(board: &mut Board, moves: &mut [PgnMove], game: usize) -> Result<(), PgnError> {
+    for mv in moves.iter_mut() {
+        for variation in mv.variations.iter_mut() {
+            let mut variation_board = board.clone();
+            replay_moves(&mut variation_board, &mut variation.moves, game)?;
+        }
+
+        mv.move_number = board.fullmove_number;
+        mv.side = board.get_side_on_move();
+
+        let illegal_move = || PgnError::IllegalMove {
+            game,
+            move_number: mv.move_number,
+            side: mv.side,
+            san: mv.san.clone(),
+        };
+        let (from, to, promotion) = parse_san(board, &mv.san).map_err(|_| illegal_move())?;
+        if !board.move_piece(from, to, promotion) {
+            return Err(illegal_move());
+        }
+        if let Some(record) = board.move_records.last() {
+            mv.san = record.san.clone();
+        }
+    }
+
+    Ok(())
+}
+
+fn tokenize(text: &str) -> Result<Vec<(Token, usize)>, PgnError> {
+    let mut tokens = Vec::new();
+    let mut chars = text.chars().peekable();
+    let mut line = 1;
+    let mut at_line_start = true;
+
+    while let Some(c) = chars.next() {
+        if c == '\n' {
+            line += 1;
+            at_line_start = true;
+            continue;
+        }
+
+        // Lines starting with '%' are escaped and ignored.
+        if at_line_start && c == '%' {
+            for c in chars.by_ref() {
+                if c == '\n' {
+                    line += 1;
+                    break;
+                }
+            }
+            continue;
+        }
+        at_line_start = false;
+
+        match c {
+            c if c.is_whitespace() => {}
+            '[' => {
+                let tag_line = line;
+                let mut content = String::new();
+                let mut in_string = false;
+                let mut escaped = false;
+                let mut closed = false;
+
+                for c in chars.by_ref() {
+                    if c == '\n' {
+                        line += 1;
+                    }
+                    if escaped {
+                        content.push(c);
+                        escaped = false;
+                    } else if in_string && c == '\\' {
+                        escaped = true;
+                    } else if c == '"' {
+                        in_string = !in_string;
+                        content.push(c);
+                    } else if !in_string && c == ']' {
+                        closed = true;
+                        break;
+                    } else {
+                        content.push(c);
+                    }
+                }
+
+                let content = content.trim();
+                let (name, value) = match content.split_once(char::is_whitespace) {
+                    Some((name, value)) if closed => (name, value.trim()),
+                    _ => return Err(PgnError::UnterminatedTag { line: tag_line }),
+                };
+                let value = value
+                    .strip_prefix('"')
+                    .and_then(|value| value.strip_suffix('"'))
+                    .ok_or(PgnError::UnterminatedTag { line: tag_line })?;
+
+                tokens.push((Token::Tag(name.to_string(), value.to_string()), tag_line));
+            }
+            '{' => {
+                let comment_line = line;
+                let mut comment = String::new();
+                let mut closed = false;
+
+                for c in chars.by_ref() {
+                    if c == '}' {
+                        closed = true;
+                        break;
+                    }
+                    if c == '\n' {
+                        line += 1;
+                    }
+                    comment.push(c);
+                }
+
+                if !closed {
+                    return Err(PgnError::UnterminatedComment { line: comment_line });
+                }
+                let comment = comment.split_whitespace().collect::<Vec<_>>().join(" ");
+                tokens.push((Token::Comment(comment), comment_line));
+            }
+            ';' => {
+                let mut comment = String::new();
+                while let Some(c) = chars.next_if(|c| *c != '\n') {
+                    comment.push(c);
+                }
+                tokens.push((Token::Comment(comment.trim().to_string()), line));
+            }
+            '(' => tokens.push((Token::VariationStart, line)),
+            ')' => tokens.push((Token::VariationEnd, line)),
+            '*' => tokens.push((Token::Result("*".to_string()), line)),
+            '$' => {
+                let mut nag = String::new();
+                while let Some(c) = chars.next_if(|c| c.is_ascii_digit()) {
+                    nag.push(c);
+                }
+                match nag.parse::<u8>() {
+                    Ok(nag) => tokens.push((Token::Nag(nag), line)),
+                    Err(_) => {
+                        return Err(PgnError::UnexpectedCharacter {
+                            line,
+                            character: '$',
+                        })
+                    }
+                }
+            }
+            c if c.is_ascii_alphanumeric() || c == '-' => {
+                let mut symbol = String::from(c);
+                while let Some(c) =
+                    chars.next_if(|c| c.is_ascii_alphanumeric() || "+#=:-/.!?".contains(*c))
+                {
+                    symbol.push(c);
+                }
+                tokenize_symbol(&symbol, line, &mut tokens)?;
+            }
+            character => return Err(PgnError::UnexpectedCharacter { line, character }),
+        }
+    }
+
+    Ok(tokens)
+}
+
+/// Splits a movetext symbol into move number, result, SAN and suffix annotation tokens.
+fn tokenize_symbol(
+    symbol: &str,
+    line: usize,
+    tokens: &mut Vec<(Token, usize)>,
+) -> Result<(), PgnError> {
+    if ["1-0", "0-1", "1/2-1/2"].contains(&symbol) {
+        tokens.push((Token::Result(symbol.to_string()), line));
+        return Ok(());
+    }
+    if symbol.starts_with("--") {
+        return Err(PgnError::NullMove { line });
+    }
+    if symbol.starts_with('-') {
+        return Err(PgnError::UnexpectedCharacter {
+            line,
+            character: '-',
+        });
+    }
+
+    let mut symbol = symbol;
+    let digits = symbol.chars().take_while(|c| c.is_ascii_digit()).count();
+    // The period after a move number is optional.
+    if digits > 0 && (digits == symbol.len() || symbol[digits..].starts_with('.')) {
+        tokens.push((Token::MoveNumber, line));
+        symbol = symbol[digits..].trim_start_matches('.');
+    }
+
+    if symbol.is_empty() {
+        return Ok(());
+    }
+
+    let san = symbol.trim_end_matches(['!', '?']);
+    let nag = match &symbol[san.len()..] {
+        "!" => Some(1),
+        "?" => Some(2),
+        "!!" => Some(3),
+        "??" => Some(4),
+        "!?" => Some(5),
+        "?!" => Some(6),
+        _ => None,
+    };
+
+    tokens.push((Token::San(san.to_string()), line));
+    if let Some(nag) = nag {
+        tokens.push((Token::Nag(nag), line));
+    }
+
+    Ok(())
+}
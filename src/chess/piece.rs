@@ -1,7 +1,3 @@
-use raylib::color::Color;
-
-pub type TileColorSchema = (Color, Color);
-
 #[derive(Debug, Copy, Clone)]
 pub struct Piece {
     pub kind: PieceType,
@@ -19,6 +15,26 @@ impl Piece {
     }
 }
 
+impl Piece {
+    /// Letter used for the piece in FEN, uppercase for White and lowercase for Black.
+    pub fn to_fen_char(&self) -> char {
+        match self.side {
+            Side::White => self.kind.to_char(),
+            Side::Black => self.kind.to_char().to_ascii_lowercase(),
+        }
+    }
+    pub fn from_fen_char(c: char) -> Option<Self> {
+        let kind = PieceType::from_char(c.to_ascii_uppercase())?;
+        let side = if c.is_ascii_uppercase() {
+            Side::White
+        } else {
+            Side::Black
+        };
+
+        Some(Piece::new(kind, side))
+    }
+}
+
 impl std::fmt::Display for Piece {
     fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
         match self.kind {
@@ -54,8 +70,61 @@ pub enum PieceType {
     King,
 }
 
+impl PieceType {
+    pub const ALL: [PieceType; 6] = [
+        PieceType::Pawn,
+        PieceType::Rook,
+        PieceType::Knight,
+        PieceType::Bishop,
+        PieceType::Queen,
+        PieceType::King,
+    ];
+
+    /// Standard value in pawns, the king has none.
+    pub fn get_value(&self) -> i32 {
+        match self {
+            PieceType::Pawn => 1,
+            PieceType::Knight | PieceType::Bishop => 3,
+            PieceType::Rook => 5,
+            PieceType::Queen => 9,
+            PieceType::King => 0,
+        }
+    }
+    /// Uppercase English letter of the piece, as used by FEN and algebraic notation.
+    pub fn to_char(&self) -> char {
+        match self {
+            PieceType::Pawn => 'P',
+            PieceType::Rook => 'R',
+            PieceType::Knight => 'N',
+            PieceType::Bishop => 'B',
+            PieceType::Queen => 'Q',
+            PieceType::King => 'K',
+        }
+    }
+    pub fn from_char(c: char) -> Option<Self> {
+        match c {
+            'P' => Some(PieceType::Pawn),
+            'R' => Some(PieceType::Rook),
+            'N' => Some(PieceType::Knight),
+            'B' => Some(PieceType::Bishop),
+            'Q' => Some(PieceType::Queen),
+            'K' => Some(PieceType::King),
+            _ => None,
+        }
+    }
+}
+
 #[derive(Debug, Copy, Clone, PartialEq, Eq, Hash)]
 pub enum Side {
     Black,
     White,
 }
+
+impl Side {
+    pub fn opposite(&self) -> Side {
+        match self {
+            Side::Black => Side::White,
+            Side::White => Side::Black,
+        }
+    }
+}
@@ -0,0 +1,488 @@
+use super::{bitboard::*, constants::*, moves::Move, piece::*, zobrist::ZobristKeys, Board};
+
+/// State [`Position::make_move`] overwrites and [`Position::unmake_move`] brings back.
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+struct MoveUndo {
+    mv: Move,
+    captured: Option<PieceType>,
+    castling_rights: [bool; 4],
+    en_passant_target: Option<Square>,
+    halfmove_clock: u32,
+    /// Hash of the position the move was made in.
+    hash: u64,
+}
+
+/// Bitboard representation of a position, used for move generation and
+/// search. Squares are numbered from a1, see [`get_square`] for converting
+/// tile coordinates.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Position {
+    /// Pieces indexed by `Side as usize` and `PieceType as usize`.
+    pieces: [[Bitboard; 6]; 2],
+    /// All pieces of each side.
+    sides: [Bitboard; 2],
+    side_on_move: Side,
+    /// In the FEN order: White king side, White queen side, Black king side,
+    /// Black queen side.
+    castling_rights: [bool; 4],
+    en_passant_target: Option<Square>,
+    halfmove_clock: u32,
+    fullmove_number: u32,
+    /// One entry for every move made, the last move is on top.
+    history: Vec<MoveUndo>,
+    keys: &'static ZobristKeys,
+    /// Zobrist hash, kept up to date by [`Position::make_move`].
+    hash: u64,
+}
+
+impl Position {
+    pub fn from_board(board: &Board) -> Self {
+        Self::from_board_with_keys(board, ZobristKeys::get_default())
+    }
+    /// Position of `board` hashed with `keys`, e.g. keys in the Polyglot layout.
+    pub fn from_board_with_keys(board: &Board, keys: &'static ZobristKeys) -> Self {
+        let mut position = Position {
+            pieces: [[0; 6]; 2],
+            sides: [0; 2],
+            side_on_move: board.get_side_on_move(),
+            castling_rights: board.get_castling_rights(),
+            en_passant_target: board.get_en_passant_target().map(get_square),
+            halfmove_clock: board.halfmove_clock,
+            fullmove_number: board.fullmove_number,
+            history: Vec::new(),
+            keys,
+            hash: 0,
+        };
+
+        for (x, y, tile) in board.tiles_iter() {
+            if let Some(piece) = tile.piece {
+                position.toggle_piece(get_square((x, y)), piece.kind, piece.side);
+            }
+        }
+        position.hash ^= keys.get_state_hash(&position);
+
+        position
+    }
+    /// Adds the piece to an empty square, or removes it from the square it is on.
+    fn toggle_piece(&mut self, square: Square, kind: PieceType, side: Side) {
+        self.pieces[side as usize][kind as usize] ^= square_bitboard(square);
+        self.sides[side as usize] ^= square_bitboard(square);
+        self.hash ^= self.keys.get_piece_key(square, kind, side);
+    }
+    pub fn get_side_on_move(&self) -> Side {
+        self.side_on_move
+    }
+    pub fn get_castling_rights(&self) -> [bool; 4] {
+        self.castling_rights
+    }
+    pub fn get_en_passant_target(&self) -> Option<Square> {
+        self.en_passant_target
+    }
+    pub fn get_halfmove_clock(&self) -> u32 {
+        self.halfmove_clock
+    }
+    pub fn get_fullmove_number(&self) -> u32 {
+        self.fullmove_number
+    }
+    /// Moves made with [`Position::make_move`] that were not unmade yet.
+    pub fn get_move_history(&self) -> impl Iterator<Item = Move> + '_ {
+        self.history.iter().map(|undo| undo.mv)
+    }
+    /// Hashes of the positions the moves of [`Position::get_move_history`]
+    /// were made in.
+    pub fn get_hash_history(&self) -> impl DoubleEndedIterator<Item = u64> + '_ {
+        self.history.iter().map(|undo| undo.hash)
+    }
+    pub fn get_hash(&self) -> u64 {
+        self.hash
+    }
+    pub fn get_zobrist_keys(&self) -> &'static ZobristKeys {
+        self.keys
+    }
+    pub fn get_pieces(&self, kind: PieceType, side: Side) -> Bitboard {
+        self.pieces[side as usize][kind as usize]
+    }
+    pub fn get_side_pieces(&self, side: Side) -> Bitboard {
+        self.sides[side as usize]
+    }
+    pub fn get_occupancy(&self) -> Bitboard {
+        self.sides[0] | self.sides[1]
+    }
+    pub fn get_piece_at(&self, square: Square) -> Option<(PieceType, Side)> {
+        let bitboard = square_bitboard(square);
+        for side in [Side::White, Side::Black] {
+            if self.sides[side as usize] & bitboard == 0 {
+                continue;
+            }
+            for kind in PieceType::ALL {
+                if self.pieces[side as usize][kind as usize] & bitboard != 0 {
+                    return Some((kind, side));
+                }
+            }
+        }
+
+        None
+    }
+    pub fn get_king_square(&self, side: Side) -> Option<Square> {
+        get_squares(self.get_pieces(PieceType::King, side)).next()
+    }
+    /// Pieces of `by` side attacking `square` when the board is occupied by `occupancy`.
+    pub fn get_attackers(&self, square: Square, by: Side, occupancy: Bitboard) -> Bitboard {
+        let queens = self.get_pieces(PieceType::Queen, by);
+
+        (get_pawn_attacks(by.opposite(), square) & self.get_pieces(PieceType::Pawn, by))
+            | (get_knight_attacks(square) & self.get_pieces(PieceType::Knight, by))
+            | (get_king_attacks(square) & self.get_pieces(PieceType::King, by))
+            | (get_rook_attacks(square, occupancy)
+                & (self.get_pieces(PieceType::Rook, by) | queens))
+            | (get_bishop_attacks(square, occupancy)
+                & (self.get_pieces(PieceType::Bishop, by) | queens))
+    }
+    pub fn is_square_attacked(&self, square: Square, by: Side) -> bool {
+        self.get_attackers(square, by, self.get_occupancy()) != 0
+    }
+    pub fn is_side_in_check(&self, side: Side) -> bool {
+        match self.get_king_square(side) {
+            Some(king) => self.is_square_attacked(king, side.opposite()),
+            None => false,
+        }
+    }
+    pub fn is_check(&self) -> bool {
+        self.is_side_in_check(self.side_on_move)
+    }
+    /// Pieces of `side` that cannot leave the line between their king on
+    /// `king` and an enemy slider.
+    fn get_pinned(&self, side: Side, king: Square) -> Bitboard {
+        let enemy = side.opposite();
+        let occupancy = self.get_occupancy();
+        let queens = self.get_pieces(PieceType::Queen, enemy);
+        let snipers = (get_rook_attacks(king, 0)
+            & (self.get_pieces(PieceType::Rook, enemy) | queens))
+            | (get_bishop_attacks(king, 0) & (self.get_pieces(PieceType::Bishop, enemy) | queens));
+
+        let mut pinned = 0;
+        for sniper in get_squares(snipers) {
+            let blockers = get_between(king, sniper) & occupancy;
+            if blockers.count_ones() == 1 {
+                pinned |= blockers & self.get_side_pieces(side);
+            }
+        }
+
+        pinned
+    }
+    /// Every legal move of the side on move. Castling is the king moving two
+    /// squares towards the rook.
+    pub fn legal_moves(&self) -> Vec<Move> {
+        let mut moves = Vec::with_capacity(64);
+        let side = self.side_on_move;
+        let enemy = side.opposite();
+        let king = match self.get_king_square(side) {
+            Some(king) => king,
+            None => return moves,
+        };
+
+        let own = self.get_side_pieces(side);
+        let enemies = self.get_side_pieces(enemy);
+        let occupancy = own | enemies;
+        let checkers = self.get_attackers(king, enemy, occupancy);
+
+        // The king must not hide behind itself from a slider, so it is taken off the board.
+        let occupancy_without_king = occupancy ^ square_bitboard(king);
+        for to in get_squares(get_king_attacks(king) & !own) {
+            if self.get_attackers(to, enemy, occupancy_without_king) == 0 {
+                moves.push(Self::get_normal_move(king, to, enemies));
+            }
+        }
+
+        if checkers.count_ones() > 1 {
+            return moves;
+        }
+
+        // Squares that capture or block the checking piece.
+        let check_mask = match get_squares(checkers).next() {
+            Some(checker) => get_between(king, checker) | checkers,
+            None => !0,
+        };
+        let pinned = self.get_pinned(side, king);
+        let get_pin_mask = |from: Square| {
+            if pinned & square_bitboard(from) != 0 {
+                get_line(king, from)
+            } else {
+                !0
+            }
+        };
+
+        if checkers == 0 {
+            self.push_castling_moves(&mut moves, king, occupancy);
+        }
+
+        for kind in [
+            PieceType::Knight,
+            PieceType::Bishop,
+            PieceType::Rook,
+            PieceType::Queen,
+        ] {
+            for from in get_squares(self.get_pieces(kind, side)) {
+                let attacks = match kind {
+                    PieceType::Knight => get_knight_attacks(from),
+                    PieceType::Bishop => get_bishop_attacks(from, occupancy),
+                    PieceType::Rook => get_rook_attacks(from, occupancy),
+                    _ => get_queen_attacks(from, occupancy),
+                };
+                for to in get_squares(attacks & !own & check_mask & get_pin_mask(from)) {
+                    moves.push(Self::get_normal_move(from, to, enemies));
+                }
+            }
+        }
+
+        let (forward, start_rank, last_rank) = match side {
+            Side::White => (8, RANK_1 << 8, RANK_8),
+            Side::Black => (-8, RANK_8 >> 8, RANK_1),
+        };
+        for from in get_squares(self.get_pieces(PieceType::Pawn, side)) {
+            let mask = check_mask & get_pin_mask(from);
+            let mut targets = get_pawn_attacks(side, from) & enemies;
+
+            let one_step = (from as i32 + forward) as Square;
+            if occupancy & square_bitboard(one_step) == 0 {
+                targets |= square_bitboard(one_step);
+
+                let two_steps = (one_step as i32 + forward) as Square;
+                if start_rank & square_bitboard(from) != 0
+                    && occupancy & square_bitboard(two_steps) == 0
+                {
+                    targets |= square_bitboard(two_steps);
+                }
+            }
+
+            for to in get_squares(targets & mask) {
+                if last_rank & square_bitboard(to) != 0 {
+                    let is_capture = enemies & square_bitboard(to) != 0;
+                    for kind in PROMOTION_PIECES {
+                        moves.push(Move::new_promotion(from, to, kind, is_capture));
+                    }
+                } else if from.abs_diff(to) == 16 {
+                    moves.push(Move::new(from, to, Move::DOUBLE_PUSH));
+                } else {
+                    moves.push(Self::get_normal_move(from, to, enemies));
+                }
+            }
+
+            if let Some(target) = self.en_passant_target {
+                if get_pawn_attacks(side, from) & square_bitboard(target) != 0 {
+                    // Taking en passant clears two squares of a rank at once,
+                    // so the simplest check is to play it on the occupancy.
+                    let captured = (target as i32 - forward) as Square;
+                    let occupancy_after =
+                        (occupancy ^ square_bitboard(from) ^ square_bitboard(captured))
+                            | square_bitboard(target);
+                    if self.get_attackers(king, enemy, occupancy_after) & !square_bitboard(captured)
+                        == 0
+                    {
+                        moves.push(Move::new(from, target, Move::EN_PASSANT));
+                    }
+                }
+            }
+        }
+
+        moves
+    }
+    fn push_castling_moves(&self, moves: &mut Vec<Move>, king: Square, occupancy: Bitboard) {
+        let side = self.side_on_move;
+        let (home_king, rights_offset) = match side {
+            Side::White => (4, 0),
+            Side::Black => (60, 2),
+        };
+        if king != home_king {
+            return;
+        }
+
+        // King side rook is three squares right of the king, queen side four left.
+        for (right, rook) in [(rights_offset, king + 3), (rights_offset + 1, king - 4)] {
+            if !self.castling_rights[right] || get_between(king, rook) & occupancy != 0 {
+                continue;
+            }
+
+            let (crossed, to, flags) = if rook > king {
+                (king + 1, king + 2, Move::KING_CASTLE)
+            } else {
+                (king - 1, king - 2, Move::QUEEN_CASTLE)
+            };
+            if !self.is_square_attacked(crossed, side.opposite())
+                && !self.is_square_attacked(to, side.opposite())
+            {
+                moves.push(Move::new(king, to, flags));
+            }
+        }
+    }
+    fn get_normal_move(from: Square, to: Square, enemies: Bitboard) -> Move {
+        if enemies & square_bitboard(to) != 0 {
+            Move::new(from, to, Move::CAPTURE)
+        } else {
+            Move::new(from, to, Move::QUIET)
+        }
+    }
+    /// Rook squares of a castle, from and to.
+    fn get_castling_rook_squares(mv: Move) -> (Square, Square) {
+        if mv.get_flags() == Move::KING_CASTLE {
+            (mv.get_to() + 1, mv.get_to() - 1)
+        } else {
+            (mv.get_to() - 2, mv.get_to() + 1)
+        }
+    }
+    /// Square of the pawn taken by an en passant move.
+    fn get_en_passant_capture_square(mv: Move) -> Square {
+        if mv.get_to() > mv.get_from() {
+            mv.get_to() - 8
+        } else {
+            mv.get_to() + 8
+        }
+    }
+    /// Plays `mv`, which has to be one of [`Position::legal_moves`].
+    pub fn make_move(&mut self, mv: Move) {
+        let side = self.side_on_move;
+        let enemy = side.opposite();
+        let (from, to) = (mv.get_from(), mv.get_to());
+        let (kind, _) = self
+            .get_piece_at(from)
+            .expect("a legal move starts on a piece");
+        let hash = self.hash;
+        // Castling rights and en passant are hashed again once the move is made.
+        self.hash ^= self.keys.get_state_hash(self);
+
+        let captured = if mv.is_en_passant() {
+            self.toggle_piece(
+                Self::get_en_passant_capture_square(mv),
+                PieceType::Pawn,
+                enemy,
+            );
+            Some(PieceType::Pawn)
+        } else if mv.is_capture() {
+            let captured = self.get_piece_at(to).map(|(kind, _)| kind);
+            if let Some(captured) = captured {
+                self.toggle_piece(to, captured, enemy);
+            }
+            captured
+        } else {
+            None
+        };
+
+        self.history.push(MoveUndo {
+            mv,
+            captured,
+            castling_rights: self.castling_rights,
+            en_passant_target: self.en_passant_target,
+            halfmove_clock: self.halfmove_clock,
+            hash,
+        });
+
+        self.toggle_piece(from, kind, side);
+        self.toggle_piece(to, mv.get_promotion().unwrap_or(kind), side);
+        if mv.is_castle() {
+            let (rook_from, rook_to) = Self::get_castling_rook_squares(mv);
+            self.toggle_piece(rook_from, PieceType::Rook, side);
+            self.toggle_piece(rook_to, PieceType::Rook, side);
+        }
+
+        for square in [from, to] {
+            match square {
+                0 => self.castling_rights[1] = false,
+                4 => self.castling_rights[..2].fill(false),
+                7 => self.castling_rights[0] = false,
+                56 => self.castling_rights[3] = false,
+                60 => self.castling_rights[2..].fill(false),
+                63 => self.castling_rights[2] = false,
+                _ => {}
+            }
+        }
+
+        self.en_passant_target = if mv.is_double_push() {
+            Some((from + to) / 2)
+        } else {
+            None
+        };
+        self.halfmove_clock = if kind == PieceType::Pawn || captured.is_some() {
+            0
+        } else {
+            self.halfmove_clock + 1
+        };
+        if side == Side::Black {
+            self.fullmove_number += 1;
+        }
+        self.side_on_move = enemy;
+        self.hash ^= self.keys.get_state_hash(self);
+    }
+    /// Takes back the last move made with [`Position::make_move`] and returns it.
+    pub fn unmake_move(&mut self) -> Option<Move> {
+        let undo = self.history.pop()?;
+        let mv = undo.mv;
+        let side = self.side_on_move.opposite();
+        let (from, to) = (mv.get_from(), mv.get_to());
+
+        let (kind, _) = self
+            .get_piece_at(to)
+            .expect("the moved piece is on its square");
+        self.toggle_piece(to, kind, side);
+        self.toggle_piece(
+            from,
+            if mv.get_promotion().is_some() {
+                PieceType::Pawn
+            } else {
+                kind
+            },
+            side,
+        );
+        if mv.is_castle() {
+            let (rook_from, rook_to) = Self::get_castling_rook_squares(mv);
+            self.toggle_piece(rook_to, PieceType::Rook, side);
+            self.toggle_piece(rook_from, PieceType::Rook, side);
+        }
+
+        if let Some(captured) = undo.captured {
+            let square = if mv.is_en_passant() {
+                Self::get_en_passant_capture_square(mv)
+            } else {
+                to
+            };
+            self.toggle_piece(square, captured, side.opposite());
+        }
+
+        self.castling_rights = undo.castling_rights;
+        self.en_passant_target = undo.en_passant_target;
+        self.halfmove_clock = undo.halfmove_clock;
+        if side == Side::Black {
+            self.fullmove_number -= 1;
+        }
+        self.side_on_move = side;
+        self.hash = undo.hash;
+
+        Some(mv)
+    }
+}
+
+impl Board {
+    pub fn get_position(&self) -> Position {
+        Position::from_board(self)
+    }
+    /// Position with the moves of the game in its history, so repetitions of
+    /// earlier positions can be told.
+    pub fn get_position_with_history(&self) -> Position {
+        let mut position = Board::from_fen(&self.starting_fen)
+            .expect("starting FEN of a board is always valid")
+            .get_position();
+
+        for record in &self.move_records {
+            let mv = position
+                .legal_moves()
+                .into_iter()
+                .find(|mv| {
+                    mv.get_coords() == (record.from, record.to)
+                        && mv.get_promotion() == record.promotion
+                })
+                .expect("recorded moves are legal");
+            position.make_move(mv);
+        }
+
+        position
+    }
+}
@@ -0,0 +1,64 @@
+use super::piece::Side;
+
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+pub enum GameResult {
+    Checkmate {
+        winner: Side,
+    },
+    Stalemate,
+    /// Claimed after 50 moves of each side without a capture or pawn move.
+    FiftyMoveRule,
+    /// Applied automatically after 75 moves of each side without a capture or pawn move.
+    SeventyFiveMoveRule,
+    /// Claimed when the same position appeared for the third time.
+    ThreefoldRepetition,
+    /// Applied automatically when the same position appeared for the fifth time.
+    FivefoldRepetition,
+    InsufficientMaterial,
+    /// The loser ran out of time.
+    Timeout {
+        winner: Side,
+    },
+    /// A side ran out of time, but the other one could not have checkmated.
+    TimeoutVsInsufficientMaterial,
+    /// The loser resigned.
+    Resignation {
+        winner: Side,
+    },
+    /// Both sides agreed to a draw.
+    DrawAgreement,
+}
+
+impl GameResult {
+    pub fn winner(&self) -> Option<Side> {
+        match self {
+            GameResult::Checkmate { winner }
+            | GameResult::Timeout { winner }
+            | GameResult::Resignation { winner } => Some(*winner),
+            _ => None,
+        }
+    }
+    pub fn is_draw(&self) -> bool {
+        self.winner().is_none()
+    }
+}
+
+impl std::fmt::Display for GameResult {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            GameResult::Checkmate { .. } => write!(f, "Checkmate"),
+            GameResult::Stalemate => write!(f, "Stalemate"),
+            GameResult::FiftyMoveRule => write!(f, "Fifty-move rule"),
+            GameResult::SeventyFiveMoveRule => write!(f, "Seventy-five-move rule"),
+            GameResult::ThreefoldRepetition => write!(f, "Threefold repetition"),
+            GameResult::FivefoldRepetition => write!(f, "Fivefold repetition"),
+            GameResult::InsufficientMaterial => write!(f, "Insufficient material"),
+            GameResult::Timeout { .. } => write!(f, "Timeout"),
+            GameResult::TimeoutVsInsufficientMaterial => {
+                write!(f, "Timeout vs insufficient material")
+            }
+            GameResult::Resignation { .. } => write!(f, "Resignation"),
+            GameResult::DrawAgreement => write!(f, "Draw agreed"),
+        }
+    }
+}
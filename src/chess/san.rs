@@ -0,0 +1,258 @@
+use super::{
+    moves::{parse_uci_move, TileMove},
+    piece::*,
+    result::GameResult,
+    tile::{get_tile_coords, get_tile_name},
+    Board, GameMoveRecord,
+};
+
+/// Move described by a SAN string: from, to and the promotion piece.
+pub type SanMove = TileMove;
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum SanError {
+    /// The string is not a move in Standard Algebraic Notation.
+    InvalidSyntax(String),
+    /// No legal move of the side on move matches the string.
+    IllegalMove(String),
+    /// More than one legal move matches the string.
+    AmbiguousMove(String),
+}
+
+impl std::fmt::Display for SanError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            SanError::InvalidSyntax(san) => write!(f, "'{}' is not a valid SAN move", san),
+            SanError::IllegalMove(san) => write!(f, "'{}' is not a legal move", san),
+            SanError::AmbiguousMove(san) => {
+                write!(f, "'{}' matches more than one legal move", san)
+            }
+        }
+    }
+}
+
+impl std::error::Error for SanError {}
+
+/// SAN of `record`, played in the position on `board`.
+pub fn record_to_san(board: &Board, record: &GameMoveRecord) -> Option<String> {
+    move_to_san(board, record.from, record.to, record.promotion)
+}
+
+/// SAN of moving the piece on `from` to `to` on `board`, `None` when the move
+/// is not legal.
+pub fn move_to_san(
+    board: &Board,
+    from: (usize, usize),
+    to: (usize, usize),
+    promotion: Option<PieceType>,
+) -> Option<String> {
+    let mut board = board.clone();
+    if !board.move_piece(from, to, promotion) {
+        return None;
+    }
+
+    board.move_records.last().map(|record| record.san.clone())
+}
+
+/// SAN of a pseudo-legal move without the check or checkmate suffix, which
+/// depends on the position after the move.
+pub(super) fn get_san_without_suffix(
+    board: &Board,
+    from: (usize, usize),
+    to: (usize, usize),
+    promotion: Option<PieceType>,
+) -> String {
+    let piece = match board.tiles[from.1][from.0].piece {
+        Some(piece) => piece,
+        None => return String::new(),
+    };
+
+    if piece.kind == PieceType::King && from.0.abs_diff(to.0) == 2 {
+        return if to.0 > from.0 { "O-O" } else { "O-O-O" }.to_string();
+    }
+
+    let mut san = String::new();
+    let is_capture = board.tiles[to.1][to.0].piece.is_some()
+        || (piece.kind == PieceType::Pawn && from.0 != to.0);
+
+    if piece.kind == PieceType::Pawn {
+        if is_capture {
+            san.push_str(&get_tile_name(from)[..1]);
+        }
+    } else {
+        san.push(piece.kind.to_char());
+
+        let from_name = get_tile_name(from);
+        let others = get_other_pieces_reaching(board, piece, from, to);
+        if !others.is_empty() {
+            if others.iter().all(|other| other.0 != from.0) {
+                san.push_str(&from_name[..1]);
+            } else if others.iter().all(|other| other.1 != from.1) {
+                san.push_str(&from_name[1..]);
+            } else {
+                san.push_str(&from_name);
+            }
+        }
+    }
+
+    if is_capture {
+        san.push('x');
+    }
+    san.push_str(&get_tile_name(to));
+
+    if let Some(kind) = promotion {
+        san.push('=');
+        san.push(kind.to_char());
+    }
+
+    san
+}
+
+/// `+` or `#` for the side on move of `board`, empty when it is not in check.
+pub(super) fn get_san_suffix(board: &Board) -> &'static str {
+    match (board.result, board.is_check) {
+        (Some(GameResult::Checkmate { .. }), _) => "#",
+        (_, Some(_)) => "+",
+        _ => "",
+    }
+}
+
+/// Tiles of the other pieces of the same kind and side as `piece` that can
+/// legally move to `to`.
+fn get_other_pieces_reaching(
+    board: &Board,
+    piece: Piece,
+    from: (usize, usize),
+    to: (usize, usize),
+) -> Vec<(usize, usize)> {
+    let mut others: Vec<(usize, usize)> = board
+        .get_legal_moves()
+        .into_iter()
+        .filter(|(other, other_to)| {
+            *other != from
+                && *other_to == to
+                && matches!(board.tiles[other.1][other.0].piece, Some(p) if p.kind == piece.kind)
+        })
+        .map(|(other, _)| other)
+        .collect();
+    others.dedup();
+
+    others
+}
+
+/// Finds the legal move described by `san` for the side on move. Check,
+/// checkmate and annotation suffixes are ignored.
+pub fn parse_san(board: &Board, san: &str) -> Result<SanMove, SanError> {
+    let invalid_syntax = || SanError::InvalidSyntax(san.to_string());
+    let illegal_move = || SanError::IllegalMove(san.to_string());
+
+    let body = san.trim().trim_end_matches(['+', '#', '!', '?']);
+    let legal_moves = board.get_legal_moves();
+
+    let castling_target = match body {
+        "O-O" | "0-0" => Some(6),
+        "O-O-O" | "0-0-0" => Some(2),
+        _ => None,
+    };
+    if let Some(target_x) = castling_target {
+        return legal_moves
+            .into_iter()
+            .find(|(from, to)| {
+                matches!(board.tiles[from.1][from.0].piece, Some(p) if p.kind == PieceType::King)
+                    && from.0 == 4
+                    && to.0 == target_x
+            })
+            .map(|(from, to)| (from, to, None))
+            .ok_or_else(illegal_move);
+    }
+
+    let (body, promotion) = match body.split_once('=') {
+        Some((body, promotion)) => {
+            let mut chars = promotion.chars();
+            let kind = chars
+                .next()
+                .and_then(PieceType::from_char)
+                .ok_or_else(invalid_syntax)?;
+            if chars.next().is_some() {
+                return Err(invalid_syntax());
+            }
+            (body, Some(kind))
+        }
+        None => match body.chars().last() {
+            Some(c) if c.is_ascii_uppercase() && body.len() > 2 => (
+                &body[..body.len() - 1],
+                Some(PieceType::from_char(c).ok_or_else(invalid_syntax)?),
+            ),
+            _ => (body, None),
+        },
+    };
+
+    let (kind, body) = match body.chars().next() {
+        Some(c) if c.is_ascii_uppercase() => (
+            PieceType::from_char(c).ok_or_else(invalid_syntax)?,
+            &body[1..],
+        ),
+        _ => (PieceType::Pawn, body),
+    };
+
+    let body: String = body.chars().filter(|c| *c != 'x' && *c != ':').collect();
+    if body.len() < 2 || !body.is_ascii() {
+        return Err(invalid_syntax());
+    }
+    let to = get_tile_coords(&body[body.len() - 2..]).ok_or_else(invalid_syntax)?;
+    let disambiguation = &body[..body.len() - 2];
+    if !disambiguation
+        .chars()
+        .all(|c| ('a'..='h').contains(&c) || ('1'..='8').contains(&c))
+    {
+        return Err(invalid_syntax());
+    }
+
+    let candidates: Vec<(usize, usize)> = legal_moves
+        .into_iter()
+        .filter(|(from, move_to)| {
+            let name = get_tile_name(*from);
+            *move_to == to
+                && matches!(board.tiles[from.1][from.0].piece, Some(p) if p.kind == kind)
+                && disambiguation.chars().all(|c| name.contains(c))
+        })
+        .map(|(from, _)| from)
+        .collect();
+
+    match candidates.as_slice() {
+        [from] if board.is_promotion_move(*from, to) == promotion.is_some() => {
+            Ok((*from, to, promotion))
+        }
+        [] | [_] => Err(illegal_move()),
+        _ => Err(SanError::AmbiguousMove(san.to_string())),
+    }
+}
+
+/// Finds the legal move described by `text` for the side on move, written
+/// either in SAN (`Nf3`) or in coordinates (`g1f3`, `e7e8q`).
+pub fn parse_move(board: &Board, text: &str) -> Result<SanMove, SanError> {
+    let text = text.trim();
+    if let Some((from, to, promotion)) = parse_uci_move(text) {
+        return if board.get_legal_moves().contains(&(from, to))
+            && board.is_promotion_move(from, to) == promotion.is_some()
+        {
+            Ok((from, to, promotion))
+        } else {
+            Err(SanError::IllegalMove(text.to_string()))
+        };
+    }
+
+    parse_san(board, text)
+}
+
+impl Board {
+    /// Plays the move described by `san` for the side on move.
+    pub fn move_piece_san(&mut self, san: &str) -> Result<(), SanError> {
+        let (from, to, promotion) = parse_san(self, san)?;
+        if !self.move_piece(from, to, promotion) {
+            return Err(SanError::IllegalMove(san.to_string()));
+        }
+
+        Ok(())
+    }
+}
@@ -0,0 +1,536 @@
+use std::{
+    sync::{
+        atomic::{AtomicBool, Ordering},
+        Arc,
+    },
+    time::{Duration, Instant},
+};
+
+use super::{evaluation::EvalParams, moves::Move, piece::*, position::Position};
+
+/// Score of giving mate right now, mates further away score less.
+pub const MATE_SCORE: i32 = 30_000;
+const INFINITY: i32 = 32_000;
+const MAX_PLY: usize = 128;
+/// Scores beyond this are mates.
+const MATE_BOUND: i32 = MATE_SCORE - MAX_PLY as i32;
+/// Deepest iteration when only the time limits the search.
+const MAX_DEPTH: u32 = 64;
+
+/// When to stop searching. With neither limit set the search runs until it is
+/// stopped through [`Searcher::get_stop_flag`].
+#[derive(Debug, Default, Copy, Clone, PartialEq, Eq)]
+pub struct SearchLimits {
+    pub depth: Option<u32>,
+    pub time: Option<Duration>,
+}
+
+impl SearchLimits {
+    pub fn depth(depth: u32) -> Self {
+        SearchLimits {
+            depth: Some(depth),
+            time: None,
+        }
+    }
+    pub fn time(time: Duration) -> Self {
+        SearchLimits {
+            depth: None,
+            time: Some(time),
+        }
+    }
+}
+
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+pub struct SearchResult {
+    /// `None` when the side on move has no legal move.
+    pub best_move: Option<Move>,
+    /// Centipawns from the view of the side on move, see [`MATE_SCORE`].
+    pub score: i32,
+    /// Depth of the last finished iteration.
+    pub depth: u32,
+    pub nodes: u64,
+}
+
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+enum Bound {
+    Exact,
+    /// The score is at least this, the search failed high.
+    Lower,
+    /// The score is at most this, no move raised alpha.
+    Upper,
+}
+
+#[derive(Debug, Copy, Clone)]
+struct TtEntry {
+    key: u64,
+    mv: Option<Move>,
+    score: i32,
+    depth: i32,
+    bound: Bound,
+}
+
+/// Results of searched positions by their Zobrist hash.
+pub struct TranspositionTable {
+    entries: Vec<Option<TtEntry>>,
+}
+
+impl TranspositionTable {
+    /// Table taking at most `size_mb` megabytes.
+    pub fn new(size_mb: usize) -> Self {
+        let count = (size_mb * 1024 * 1024 / std::mem::size_of::<Option<TtEntry>>()).max(1);
+        // A power of two, so the index is just the low bits of the key.
+        let count = 1 << (usize::BITS - 1 - count.leading_zeros());
+
+        TranspositionTable {
+            entries: vec![None; count],
+        }
+    }
+    pub fn clear(&mut self) {
+        self.entries.fill(None);
+    }
+    fn get_index(&self, key: u64) -> usize {
+        key as usize & (self.entries.len() - 1)
+    }
+    fn probe(&self, key: u64) -> Option<TtEntry> {
+        self.entries[self.get_index(key)].filter(|entry| entry.key == key)
+    }
+    fn store(&mut self, entry: TtEntry) {
+        let index = self.get_index(entry.key);
+        self.entries[index] = Some(entry);
+    }
+}
+
+/// Iterative deepening alpha-beta search with quiescence search. Keeps its
+/// transposition table and move ordering statistics between searches.
+pub struct Searcher {
+    tt: TranspositionTable,
+    /// Two quiet moves per ply that caused a beta cutoff.
+    killers: Vec<[Option<Move>; 2]>,
+    /// Cutoff statistics of quiet moves, indexed by side, from and to.
+    history: Box<[[[i32; 64]; 64]; 2]>,
+    stop: Arc<AtomicBool>,
+    deadline: Option<Instant>,
+    /// Set once the search has to stop, the iteration in progress is thrown away.
+    is_stopped: bool,
+    /// Stopping is only allowed once the first iteration has given a move.
+    can_stop: bool,
+    nodes: u64,
+    root_best_move: Option<Move>,
+    eval: EvalParams,
+}
+
+impl Default for Searcher {
+    fn default() -> Self {
+        Self::new(16)
+    }
+}
+
+impl Searcher {
+    /// Searcher with a transposition table of `tt_size_mb` megabytes.
+    pub fn new(tt_size_mb: usize) -> Self {
+        Searcher {
+            tt: TranspositionTable::new(tt_size_mb),
+            killers: vec![[None; 2]; MAX_PLY],
+            history: Box::new([[[0; 64]; 64]; 2]),
+            stop: Arc::new(AtomicBool::new(false)),
+            deadline: None,
+            is_stopped: false,
+            can_stop: false,
+            nodes: 0,
+            root_best_move: None,
+            eval: EvalParams::default(),
+        }
+    }
+    /// Flag that stops a running search once set. It stays set, so it has to
+    /// be cleared before the next search.
+    pub fn get_stop_flag(&self) -> Arc<AtomicBool> {
+        self.stop.clone()
+    }
+    /// Weights of the evaluation used by the following searches.
+    pub fn set_eval_params(&mut self, params: EvalParams) {
+        self.eval = params;
+        self.tt.clear();
+    }
+    /// Forgets everything learned in previous searches.
+    pub fn clear(&mut self) {
+        self.tt.clear();
+        self.killers.fill([None; 2]);
+        *self.history = [[[0; 64]; 64]; 2];
+    }
+    /// Finds the best move for the side on move of `position`. The position is
+    /// given back unchanged.
+    pub fn search(&mut self, position: &mut Position, limits: SearchLimits) -> SearchResult {
+        self.search_with_info(position, limits, |_, _| {})
+    }
+    /// [`Searcher::search`] calling `on_iteration` with the result and the
+    /// principal variation after every finished iteration.
+    pub fn search_with_info(
+        &mut self,
+        position: &mut Position,
+        limits: SearchLimits,
+        mut on_iteration: impl FnMut(&SearchResult, &[Move]),
+    ) -> SearchResult {
+        let started = Instant::now();
+        self.deadline = limits.time.map(|time| started + time);
+        self.is_stopped = false;
+        self.can_stop = false;
+        self.nodes = 0;
+        self.killers.fill([None; 2]);
+        for value in self.history.iter_mut().flatten().flatten() {
+            *value /= 8;
+        }
+
+        let mut result = SearchResult {
+            best_move: None,
+            score: 0,
+            depth: 0,
+            nodes: 0,
+        };
+        if position.legal_moves().is_empty() {
+            result.score = self.alpha_beta(position, 1, -INFINITY, INFINITY, 0);
+            return result;
+        }
+
+        for depth in 1..=limits.depth.unwrap_or(MAX_DEPTH).max(1) {
+            self.root_best_move = None;
+            let score = self.alpha_beta(position, depth as i32, -INFINITY, INFINITY, 0);
+            if self.is_stopped {
+                break;
+            }
+
+            result.best_move = self.root_best_move;
+            result.score = score;
+            result.depth = depth;
+            result.nodes = self.nodes;
+            self.can_stop = true;
+            on_iteration(
+                &result,
+                &self.get_principal_variation(position, result.best_move, depth),
+            );
+
+            // The next iteration takes longer than all the previous ones together.
+            if let Some(time) = limits.time {
+                if started.elapsed() > time / 2 {
+                    break;
+                }
+            }
+            if score.abs() >= MATE_BOUND {
+                break;
+            }
+        }
+
+        result.nodes = self.nodes;
+        result
+    }
+    /// Best line starting with `best_move`, followed through the moves stored
+    /// in the transposition table.
+    fn get_principal_variation(
+        &self,
+        position: &mut Position,
+        best_move: Option<Move>,
+        depth: u32,
+    ) -> Vec<Move> {
+        let mut variation = Vec::new();
+        let mut next_move = best_move;
+        while let Some(mv) = next_move {
+            // Entries can be overwritten by other positions, or lead into a loop.
+            if variation.len() >= depth as usize || !position.legal_moves().contains(&mv) {
+                break;
+            }
+            position.make_move(mv);
+            variation.push(mv);
+            next_move = self
+                .tt
+                .probe(position.get_hash())
+                .and_then(|entry| entry.mv);
+        }
+        for _ in &variation {
+            position.unmake_move();
+        }
+
+        variation
+    }
+    fn check_stop(&mut self) -> bool {
+        if self.can_stop && self.nodes.is_multiple_of(2048) {
+            self.is_stopped = self.stop.load(Ordering::Relaxed)
+                || self
+                    .deadline
+                    .is_some_and(|deadline| Instant::now() >= deadline);
+        }
+
+        self.is_stopped
+    }
+    fn alpha_beta(
+        &mut self,
+        position: &mut Position,
+        depth: i32,
+        mut alpha: i32,
+        beta: i32,
+        ply: usize,
+    ) -> i32 {
+        if self.check_stop() {
+            return 0;
+        }
+        self.nodes += 1;
+
+        if ply > 0 && (position.get_halfmove_clock() >= 100 || is_repetition(position)) {
+            return 0;
+        }
+
+        let is_check = position.is_check();
+        // Checks are searched one move deeper so mates are not missed.
+        let depth = if is_check { depth + 1 } else { depth };
+        if depth <= 0 {
+            return self.quiescence(position, alpha, beta, ply);
+        }
+        if ply >= MAX_PLY - 1 {
+            return self.eval.evaluate(position);
+        }
+
+        let key = position.get_hash();
+        let tt_entry = self.tt.probe(key);
+        if let Some(entry) = tt_entry {
+            let score = get_score_from_tt(entry.score, ply);
+            if ply > 0 && entry.depth >= depth {
+                match entry.bound {
+                    Bound::Exact => return score,
+                    Bound::Lower if score >= beta => return score,
+                    Bound::Upper if score <= alpha => return score,
+                    _ => {}
+                }
+            }
+        }
+
+        let mut moves = position.legal_moves();
+        if moves.is_empty() {
+            return if is_check {
+                -MATE_SCORE + ply as i32
+            } else {
+                0
+            };
+        }
+        self.order_moves(
+            position,
+            &mut moves,
+            tt_entry.and_then(|entry| entry.mv),
+            ply,
+        );
+
+        let original_alpha = alpha;
+        let mut best_score = -INFINITY;
+        let mut best_move = None;
+        for mv in moves {
+            position.make_move(mv);
+            let score = -self.alpha_beta(position, depth - 1, -beta, -alpha, ply + 1);
+            position.unmake_move();
+            if self.is_stopped {
+                return 0;
+            }
+
+            if score > best_score {
+                best_score = score;
+                best_move = Some(mv);
+                if score > alpha {
+                    alpha = score;
+                    if ply == 0 {
+                        self.root_best_move = Some(mv);
+                    }
+                }
+            }
+            if alpha >= beta {
+                if !mv.is_capture() && mv.get_promotion().is_none() {
+                    self.store_quiet_cutoff(position.get_side_on_move(), mv, depth, ply);
+                }
+                break;
+            }
+        }
+
+        let bound = if best_score >= beta {
+            Bound::Lower
+        } else if best_score > original_alpha {
+            Bound::Exact
+        } else {
+            Bound::Upper
+        };
+        self.tt.store(TtEntry {
+            key,
+            mv: best_move,
+            score: get_score_to_tt(best_score, ply),
+            depth,
+            bound,
+        });
+
+        best_score
+    }
+    /// Searches captures and queen promotions only, until the position is
+    /// quiet enough for the static evaluation.
+    fn quiescence(
+        &mut self,
+        position: &mut Position,
+        mut alpha: i32,
+        beta: i32,
+        ply: usize,
+    ) -> i32 {
+        if self.check_stop() {
+            return 0;
+        }
+        self.nodes += 1;
+
+        if ply >= MAX_PLY - 1 {
+            return self.eval.evaluate(position);
+        }
+
+        let is_check = position.is_check();
+        let mut best_score = -INFINITY;
+        if !is_check {
+            // Not capturing anything is an option too.
+            best_score = self.eval.evaluate(position);
+            if best_score >= beta {
+                return best_score;
+            }
+            alpha = alpha.max(best_score);
+        }
+
+        let mut moves = position.legal_moves();
+        if is_check && moves.is_empty() {
+            return -MATE_SCORE + ply as i32;
+        }
+        // In check every evasion has to be looked at.
+        if !is_check {
+            moves.retain(|mv| mv.is_capture() || mv.get_promotion() == Some(PieceType::Queen));
+        }
+        self.order_moves(position, &mut moves, None, ply);
+
+        for mv in moves {
+            position.make_move(mv);
+            let score = -self.quiescence(position, -beta, -alpha, ply + 1);
+            position.unmake_move();
+            if self.is_stopped {
+                return 0;
+            }
+
+            if score > best_score {
+                best_score = score;
+                alpha = alpha.max(score);
+                if alpha >= beta {
+                    break;
+                }
+            }
+        }
+
+        best_score
+    }
+    fn store_quiet_cutoff(&mut self, side: Side, mv: Move, depth: i32, ply: usize) {
+        let killers = &mut self.killers[ply];
+        if killers[0] != Some(mv) {
+            killers[1] = killers[0];
+            killers[0] = Some(mv);
+        }
+
+        let value = &mut self.history[side as usize][mv.get_from()][mv.get_to()];
+        *value += depth * depth;
+        // Keeps history scores below the killer moves.
+        if *value > 50_000 {
+            for value in self.history.iter_mut().flatten().flatten() {
+                *value /= 2;
+            }
+        }
+    }
+    /// Sorts the moves so that the likely best come first: the move from the
+    /// transposition table, captures by MVV-LVA, promotions, killer moves and
+    /// then quiet moves by their history score.
+    fn order_moves(
+        &self,
+        position: &Position,
+        moves: &mut [Move],
+        tt_move: Option<Move>,
+        ply: usize,
+    ) {
+        let side = position.get_side_on_move();
+        moves.sort_by_cached_key(|mv| {
+            let score = if Some(*mv) == tt_move {
+                1_000_000
+            } else if mv.is_capture() {
+                let victim = if mv.is_en_passant() {
+                    PieceType::Pawn
+                } else {
+                    position
+                        .get_piece_at(mv.get_to())
+                        .map_or(PieceType::Pawn, |(kind, _)| kind)
+                };
+                let attacker = position
+                    .get_piece_at(mv.get_from())
+                    .map_or(PieceType::Pawn, |(kind, _)| kind);
+                // Most valuable victim first, the least valuable attacker breaks ties.
+                200_000 + get_mvv_lva_rank(victim) * 10 - get_mvv_lva_rank(attacker)
+            } else if mv.get_promotion().is_some() {
+                150_000
+            } else if self.killers[ply][0] == Some(*mv) {
+                100_001
+            } else if self.killers[ply][1] == Some(*mv) {
+                100_000
+            } else {
+                self.history[side as usize][mv.get_from()][mv.get_to()]
+            };
+
+            std::cmp::Reverse(score)
+        });
+    }
+}
+
+fn get_mvv_lva_rank(kind: PieceType) -> i32 {
+    match kind {
+        PieceType::Pawn => 1,
+        PieceType::Knight => 2,
+        PieceType::Bishop => 3,
+        PieceType::Rook => 4,
+        PieceType::Queen => 5,
+        PieceType::King => 6,
+    }
+}
+
+/// Moves until mate for a mate score, negative when the side on move is
+/// getting mated.
+pub fn get_mate_in(score: i32) -> Option<i32> {
+    if score >= MATE_BOUND {
+        Some((MATE_SCORE - score + 1) / 2)
+    } else if score <= -MATE_BOUND {
+        Some(-(MATE_SCORE + score) / 2)
+    } else {
+        None
+    }
+}
+
+/// Mates are stored by their distance from the stored position, not the root.
+fn get_score_to_tt(score: i32, ply: usize) -> i32 {
+    if score >= MATE_BOUND {
+        score + ply as i32
+    } else if score <= -MATE_BOUND {
+        score - ply as i32
+    } else {
+        score
+    }
+}
+
+fn get_score_from_tt(score: i32, ply: usize) -> i32 {
+    if score >= MATE_BOUND {
+        score - ply as i32
+    } else if score <= -MATE_BOUND {
+        score + ply as i32
+    } else {
+        score
+    }
+}
+
+/// Whether the position already occurred since the last capture or pawn move,
+/// with the same side on move.
+fn is_repetition(position: &Position) -> bool {
+    let hash = position.get_hash();
+    position
+        .get_hash_history()
+        .rev()
+        .take(position.get_halfmove_clock() as usize)
+        .skip(1)
+        .step_by(2)
+        .any(|previous| previous == hash)
+}
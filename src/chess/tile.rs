@@ -1,47 +1,41 @@
-use raylib::prelude::*;
+use super::{Piece, CHESSBOARD_SIZE, X_AXIS_LABELS, Y_AXIS_LABELS};
 
-use super::{Piece, PiecesImagesType, CHESSBOARD_SIZE};
-
-#[derive(Debug, Copy, Clone)]
+#[derive(Debug, Copy, Clone, Default)]
 pub struct Tile {
-    pub bg: Option<Color>,
-    pub color: Color,
     pub piece: Option<Piece>,
 }
 
 impl Tile {
     pub fn new() -> Self {
-        Tile {
-            color: Color::BLACK,
-            bg: None,
-            piece: None,
-        }
+        Tile { piece: None }
     }
+}
 
-    pub fn render(
-        &self,
-        d: &mut RaylibDrawHandle,
-        (x, y): (i32, i32),
-        pieces_images: &PiecesImagesType,
-    ) {
-        match self.piece {
-            Some(piece) => d.draw_texture_ex(
-                pieces_images.get(&(piece.kind, piece.side)).unwrap(),
-                Vector2 {
-                    x: x as f32,
-                    y: y as f32,
-                },
-                0.0,
-                1.0,
-                Color::WHITE,
-            ),
-            // Some(piece) => d.draw_text(&piece.to_string(), x, y, 18, Color::PURPLE),
-            None => {}
-        }
-    }
-    pub fn clear_bg(&mut self) {
-        self.bg = None;
+/// Name of the tile on `(x, y)` in algebraic notation, e.g. `(4, 6)` is `"e2"`.
+pub fn get_tile_name((x, y): (usize, usize)) -> String {
+    format!(
+        "{}{}",
+        X_AXIS_LABELS[x].to_lowercase(),
+        Y_AXIS_LABELS[CHESSBOARD_SIZE - 1 - y]
+    )
+}
+
+/// Coordinates of a tile given by its algebraic name, e.g. `"e2"` is `(4, 6)`.
+pub fn get_tile_coords(name: &str) -> Option<(usize, usize)> {
+    let mut chars = name.chars();
+    let (file, rank) = (chars.next()?, chars.next()?);
+    if chars.next().is_some() {
+        return None;
     }
+
+    let x = X_AXIS_LABELS
+        .iter()
+        .position(|label| label.eq_ignore_ascii_case(&file.to_string()))?;
+    let y = Y_AXIS_LABELS
+        .iter()
+        .position(|label| *label == rank.to_string())?;
+
+    Some((x, CHESSBOARD_SIZE - 1 - y))
 }
 
 pub struct TilesIter<'a> {
@@ -67,7 +61,7 @@ impl<'a> Iterator for TilesIter<'a> {
             self.index_y += 1;
         }
 
-        return Some(tuple);
+        Some(tuple)
     }
 }
 
@@ -89,7 +83,7 @@ impl<'a> Iterator for TilesIterMut<'a> {
             (
                 self.index_x,
                 self.index_y,
-                &mut *(&mut self.tiles[self.index_x][self.index_y] as *mut Tile),
+                &mut *(&mut self.tiles[self.index_y][self.index_x] as *mut Tile),
             )
         };
 
@@ -99,6 +93,6 @@ impl<'a> Iterator for TilesIterMut<'a> {
             self.index_y += 1;
         }
 
-        return Some(tuple);
+        Some(tuple)
     }
 }
@@ -0,0 +1,397 @@
+use std::{
+    io::{BufRead, Write},
+    sync::{
+        atomic::{AtomicBool, Ordering},
+        mpsc::{self, RecvTimeoutError, Sender},
+        Arc, Mutex,
+    },
+    thread::{self, JoinHandle},
+    time::{Duration, Instant},
+};
+
+use super::{
+    evaluation::EvalParams,
+    fen::STARTING_POSITION_FEN,
+    moves::Move,
+    piece::Side,
+    position::Position,
+    search::{get_mate_in, SearchLimits, SearchResult, Searcher},
+    Board,
+};
+
+pub const ENGINE_NAME: &str = "Chessio";
+pub const ENGINE_AUTHOR: &str = "the Chessio developers";
+
+const DEFAULT_HASH_MB: usize = 16;
+const MAX_HASH_MB: usize = 1024;
+/// Time kept back when thinking on the clock, so the move arrives in time.
+const MOVE_OVERHEAD: Duration = Duration::from_millis(50);
+/// Moves the remaining time is split into when `movestogo` is not given.
+const DEFAULT_MOVES_TO_GO: u32 = 30;
+
+/// Search running on its own thread. It prints `bestmove` when it finishes
+/// and gives the searcher back.
+struct RunningSearch {
+    handle: JoinHandle<Searcher>,
+    stop: Arc<AtomicBool>,
+    /// Keeps the search from printing `bestmove` until it is dropped, set
+    /// for `go infinite` and `go ponder`.
+    hold: Option<Sender<()>>,
+    is_infinite: bool,
+    /// Thinking time on the clock after `ponderhit`, `None` once the ponder
+    /// move was played or when the search is not pondering.
+    ponder_time: Option<Option<Duration>>,
+    /// Stops the search once its time after `ponderhit` is up. Dropping the
+    /// sender lets the timer end early.
+    timer: Option<(Sender<()>, JoinHandle<()>)>,
+}
+
+/// Engine speaking the Universal Chess Interface. Commands are passed to
+/// [`UciEngine::handle_command`] line by line and the replies are written to
+/// the output, which the search thread shares.
+pub struct UciEngine<W: Write + Send + 'static> {
+    out: Arc<Mutex<W>>,
+    position: Position,
+    /// `None` while it is lent to the running search.
+    searcher: Option<Searcher>,
+    search: Option<RunningSearch>,
+    hash_mb: usize,
+    eval_params: EvalParams,
+}
+
+impl<W: Write + Send + 'static> UciEngine<W> {
+    pub fn new(out: W) -> Self {
+        UciEngine {
+            out: Arc::new(Mutex::new(out)),
+            position: Board::new().get_position(),
+            searcher: Some(Searcher::new(DEFAULT_HASH_MB)),
+            search: None,
+            hash_mb: DEFAULT_HASH_MB,
+            eval_params: EvalParams::default(),
+        }
+    }
+    /// Handles one line of input. Returns `false` once the engine should quit.
+    pub fn handle_command(&mut self, line: &str) -> bool {
+        let tokens: Vec<&str> = line.split_whitespace().collect();
+        match tokens.first().copied() {
+            Some("uci") => self.send_id(),
+            Some("isready") => send(&self.out, "readyok"),
+            Some("ucinewgame") => {
+                self.stop_search();
+                self.get_searcher().clear();
+                self.position = Board::new().get_position();
+            }
+            Some("setoption") => self.set_option(&tokens[1..]),
+            Some("position") => self.set_position(&tokens[1..]),
+            Some("go") => self.go(&tokens[1..]),
+            Some("stop") => self.stop_search(),
+            Some("ponderhit") => self.ponder_hit(),
+            Some("quit") => {
+                self.stop_search();
+                return false;
+            }
+            // Unknown commands are ignored, as the protocol asks.
+            _ => {}
+        }
+
+        true
+    }
+    fn send_id(&self) {
+        send(&self.out, &format!("id name {}", ENGINE_NAME));
+        send(&self.out, &format!("id author {}", ENGINE_AUTHOR));
+        send(
+            &self.out,
+            &format!(
+                "option name Hash type spin default {} min 1 max {}",
+                DEFAULT_HASH_MB, MAX_HASH_MB
+            ),
+        );
+        send(&self.out, "option name Clear Hash type button");
+        send(&self.out, "option name Ponder type check default false");
+        send(
+            &self.out,
+            "option name EvalFile type string default <empty>",
+        );
+        send(&self.out, "uciok");
+    }
+    fn new_searcher(&self) -> Searcher {
+        let mut searcher = Searcher::new(self.hash_mb);
+        searcher.set_eval_params(self.eval_params.clone());
+        searcher
+    }
+    /// Searcher to change settings on. A running search is stopped first, an
+    /// infinite or pondering one would never hand the searcher back.
+    fn get_searcher(&mut self) -> &mut Searcher {
+        self.stop_search();
+        if self.searcher.is_none() {
+            self.searcher = Some(self.new_searcher());
+        }
+        self.searcher
+            .as_mut()
+            .expect("the searcher was just created")
+    }
+    /// Stops the running search and waits for it to print its move.
+    fn stop_search(&mut self) {
+        if let Some(mut search) = self.search.take() {
+            search.stop.store(true, Ordering::Relaxed);
+            search.hold = None;
+            // A search that panicked took the searcher with it.
+            self.searcher = search.handle.join().ok();
+            if let Some((sender, timer)) = search.timer.take() {
+                drop(sender);
+                let _ = timer.join();
+            }
+        }
+    }
+    /// The opponent played the move the engine was pondering on: the search
+    /// goes on as a normal one, on the clock it was given.
+    fn ponder_hit(&mut self) {
+        let search = match &mut self.search {
+            Some(search) => search,
+            None => return,
+        };
+        let time = match search.ponder_time.take() {
+            Some(time) => time,
+            None => return,
+        };
+
+        if !search.is_infinite {
+            search.hold = None;
+        }
+        if let Some(time) = time {
+            let (sender, receiver) = mpsc::channel::<()>();
+            let stop = search.stop.clone();
+            let timer = thread::spawn(move || {
+                if receiver.recv_timeout(time) == Err(RecvTimeoutError::Timeout) {
+                    stop.store(true, Ordering::Relaxed);
+                }
+            });
+            search.timer = Some((sender, timer));
+        }
+    }
+    /// `setoption name <name> [value <value>]`
+    fn set_option(&mut self, tokens: &[&str]) {
+        if tokens.first() != Some(&"name") {
+            send_info_string(&self.out, "setoption needs a name");
+            return;
+        }
+        let value_index = tokens
+            .iter()
+            .position(|token| *token == "value")
+            .unwrap_or(tokens.len());
+        let name = tokens[1..value_index].join(" ");
+        let value = tokens.get(value_index + 1..).unwrap_or_default().join(" ");
+
+        match name.to_ascii_lowercase().as_str() {
+            "hash" => match value.parse::<usize>() {
+                Ok(hash_mb) => {
+                    self.stop_search();
+                    self.hash_mb = hash_mb.clamp(1, MAX_HASH_MB);
+                    self.searcher = Some(self.new_searcher());
+                }
+                Err(_) => send_info_string(&self.out, &format!("invalid Hash value '{}'", value)),
+            },
+            "clear hash" => self.get_searcher().clear(),
+            // Only tells whether the GUI will send `go ponder`.
+            "ponder" => {}
+            "evalfile" => {
+                let params = if value.is_empty() || value == "<empty>" {
+                    Ok(EvalParams::default())
+                } else {
+                    EvalParams::load(&value)
+                };
+                match params {
+                    Ok(params) => {
+                        self.eval_params = params.clone();
+                        self.get_searcher().set_eval_params(params);
+                    }
+                    Err(err) => send_info_string(
+                        &self.out,
+                        &format!("could not load EvalFile '{}': {}", value, err),
+                    ),
+                }
+            }
+            _ => send_info_string(&self.out, &format!("unknown option '{}'", name)),
+        }
+    }
+    /// `position (startpos | fen <fen>) [moves <move>...]`
+    fn set_position(&mut self, tokens: &[&str]) {
+        let moves_index = tokens
+            .iter()
+            .position(|token| *token == "moves")
+            .unwrap_or(tokens.len());
+        let fen = match tokens.first() {
+            Some(&"startpos") => STARTING_POSITION_FEN.to_string(),
+            Some(&"fen") => tokens[1..moves_index].join(" "),
+            _ => {
+                send_info_string(&self.out, "position needs startpos or fen");
+                return;
+            }
+        };
+
+        let mut position = match Board::from_fen(&fen) {
+            Ok(board) => board.get_position(),
+            Err(err) => {
+                send_info_string(&self.out, &format!("invalid FEN '{}': {}", fen, err));
+                return;
+            }
+        };
+        for name in tokens.iter().skip(moves_index + 1) {
+            let mv = position
+                .legal_moves()
+                .into_iter()
+                .find(|mv| mv.to_string() == *name);
+            match mv {
+                Some(mv) => position.make_move(mv),
+                None => {
+                    send_info_string(&self.out, &format!("illegal move '{}'", name));
+                    return;
+                }
+            }
+        }
+
+        self.position = position;
+    }
+    /// `go [depth <plies>] [movetime <ms>] [wtime <ms>] [btime <ms>] [winc <ms>]
+    /// [binc <ms>] [movestogo <moves>] [infinite] [ponder]`
+    ///
+    /// An infinite or pondering search keeps its `bestmove` until `stop`, a
+    /// pondering one also until `ponderhit`.
+    fn go(&mut self, tokens: &[&str]) {
+        self.stop_search();
+
+        let get_value = |name: &str| {
+            let index = tokens.iter().position(|token| *token == name)?;
+            tokens.get(index + 1)?.parse::<u64>().ok()
+        };
+        let side = self.position.get_side_on_move();
+        let (time_left, increment) = match side {
+            Side::White => (get_value("wtime"), get_value("winc")),
+            Side::Black => (get_value("btime"), get_value("binc")),
+        };
+
+        let mut limits = SearchLimits {
+            depth: get_value("depth").map(|depth| depth as u32),
+            time: get_value("movetime").map(Duration::from_millis),
+        };
+        if limits.time.is_none() {
+            if let Some(time_left) = time_left {
+                limits.time = Some(get_time_for_move(
+                    Duration::from_millis(time_left),
+                    Duration::from_millis(increment.unwrap_or(0)),
+                    get_value("movestogo").map(|moves| moves as u32),
+                ));
+            }
+        }
+
+        let is_infinite = tokens.contains(&"infinite");
+        let ponder_time = tokens.contains(&"ponder").then_some(limits.time);
+        if is_infinite || ponder_time.is_some() {
+            // The clock only runs for a pondering search after `ponderhit`.
+            limits.time = None;
+        }
+        if is_infinite {
+            limits.depth = None;
+        }
+        let (hold, held) = if is_infinite || ponder_time.is_some() {
+            let (sender, receiver) = mpsc::channel::<()>();
+            (Some(sender), Some(receiver))
+        } else {
+            (None, None)
+        };
+
+        let mut searcher = match self.searcher.take() {
+            Some(searcher) => searcher,
+            None => self.new_searcher(),
+        };
+        let stop = searcher.get_stop_flag();
+        stop.store(false, Ordering::Relaxed);
+        let mut position = self.position.clone();
+        let out = self.out.clone();
+
+        let handle = thread::spawn(move || {
+            let started = Instant::now();
+            let result = searcher.search_with_info(&mut position, limits, |result, pv| {
+                send(&out, &get_info_line(result, pv, started.elapsed()));
+            });
+            if let Some(held) = held {
+                // Nothing is ever sent, the sender is dropped to let go.
+                let _ = held.recv();
+            }
+            let best_move = result
+                .best_move
+                .map(|mv| mv.to_string())
+                .unwrap_or_else(|| "0000".to_string());
+            send(&out, &format!("bestmove {}", best_move));
+
+            searcher
+        });
+
+        self.search = Some(RunningSearch {
+            handle,
+            stop,
+            hold,
+            is_infinite,
+            ponder_time,
+            timer: None,
+        });
+    }
+}
+
+/// Thinking time out of `time_left` on the clock.
+fn get_time_for_move(
+    time_left: Duration,
+    increment: Duration,
+    moves_to_go: Option<u32>,
+) -> Duration {
+    let usable = time_left.saturating_sub(MOVE_OVERHEAD);
+    let moves_to_go = moves_to_go.unwrap_or(DEFAULT_MOVES_TO_GO).max(1);
+
+    (usable / moves_to_go + increment * 3 / 4)
+        .min(usable)
+        .max(Duration::from_millis(1))
+}
+
+fn get_info_line(result: &SearchResult, pv: &[Move], elapsed: Duration) -> String {
+    let score = match get_mate_in(result.score) {
+        Some(moves) => format!("mate {}", moves),
+        None => format!("cp {}", result.score),
+    };
+    let millis = elapsed.as_millis().max(1) as u64;
+    let pv: Vec<String> = pv.iter().map(Move::to_string).collect();
+
+    format!(
+        "info depth {} score {} nodes {} time {} nps {} pv {}",
+        result.depth,
+        score,
+        result.nodes,
+        millis,
+        result.nodes * 1000 / millis,
+        pv.join(" ")
+    )
+}
+
+fn send<W: Write>(out: &Mutex<W>, line: &str) {
+    let mut out = out.lock().unwrap_or_else(|err| err.into_inner());
+    // Nobody is listening anymore when the output is closed.
+    let _ = writeln!(out, "{}", line);
+    let _ = out.flush();
+}
+
+fn send_info_string<W: Write>(out: &Mutex<W>, text: &str) {
+    send(out, &format!("info string {}", text));
+}
+
+/// Runs the engine on the standard input and output until `quit` or the end
+/// of the input.
+pub fn run() {
+    let mut engine = UciEngine::new(std::io::stdout());
+    for line in std::io::stdin().lock().lines() {
+        match line {
+            Ok(line) if engine.handle_command(&line) => {}
+            _ => break,
+        }
+    }
+
+    engine.stop_search();
+}
@@ -0,0 +1,361 @@
+use std::{
+    collections::VecDeque,
+    io::{BufRead, BufReader, Write},
+    process::{Child, ChildStdin, Command, Stdio},
+    sync::mpsc::{self, Receiver, RecvTimeoutError, TryRecvError},
+    thread,
+    time::{Duration, Instant},
+};
+
+use super::{
+    clock::{ChessClock, Increment, TimeSource},
+    fen::STARTING_POSITION_FEN,
+    moves::{get_uci_move_name, parse_uci_move},
+    piece::Side,
+    san::SanMove,
+    search::SearchLimits,
+    Board,
+};
+
+/// How long the engine has to answer `uci` and `isready`.
+const HANDSHAKE_TIMEOUT: Duration = Duration::from_secs(10);
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum UciClientError {
+    /// The engine executable could not be started.
+    Spawn(String),
+    /// Writing to the engine failed.
+    Io(String),
+    /// The engine did not send the given reply in time.
+    Timeout(&'static str),
+    /// The engine closed its output, it has most likely crashed.
+    Exited,
+    /// The engine does not declare an option with this name.
+    UnknownOption(String),
+    /// The engine sent a `bestmove` that is not a move in UCI notation.
+    InvalidMove(String),
+    /// The engine replied with a move that is not legal in the position.
+    IllegalMove(String),
+}
+
+impl std::fmt::Display for UciClientError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            UciClientError::Spawn(err) => write!(f, "could not start the engine: {}", err),
+            UciClientError::Io(err) => write!(f, "could not talk to the engine: {}", err),
+            UciClientError::Timeout(reply) => write!(f, "the engine did not send '{}'", reply),
+            UciClientError::Exited => write!(f, "the engine has exited"),
+            UciClientError::UnknownOption(name) => {
+                write!(f, "the engine has no option '{}'", name)
+            }
+            UciClientError::InvalidMove(mv) => write!(f, "'{}' is not a UCI move", mv),
+            UciClientError::IllegalMove(mv) => {
+                write!(f, "the engine played '{}', which is not legal", mv)
+            }
+        }
+    }
+}
+
+impl std::error::Error for UciClientError {}
+
+/// Option declared by the engine in its reply to `uci`.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct UciOption {
+    pub name: String,
+    /// `check`, `spin`, `combo`, `button` or `string`.
+    pub kind: String,
+    pub default: Option<String>,
+}
+
+impl UciOption {
+    /// Parses the words after `option` in `option name <name> type <kind>
+    /// [default <value>] ...`.
+    fn parse(line: &str) -> Option<Self> {
+        let tokens: Vec<&str> = line.split_whitespace().collect();
+        let get_value = |keyword: &str| {
+            let start = tokens.iter().position(|token| *token == keyword)? + 1;
+            let end = tokens[start..]
+                .iter()
+                .position(|token| matches!(*token, "type" | "default" | "min" | "max" | "var"))
+                .map_or(tokens.len(), |end| start + end);
+            Some(tokens[start..end].join(" "))
+        };
+
+        Some(UciOption {
+            name: get_value("name").filter(|name| !name.is_empty())?,
+            kind: get_value("type")?,
+            default: get_value("default"),
+        })
+    }
+}
+
+/// Separate engine process spoken to over the Universal Chess Interface.
+/// Replies are read on a background thread, so waiting for a move does not
+/// block the caller.
+pub struct UciClient {
+    child: Child,
+    stdin: ChildStdin,
+    lines: Receiver<String>,
+    /// `bestmove` replies read while waiting for another reply.
+    unread: VecDeque<String>,
+    name: Option<String>,
+    options: Vec<UciOption>,
+    /// Number of `go` commands whose `bestmove` has not arrived yet.
+    searches: usize,
+    /// Number of those searches that were stopped, their moves are dropped.
+    stopped_searches: usize,
+}
+
+impl UciClient {
+    /// Starts the engine on `path` and waits for it to finish the `uci`
+    /// handshake.
+    pub fn spawn(path: &str, args: &[String]) -> Result<Self, UciClientError> {
+        let mut child = Command::new(path)
+            .args(args)
+            .stdin(Stdio::piped())
+            .stdout(Stdio::piped())
+            .stderr(Stdio::null())
+            .spawn()
+            .map_err(|err| UciClientError::Spawn(err.to_string()))?;
+        let stdin = child.stdin.take().expect("stdin of the engine is piped");
+        let stdout = child.stdout.take().expect("stdout of the engine is piped");
+
+        let (sender, lines) = mpsc::channel();
+        thread::spawn(move || {
+            for line in BufReader::new(stdout).lines().map_while(Result::ok) {
+                if sender.send(line).is_err() {
+                    break;
+                }
+            }
+        });
+
+        let mut client = UciClient {
+            child,
+            stdin,
+            lines,
+            unread: VecDeque::new(),
+            name: None,
+            options: Vec::new(),
+            searches: 0,
+            stopped_searches: 0,
+        };
+        client.send("uci")?;
+        for line in client.read_until("uciok")? {
+            if let Some(name) = line.strip_prefix("id name ") {
+                client.name = Some(name.trim().to_string());
+            } else if let Some(option) = line.strip_prefix("option ") {
+                client.options.extend(UciOption::parse(option));
+            }
+        }
+
+        Ok(client)
+    }
+    /// Name the engine reported, if any.
+    pub fn get_name(&self) -> Option<&str> {
+        self.name.as_deref()
+    }
+    pub fn get_options(&self) -> &[UciOption] {
+        &self.options
+    }
+    fn send(&mut self, command: &str) -> Result<(), UciClientError> {
+        writeln!(self.stdin, "{}", command)
+            .and_then(|_| self.stdin.flush())
+            .map_err(|err| UciClientError::Io(err.to_string()))
+    }
+    /// Lines the engine sends up to and including `reply`.
+    fn read_until(&mut self, reply: &'static str) -> Result<Vec<String>, UciClientError> {
+        let deadline = Instant::now() + HANDSHAKE_TIMEOUT;
+        let mut lines = Vec::new();
+        loop {
+            let timeout = deadline.saturating_duration_since(Instant::now());
+            match self.lines.recv_timeout(timeout) {
+                Ok(line) => {
+                    if line.starts_with("bestmove") {
+                        self.unread.push_back(line);
+                        continue;
+                    }
+                    let is_reply = line.trim() == reply;
+                    lines.push(line);
+                    if is_reply {
+                        return Ok(lines);
+                    }
+                }
+                Err(RecvTimeoutError::Timeout) => return Err(UciClientError::Timeout(reply)),
+                Err(RecvTimeoutError::Disconnected) => return Err(UciClientError::Exited),
+            }
+        }
+    }
+    /// Waits until the engine has processed every command sent so far.
+    pub fn wait_until_ready(&mut self) -> Result<(), UciClientError> {
+        self.send("isready")?;
+        self.read_until("readyok").map(|_| ())
+    }
+    /// Sets an option the engine declared. Buttons take no value.
+    pub fn set_option(&mut self, name: &str, value: Option<&str>) -> Result<(), UciClientError> {
+        let option = self
+            .options
+            .iter()
+            .find(|option| option.name.eq_ignore_ascii_case(name))
+            .ok_or_else(|| UciClientError::UnknownOption(name.to_string()))?;
+
+        let command = match value {
+            Some(value) => format!("setoption name {} value {}", option.name, value),
+            None => format!("setoption name {}", option.name),
+        };
+        self.send(&command)
+    }
+    /// Tells the engine the next search belongs to a different game.
+    pub fn new_game(&mut self) -> Result<(), UciClientError> {
+        self.stop()?;
+        self.send("ucinewgame")?;
+        self.wait_until_ready()
+    }
+    /// Starts searching the position on `board`. The move arrives through
+    /// [`UciClient::poll_best_move`].
+    pub fn start_search(
+        &mut self,
+        board: &Board,
+        limits: SearchLimits,
+        clock: Option<ClockTimes>,
+    ) -> Result<(), UciClientError> {
+        self.send(&get_position_command(board))?;
+        self.send(&get_go_command(limits, clock))?;
+        self.searches += 1;
+
+        Ok(())
+    }
+    /// Stops the running search, its move will not be returned.
+    pub fn stop(&mut self) -> Result<(), UciClientError> {
+        if self.searches > self.stopped_searches {
+            self.stopped_searches = self.searches;
+            self.send("stop")?;
+        }
+
+        Ok(())
+    }
+    pub fn is_searching(&self) -> bool {
+        self.searches > self.stopped_searches
+    }
+    /// Move found by the search that was not stopped. `Ok(None)` while the
+    /// engine is thinking, `Ok(Some(None))` when it has no move because the
+    /// game is over.
+    pub fn poll_best_move(&mut self) -> Result<Option<Option<SanMove>>, UciClientError> {
+        loop {
+            let line = match self.unread.pop_front() {
+                Some(line) => line,
+                None => match self.lines.try_recv() {
+                    Ok(line) => line,
+                    Err(TryRecvError::Empty) => return Ok(None),
+                    Err(TryRecvError::Disconnected) => return Err(UciClientError::Exited),
+                },
+            };
+
+            let mut tokens = line.split_whitespace();
+            if tokens.next() != Some("bestmove") || self.searches == 0 {
+                continue;
+            }
+            self.searches -= 1;
+            if self.stopped_searches > 0 {
+                self.stopped_searches -= 1;
+                continue;
+            }
+
+            return match tokens.next() {
+                None | Some("0000") | Some("(none)") => Ok(Some(None)),
+                Some(mv) => match parse_uci_move(mv) {
+                    Some(mv) => Ok(Some(Some(mv))),
+                    None => Err(UciClientError::InvalidMove(mv.to_string())),
+                },
+            };
+        }
+    }
+}
+
+impl Drop for UciClient {
+    fn drop(&mut self) {
+        let _ = self.send("quit");
+        // Give the engine a moment to exit on its own before killing it.
+        let deadline = Instant::now() + Duration::from_millis(500);
+        while Instant::now() < deadline {
+            if let Ok(Some(_)) = self.child.try_wait() {
+                return;
+            }
+            thread::sleep(Duration::from_millis(10));
+        }
+        let _ = self.child.kill();
+        let _ = self.child.wait();
+    }
+}
+
+/// `position` command with the game on `board`, from its starting position
+/// and its moves.
+pub fn get_position_command(board: &Board) -> String {
+    let mut command = if board.starting_fen == STARTING_POSITION_FEN {
+        "position startpos".to_string()
+    } else {
+        format!("position fen {}", board.starting_fen)
+    };
+    if !board.move_records.is_empty() {
+        command.push_str(" moves");
+        for record in &board.move_records {
+            command.push(' ');
+            command.push_str(&get_uci_move_name(record.from, record.to, record.promotion));
+        }
+    }
+
+    command
+}
+
+/// Time left on the clocks of a timed game and the time added per move, in
+/// the order White, Black.
+#[derive(Debug, Default, Copy, Clone, PartialEq, Eq)]
+pub struct ClockTimes {
+    pub remaining: [Duration; 2],
+    pub increments: [Duration; 2],
+}
+
+impl ClockTimes {
+    /// Times on `clock`. A delay or a Bronstein increment counts as the most
+    /// a move can get back.
+    pub fn from_clock<T: TimeSource>(clock: &ChessClock<T>) -> Self {
+        let sides = [Side::White, Side::Black];
+        ClockTimes {
+            remaining: sides.map(|side| clock.get_remaining(side)),
+            increments: sides.map(|side| match clock.get_increment(side) {
+                Increment::None => Duration::ZERO,
+                Increment::Fischer(time) | Increment::Bronstein(time) | Increment::Delay(time) => {
+                    time
+                }
+            }),
+        }
+    }
+}
+
+/// `go` command searching within `limits`. In a timed game the engine gets
+/// the clock times instead of a fixed time per move, without either it
+/// searches until stopped.
+pub fn get_go_command(limits: SearchLimits, clock: Option<ClockTimes>) -> String {
+    let mut command = "go".to_string();
+    if let Some(depth) = limits.depth {
+        command.push_str(&format!(" depth {}", depth));
+    }
+    match clock {
+        Some(clock) => command.push_str(&format!(
+            " wtime {} btime {} winc {} binc {}",
+            clock.remaining[0].as_millis(),
+            clock.remaining[1].as_millis(),
+            clock.increments[0].as_millis(),
+            clock.increments[1].as_millis()
+        )),
+        None => {
+            if let Some(time) = limits.time {
+                command.push_str(&format!(" movetime {}", time.as_millis()));
+            }
+            if limits == SearchLimits::default() {
+                command.push_str(" infinite");
+            }
+        }
+    }
+
+    command
+}
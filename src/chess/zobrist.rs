@@ -0,0 +1,155 @@
+use std::sync::OnceLock;
+
+use super::{
+    bitboard::{get_pawn_attacks, get_squares, Square},
+    piece::*,
+    position::Position,
+};
+
+/// Number of keys in the `Random64` table of the Polyglot book format.
+pub const POLYGLOT_KEY_COUNT: usize = 781;
+
+/// Seed of the keys used when no other layout is asked for.
+const DEFAULT_SEED: u64 = 0x6368_6573_7369_6f21;
+
+/// Random 64-bit keys of the pieces on each square, the castling rights, the
+/// en passant file and the side on move. The hash of a position is the XOR of
+/// the keys of everything in it.
+///
+/// Like in Polyglot, the side key is part of the hash when White is on move and
+/// the en passant file only when a pawn of the side on move stands next to the
+/// pawn that has just advanced two squares.
+#[derive(Clone, PartialEq, Eq)]
+pub struct ZobristKeys {
+    /// Indexed by `Side as usize`, `PieceType as usize` and the square.
+    pieces: [[[u64; 64]; 6]; 2],
+    /// In the FEN order, see [`Position::get_castling_rights`].
+    castling: [u64; 4],
+    en_passant: [u64; 8],
+    side: u64,
+}
+
+static DEFAULT_KEYS: OnceLock<ZobristKeys> = OnceLock::new();
+
+/// splitmix64 generator, only used to fill the default keys.
+struct KeyRng(u64);
+
+impl KeyRng {
+    fn next(&mut self) -> u64 {
+        self.0 = self.0.wrapping_add(0x9e37_79b9_7f4a_7c15);
+        let mut z = self.0;
+        z = (z ^ (z >> 30)).wrapping_mul(0xbf58_476d_1ce4_e5b9);
+        z = (z ^ (z >> 27)).wrapping_mul(0x94d0_49bb_1331_11eb);
+        z ^ (z >> 31)
+    }
+}
+
+impl ZobristKeys {
+    /// Keys generated from `seed`.
+    pub fn from_seed(seed: u64) -> Self {
+        let mut rng = KeyRng(seed);
+        let mut keys = ZobristKeys {
+            pieces: [[[0; 64]; 6]; 2],
+            castling: [0; 4],
+            en_passant: [0; 8],
+            side: 0,
+        };
+
+        for key in keys.pieces.iter_mut().flatten().flatten() {
+            *key = rng.next();
+        }
+        for key in keys.castling.iter_mut().chain(&mut keys.en_passant) {
+            *key = rng.next();
+        }
+        keys.side = rng.next();
+
+        keys
+    }
+    /// Keys in the Polyglot layout, so the hashes match the keys of Polyglot
+    /// opening books. `random64` is the `Random64` table of the format
+    /// specification, in its order.
+    pub fn from_polyglot(random64: &[u64; POLYGLOT_KEY_COUNT]) -> Self {
+        let mut keys = ZobristKeys {
+            pieces: [[[0; 64]; 6]; 2],
+            castling: [0; 4],
+            en_passant: [0; 8],
+            side: random64[780],
+        };
+
+        for side in [Side::White, Side::Black] {
+            for kind in PieceType::ALL {
+                // Polyglot orders the pieces black pawn, white pawn, black
+                // knight, ... and numbers squares from a1 like `Square` does.
+                let polyglot_kind = match kind {
+                    PieceType::Pawn => 0,
+                    PieceType::Knight => 1,
+                    PieceType::Bishop => 2,
+                    PieceType::Rook => 3,
+                    PieceType::Queen => 4,
+                    PieceType::King => 5,
+                };
+                let offset = 64 * (2 * polyglot_kind + (side == Side::White) as usize);
+                keys.pieces[side as usize][kind as usize]
+                    .copy_from_slice(&random64[offset..offset + 64]);
+            }
+        }
+        keys.castling.copy_from_slice(&random64[768..772]);
+        keys.en_passant.copy_from_slice(&random64[772..780]);
+
+        keys
+    }
+    /// Keys positions use unless they are given others.
+    pub fn get_default() -> &'static ZobristKeys {
+        DEFAULT_KEYS.get_or_init(|| ZobristKeys::from_seed(DEFAULT_SEED))
+    }
+    pub fn get_piece_key(&self, square: Square, kind: PieceType, side: Side) -> u64 {
+        self.pieces[side as usize][kind as usize][square]
+    }
+    /// Keys of everything but the pieces: castling rights, en passant file and
+    /// side on move.
+    pub(super) fn get_state_hash(&self, position: &Position) -> u64 {
+        let side = position.get_side_on_move();
+        let mut hash = 0;
+
+        for (right, key) in position.get_castling_rights().iter().zip(self.castling) {
+            if *right {
+                hash ^= key;
+            }
+        }
+        if let Some(target) = position.get_en_passant_target() {
+            if get_pawn_attacks(side.opposite(), target)
+                & position.get_pieces(PieceType::Pawn, side)
+                != 0
+            {
+                hash ^= self.en_passant[target % 8];
+            }
+        }
+        if side == Side::White {
+            hash ^= self.side;
+        }
+
+        hash
+    }
+    /// Hash of `position` computed from scratch.
+    pub fn get_hash(&self, position: &Position) -> u64 {
+        let mut hash = self.get_state_hash(position);
+        for side in [Side::White, Side::Black] {
+            for kind in PieceType::ALL {
+                for square in get_squares(position.get_pieces(kind, side)) {
+                    hash ^= self.get_piece_key(square, kind, side);
+                }
+            }
+        }
+
+        hash
+    }
+}
+
+/// The tables are long, so only the side key is shown.
+impl std::fmt::Debug for ZobristKeys {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("ZobristKeys")
+            .field("side", &format_args!("{:#018x}", self.side))
+            .finish_non_exhaustive()
+    }
+}
@@ -0,0 +1,123 @@
+use std::{
+    sync::{
+        atomic::{AtomicBool, Ordering},
+        mpsc::{self, Receiver, TryRecvError},
+        Arc,
+    },
+    thread,
+};
+
+use chessio::chess::{
+    evaluation::EvalParams,
+    moves::Move,
+    piece::Side,
+    search::{SearchLimits, SearchResult, Searcher},
+    Board,
+};
+
+use super::get_position_key;
+
+/// Search running on a background thread, it sends the searcher back with
+/// the result.
+struct Thinking {
+    receiver: Receiver<(Searcher, SearchResult)>,
+    stop: Arc<AtomicBool>,
+    /// Number of moves and hash of the position being searched.
+    position_key: (usize, u64),
+}
+
+/// Computer opponent. Searches on its own thread, so the window keeps
+/// rendering while it thinks.
+pub struct Computer {
+    pub side: Side,
+    pub limits: SearchLimits,
+    /// Kept to build a new searcher with when a search panics.
+    eval_params: EvalParams,
+    /// `None` while the searcher is lent to the thinking thread.
+    searcher: Option<Searcher>,
+    thinking: Option<Thinking>,
+}
+
+impl Computer {
+    pub fn new(side: Side, limits: SearchLimits) -> Self {
+        Computer {
+            side,
+            limits,
+            eval_params: EvalParams::default(),
+            searcher: Some(Searcher::default()),
+            thinking: None,
+        }
+    }
+    /// Weights the computer evaluates positions with. Has no effect on a
+    /// search that is already running.
+    pub fn set_eval_params(&mut self, params: EvalParams) {
+        self.eval_params = params.clone();
+        if let Some(searcher) = &mut self.searcher {
+            searcher.set_eval_params(params);
+        }
+    }
+    fn new_searcher(&self) -> Searcher {
+        let mut searcher = Searcher::default();
+        searcher.set_eval_params(self.eval_params.clone());
+        searcher
+    }
+    pub fn is_thinking(&self) -> bool {
+        self.thinking.is_some()
+    }
+    /// Starts searching the position on `board`, unless a search is running.
+    pub fn start_thinking(&mut self, board: &Board) {
+        let mut searcher = match self.searcher.take() {
+            Some(searcher) => searcher,
+            None => return,
+        };
+
+        let (sender, receiver) = mpsc::channel();
+        let stop = searcher.get_stop_flag();
+        stop.store(false, Ordering::Relaxed);
+        let mut position = board.get_position_with_history();
+        let limits = self.limits;
+        thread::spawn(move || {
+            let result = searcher.search(&mut position, limits);
+            // The receiver is gone only when the game was closed.
+            let _ = sender.send((searcher, result));
+        });
+
+        self.thinking = Some(Thinking {
+            receiver,
+            stop,
+            position_key: get_position_key(board),
+        });
+    }
+    /// Asks the running search to finish, its move will not be played.
+    pub fn stop_thinking(&mut self) {
+        if let Some(thinking) = &mut self.thinking {
+            thinking.stop.store(true, Ordering::Relaxed);
+            thinking.position_key = (usize::MAX, 0);
+        }
+    }
+    /// Move found by a finished search, as long as `board` is still in the
+    /// position the search started from.
+    pub fn poll_move(&mut self, board: &Board) -> Option<Move> {
+        let thinking = self.thinking.as_ref()?;
+        let (searcher, result) = match thinking.receiver.try_recv() {
+            Ok(finished) => finished,
+            Err(TryRecvError::Empty) => return None,
+            Err(TryRecvError::Disconnected) => {
+                // The search thread panicked and took the searcher with it.
+                self.thinking = None;
+                self.searcher = Some(self.new_searcher());
+                return None;
+            }
+        };
+
+        let position_key = thinking.position_key;
+        self.thinking = None;
+        self.searcher = Some(searcher);
+
+        if position_key == get_position_key(board) {
+            result.best_move
+        } else {
+            None
+        }
+    }
+}
@@ -0,0 +1,97 @@
+use std::collections::HashMap;
+
+use chessio::chess::piece::{PieceType, Side};
+use raylib::{color::Color, RaylibHandle, RaylibThread};
+
+use super::{PiecesImagesType, TileColorSchema};
+
+// Sizes of the default window, the layout of any other size is scaled from
+// them (see `Layout`).
+pub const CHESSBOARD_WIDTH: i32 = 1000;
+pub const LEFT_SIDE_PADDING: i32 = 50;
+pub const WINDOW_WIDTH: i32 = 1400;
+pub const WINDOW_HEIGHT: i32 = 1050;
+/// Smallest size the window can be resized to.
+pub const MIN_WINDOW_WIDTH: i32 = 560;
+pub const MIN_WINDOW_HEIGHT: i32 = 420;
+/// Size of the pieces in the captured pieces trays of the sidebar.
+pub const CAPTURED_PIECE_SIZE: i32 = 32;
+/// Top of the captured pieces trays, under the move list.
+pub const CAPTURED_PIECES_Y: i32 = 800;
+/// Top of the box moves can be typed into.
+pub const MOVE_TEXT_Y: i32 = 885;
+/// Longest text the move box takes, enough for any move.
+pub const MOVE_TEXT_MAX_LENGTH: usize = 10;
+/// PGN file games are appended to when saved from the GUI.
+pub const SAVED_GAMES_PATH: &str = "./games.pgn";
+
+// Piece image sets
+
+pub fn get_default_piece_images(rl: &mut RaylibHandle, thread: &RaylibThread) -> PiecesImagesType {
+    HashMap::from([
+        (
+            (PieceType::Rook, Side::Black),
+            rl.load_texture(thread, "./static/pieces/pngs/RookBlack.png")
+                .unwrap(),
+        ),
+        (
+            (PieceType::Knight, Side::Black),
+            rl.load_texture(thread, "./static/pieces/pngs/KnightBlack.png")
+                .unwrap(),
+        ),
+        (
+            (PieceType::Bishop, Side::Black),
+            rl.load_texture(thread, "./static/pieces/pngs/BishopBlack.png")
+                .unwrap(),
+        ),
+        (
+            (PieceType::Queen, Side::Black),
+            rl.load_texture(thread, "./static/pieces/pngs/QueenBlack.png")
+                .unwrap(),
+        ),
+        (
+            (PieceType::King, Side::Black),
+            rl.load_texture(thread, "./static/pieces/pngs/KingBlack.png")
+                .unwrap(),
+        ),
+        (
+            (PieceType::Pawn, Side::Black),
+            rl.load_texture(thread, "./static/pieces/pngs/PawnBlack.png")
+                .unwrap(),
+        ),
+        (
+            (PieceType::Rook, Side::White),
+            rl.load_texture(thread, "./static/pieces/pngs/RookWhite.png")
+                .unwrap(),
+        ),
+        (
+            (PieceType::Knight, Side::White),
+            rl.load_texture(thread, "./static/pieces/pngs/KnightWhite.png")
+                .unwrap(),
+        ),
+        (
+            (PieceType::Bishop, Side::White),
+            rl.load_texture(thread, "./static/pieces/pngs/BishopWhite.png")
+                .unwrap(),
+        ),
+        (
+            (PieceType::Queen, Side::White),
+            rl.load_texture(thread, "./static/pieces/pngs/QueenWhite.png")
+                .unwrap(),
+        ),
+        (
+            (PieceType::King, Side::White),
+            rl.load_texture(thread, "./static/pieces/pngs/KingWhite.png")
+                .unwrap(),
+        ),
+        (
+            (PieceType::Pawn, Side::White),
+            rl.load_texture(thread, "./static/pieces/pngs/PawnWhite.png")
+                .unwrap(),
+        ),
+    ])
+}
+
+// Board sets
+
+pub const DEFAULT_TILE_COLOR_SCHEMA: TileColorSchema = (Color::WHITE, Color::BLACK);
@@ -0,0 +1,70 @@
+use chessio::chess::{
+    clock::ChessClock,
+    piece::Side,
+    san::SanMove,
+    search::SearchLimits,
+    uci_client::{ClockTimes, UciClient, UciClientError},
+    Board,
+};
+
+use super::get_position_key;
+
+/// External UCI engine playing one of the sides. The engine runs as its own
+/// process, so the window keeps rendering while it thinks.
+pub struct Engine {
+    pub side: Side,
+    pub limits: SearchLimits,
+    client: UciClient,
+    /// Number of moves and hash of the position being searched.
+    position_key: Option<(usize, u64)>,
+}
+
+impl Engine {
+    pub fn new(client: UciClient, side: Side, limits: SearchLimits) -> Self {
+        Engine {
+            side,
+            limits,
+            client,
+            position_key: None,
+        }
+    }
+    pub fn get_name(&self) -> &str {
+        self.client.get_name().unwrap_or("Engine")
+    }
+    pub fn is_thinking(&self) -> bool {
+        self.position_key.is_some()
+    }
+    /// Sends the game on `board` to the engine and lets it search, within the
+    /// time on `clock` in a timed game.
+    pub fn start_thinking(
+        &mut self,
+        board: &Board,
+        clock: Option<&ChessClock>,
+    ) -> Result<(), UciClientError> {
+        let clock = clock.map(ClockTimes::from_clock);
+        self.client.start_search(board, self.limits, clock)?;
+        self.position_key = Some(get_position_key(board));
+
+        Ok(())
+    }
+    /// Stops the running search, its move will not be played.
+    pub fn stop_thinking(&mut self) -> Result<(), UciClientError> {
+        self.position_key = None;
+        self.client.stop()
+    }
+    /// Move the engine replied with, as long as `board` is still in the
+    /// position the search started from.
+    pub fn poll_move(&mut self, board: &Board) -> Result<Option<SanMove>, UciClientError> {
+        let best_move = match self.client.poll_best_move()? {
+            Some(best_move) => best_move,
+            None => return Ok(None),
+        };
+
+        let position_key = self.position_key.take();
+        if position_key == Some(get_position_key(board)) {
+            Ok(best_move)
+        } else {
+            Ok(None)
+        }
+    }
+}
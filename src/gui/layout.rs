@@ -0,0 +1,57 @@
+use chessio::chess::constants::CHESSBOARD_SIZE;
+
+use super::constants::*;
+
+/// Sizes and positions of everything in the window, computed from its size
+/// every frame. Everything is the default window scaled to fit.
+#[derive(Debug, Copy, Clone, PartialEq)]
+pub struct Layout {
+    pub window_width: i32,
+    pub window_height: i32,
+    /// Scale of the default window.
+    pub scale: f32,
+    /// Width of the rank labels left of the board and height of the file
+    /// labels under it. The board starts right of the rank labels.
+    pub label_size: i32,
+    pub tile_size: i32,
+    pub board_size: i32,
+    /// Left edge of the texts in the sidebar.
+    pub sidebar_x: i32,
+}
+
+impl Default for Layout {
+    fn default() -> Self {
+        Self::new(WINDOW_WIDTH, WINDOW_HEIGHT)
+    }
+}
+
+impl Layout {
+    pub fn new(window_width: i32, window_height: i32) -> Self {
+        let scale = (window_width as f32 / WINDOW_WIDTH as f32)
+            .min(window_height as f32 / WINDOW_HEIGHT as f32)
+            .max(0.01);
+        // Whole pixels per tile, so the tiles line up with the labels.
+        let tile_size = ((CHESSBOARD_WIDTH as f32 * scale) as i32 / CHESSBOARD_SIZE as i32).max(1);
+        let board_size = tile_size * CHESSBOARD_SIZE as i32;
+        let label_size = (LEFT_SIDE_PADDING as f32 * scale).round() as i32;
+
+        Layout {
+            window_width,
+            window_height,
+            scale,
+            label_size,
+            tile_size,
+            board_size,
+            sidebar_x: label_size + board_size + (20.0 * scale).round() as i32,
+        }
+    }
+    /// `size` in the default window, a position or a font size, scaled to this
+    /// one.
+    pub fn scaled(&self, size: i32) -> i32 {
+        (size as f32 * self.scale).round() as i32
+    }
+    /// Mouse position relative to the top left corner of the board.
+    pub fn get_board_coords(&self, (x, y): (f32, f32)) -> (f32, f32) {
+        (x - self.label_size as f32, y)
+    }
+}
@@ -0,0 +1,934 @@
+pub mod computer;
+pub mod constants;
+pub mod engine;
+pub mod layout;
+pub mod move_list;
+
+use std::{collections::HashMap, fs::OpenOptions, io::Write};
+
+use chessio::chess::{
+    clock::ChessClock,
+    constants::*,
+    moves::get_uci_move_name,
+    network::{NetworkError, Peer},
+    pgn::PgnGame,
+    piece::*,
+    san::parse_move,
+    tile::Tile,
+    uci_client::UciClientError,
+    Board, GameMoveRecord,
+};
+use computer::Computer;
+use constants::*;
+use engine::Engine;
+use layout::Layout;
+use move_list::MoveList;
+use raylib::prelude::*;
+
+pub type PiecesImagesType = HashMap<(PieceType, Side), Texture2D>;
+pub type TileColorSchema = (Color, Color);
+
+/// Number of moves and hash of the position on `board`. The computer and the
+/// engine compare it to tell whether the position they searched is still on
+/// the board.
+pub fn get_position_key(board: &Board) -> (usize, u64) {
+    (board.move_records.len(), board.get_hash())
+}
+
+/// Raylib front end for a [`Board`]. Owns everything needed to draw the game
+/// (textures, tile colors, highlights) and translates mouse input into moves.
+pub struct Game {
+    pub board: Board,
+    pub pieces_images: PiecesImagesType,
+    pub tile_colors: [[Color; CHESSBOARD_SIZE]; CHESSBOARD_SIZE],
+    pub tile_bgs: [[Option<Color>; CHESSBOARD_SIZE]; CHESSBOARD_SIZE],
+    pub hovered_piece_coords: Option<(usize, usize)>,
+    /// Piece picked by a click, it moves to the next tile clicked.
+    pub selected_piece_coords: Option<(usize, usize)>,
+    /// The dragged piece was already selected, releasing it in place
+    /// deselects it.
+    deselect_on_release: bool,
+    /// Pawn move (from, to) waiting for the player to pick a promotion piece.
+    pub pending_promotion: Option<((usize, usize), (usize, usize))>,
+    /// Index in [`PROMOTION_PIECES`] of the piece the keyboard picks for the
+    /// pending promotion.
+    promotion_choice: usize,
+    /// Moves taken back by [`Game::undo`], the next one to redo is last.
+    pub redo_moves: Vec<GameMoveRecord>,
+    /// Plays one of the sides when set.
+    pub computer: Option<Computer>,
+    /// External UCI engine playing one of the sides when set.
+    pub engine: Option<Engine>,
+    /// Connection to the other player when playing over the network.
+    pub network: Option<Peer>,
+    /// Clocks of both sides, the game is untimed without them.
+    pub clock: Option<ChessClock>,
+    /// Number of moves played when the clock was last updated.
+    clock_move_count: usize,
+    /// Side drawn at the bottom of the board.
+    pub orientation: Side,
+    /// Sizes of the window the game is drawn in, see [`Game::resize`].
+    pub layout: Layout,
+    /// Move typed into the move box, `None` while the box is not focused.
+    pub move_text: Option<String>,
+    /// Why the last typed move was not played.
+    pub move_text_error: Option<String>,
+    /// Screen tile highlighted in keyboard cursor mode, `None` outside of it.
+    pub keyboard_cursor: Option<(usize, usize)>,
+    pub move_list: MoveList,
+    /// Number of moves and the board after them while an earlier position of
+    /// the game is shown. The game itself goes on in `board`.
+    viewed_position: Option<(usize, Board)>,
+}
+
+impl Game {
+    pub fn new(
+        rl: &mut RaylibHandle,
+        thread: &RaylibThread,
+        custom_imgs: Option<PiecesImagesType>,
+        custom_tile_color_schema: Option<TileColorSchema>,
+    ) -> Self {
+        let mut tile_colors = [[Color::BLACK; CHESSBOARD_SIZE]; CHESSBOARD_SIZE];
+
+        let color_schema = custom_tile_color_schema.unwrap_or(DEFAULT_TILE_COLOR_SCHEMA);
+        for (y, row) in tile_colors.iter_mut().enumerate() {
+            for (x, color) in row.iter_mut().enumerate() {
+                if (x + y % 2) % 2 == 0 {
+                    *color = color_schema.0;
+                } else {
+                    *color = color_schema.1;
+                }
+            }
+        }
+
+        Game {
+            board: Board::new(),
+            pieces_images: custom_imgs.unwrap_or_else(|| get_default_piece_images(rl, thread)),
+            tile_colors,
+            tile_bgs: [[None; CHESSBOARD_SIZE]; CHESSBOARD_SIZE],
+            hovered_piece_coords: None,
+            selected_piece_coords: None,
+            deselect_on_release: false,
+            pending_promotion: None,
+            promotion_choice: 0,
+            redo_moves: Vec::new(),
+            computer: None,
+            engine: None,
+            network: None,
+            clock: None,
+            clock_move_count: 0,
+            orientation: Side::White,
+            layout: Layout::default(),
+            move_text: None,
+            move_text_error: None,
+            keyboard_cursor: None,
+            move_list: MoveList::default(),
+            viewed_position: None,
+        }
+    }
+    /// Board that is drawn, an earlier position while one is viewed.
+    pub fn get_shown_board(&self) -> &Board {
+        match &self.viewed_position {
+            Some((_, board)) => board,
+            None => &self.board,
+        }
+    }
+    /// Number of moves played in the position that is drawn.
+    pub fn get_shown_ply(&self) -> usize {
+        match &self.viewed_position {
+            Some((ply, _)) => *ply,
+            None => self.board.move_records.len(),
+        }
+    }
+    pub fn is_viewing_past(&self) -> bool {
+        self.viewed_position.is_some()
+    }
+    /// Shows the position after the first `ply` moves, or the current one
+    /// when all of them are asked for. New moves cannot be made while viewing
+    /// an earlier position.
+    pub fn show_ply(&mut self, ply: usize) {
+        self.cancel_move_input();
+
+        if ply >= self.board.move_records.len() {
+            self.viewed_position = None;
+            return;
+        }
+        if matches!(&self.viewed_position, Some((viewed, _)) if *viewed == ply) {
+            return;
+        }
+
+        let mut board = self.board.clone();
+        while board.move_records.len() > ply {
+            board.undo_move();
+        }
+        self.viewed_position = Some((ply, board));
+    }
+    /// Shows the position after the clicked move of the move list. Returns
+    /// whether a move was clicked.
+    pub fn click_move_list(&mut self, coords: (f32, f32)) -> bool {
+        match self
+            .move_list
+            .get_move_on_coords(&self.layout, &self.board, coords)
+        {
+            Some(index) => {
+                self.show_ply(index + 1);
+                true
+            }
+            None => false,
+        }
+    }
+    pub fn render_move_list(&mut self, d: &mut RaylibDrawHandle) {
+        let shown_ply = self.get_shown_ply();
+        self.move_list
+            .render(d, &self.layout, &self.board, shown_ply);
+    }
+    /// Draws the move box, with the typed move while it is focused and a hint
+    /// on the keyboard controls otherwise.
+    pub fn render_move_text(&self, d: &mut RaylibDrawHandle) {
+        let layout = &self.layout;
+        let x = layout.sidebar_x;
+        let y = layout.scaled(MOVE_TEXT_Y);
+        let width = layout.window_width - x - layout.scaled(20);
+        let height = layout.scaled(36);
+        let (text, color) = match (&self.move_text, self.keyboard_cursor) {
+            (Some(text), _) => (format!("{}_", text), Color::WHITE),
+            (None, _) if self.pending_promotion.is_some() => (
+                "Q, R, B, N or arrows and Enter, Esc to cancel".to_string(),
+                Color::LIGHTGRAY,
+            ),
+            (None, Some(_)) => ("Arrows and Enter, C to stop".to_string(), Color::LIGHTGRAY),
+            (None, None) => (
+                "Tab to type a move, C for cursor".to_string(),
+                Color::LIGHTGRAY,
+            ),
+        };
+
+        if self.move_text.is_some() {
+            d.draw_rectangle(x, y, width, height, Color::new(0, 0, 0, 120));
+        }
+        d.draw_rectangle_lines(x, y, width, height, Color::WHITE);
+        d.draw_text(
+            &text,
+            x + layout.scaled(8),
+            y + layout.scaled(8),
+            layout.scaled(20),
+            color,
+        );
+        if let Some(error) = &self.move_text_error {
+            d.draw_text(
+                error,
+                x,
+                y + layout.scaled(42),
+                layout.scaled(20),
+                Color::RED,
+            );
+        }
+    }
+    /// Draws the pieces each side has captured in the shown position, with the
+    /// material advantage next to the side that is ahead.
+    pub fn render_captured_pieces(&self, d: &mut RaylibDrawHandle) {
+        let board = self.get_shown_board();
+        let balance = board.get_material_balance();
+        let layout = &self.layout;
+        let piece_size = layout.scaled(CAPTURED_PIECE_SIZE);
+
+        for (index, side) in [Side::White, Side::Black].into_iter().enumerate() {
+            let y = layout.scaled(CAPTURED_PIECES_Y + index as i32 * (CAPTURED_PIECE_SIZE + 10));
+            let mut x = layout.sidebar_x;
+            let mut last_kind = None;
+            for kind in board.get_captured_pieces(side) {
+                // Pieces of a kind overlap, kinds are set apart.
+                if last_kind.is_some() {
+                    x += if last_kind == Some(kind) {
+                        piece_size / 2
+                    } else {
+                        piece_size
+                    };
+                }
+                last_kind = Some(kind);
+                if let Some(img) = self.pieces_images.get(&(kind, side.opposite())) {
+                    d.draw_texture_ex(
+                        img,
+                        Vector2 {
+                            x: x as f32,
+                            y: y as f32,
+                        },
+                        0.0,
+                        piece_size as f32 / img.width as f32,
+                        Color::WHITE,
+                    );
+                }
+            }
+
+            let advantage = match side {
+                Side::White => balance,
+                Side::Black => -balance,
+            };
+            if advantage > 0 {
+                if last_kind.is_some() {
+                    x += piece_size + layout.scaled(5);
+                }
+                d.draw_text(
+                    &format!("+{}", advantage),
+                    x,
+                    y + piece_size / 2 - layout.scaled(12),
+                    layout.scaled(24),
+                    Color::WHITE,
+                );
+            }
+        }
+    }
+    /// Stops viewing a position whose moves have been taken back. Moves are
+    /// only ever taken back from the end, so the ones before stay valid.
+    fn drop_taken_back_view(&mut self) {
+        if matches!(&self.viewed_position, Some((ply, _)) if *ply >= self.board.move_records.len())
+        {
+            self.viewed_position = None;
+        }
+    }
+    /// Lays the game out for a window of `width` by `height`. Called every
+    /// frame.
+    pub fn resize(&mut self, width: i32, height: i32) {
+        if (self.layout.window_width, self.layout.window_height) != (width, height) {
+            self.layout = Layout::new(width, height);
+        }
+    }
+    pub fn flip_board(&mut self) {
+        self.orientation = self.orientation.opposite();
+    }
+    /// Turns the board so that the side a human plays is at the bottom. Two
+    /// humans at one board play from White's side.
+    pub fn orient_to_player(&mut self) {
+        let computer_side = self
+            .computer
+            .as_ref()
+            .map(|computer| computer.side)
+            .or_else(|| self.engine.as_ref().map(|engine| engine.side));
+        self.orientation = match (&self.network, computer_side) {
+            (Some(peer), _) => peer.side,
+            (None, Some(side)) => side.opposite(),
+            (None, None) => Side::White,
+        };
+    }
+    /// Tile of the screen grid showing the board tile `(x, y)`. Flipping is its
+    /// own inverse, so this also maps a screen tile back to the board.
+    pub fn get_screen_tile(&self, (x, y): (usize, usize)) -> (usize, usize) {
+        match self.orientation {
+            Side::White => (x, y),
+            Side::Black => (CHESSBOARD_SIZE - 1 - x, CHESSBOARD_SIZE - 1 - y),
+        }
+    }
+    pub fn render(&self, d: &mut RaylibDrawHandle) {
+        let size = self.get_tile_actuall_size() as usize;
+
+        for (x, y, _) in self.get_shown_board().tiles_iter() {
+            let (screen_x, screen_y) = self.get_screen_tile((x, y));
+            d.draw_rectangle(
+                self.layout.label_size + (screen_x * size) as i32,
+                (screen_y * size) as i32,
+                size as i32,
+                size as i32,
+                self.tile_bgs[y][x].unwrap_or(if self.selected_piece_coords == Some((x, y)) {
+                    Color::GOLD
+                } else {
+                    self.tile_colors[y][x]
+                }),
+            );
+        }
+    }
+    pub fn render_pieces(&self, d: &mut RaylibDrawHandle) {
+        let size = self.get_tile_actuall_size();
+
+        for (x, y, tile) in self.get_shown_board().tiles_iter() {
+            if self.hovered_piece_coords == Some((x, y)) {
+                continue;
+            }
+
+            if let Some(piece) = tile.piece {
+                let (screen_x, screen_y) = self.get_screen_tile((x, y));
+                let img = self.pieces_images.get(&(piece.kind, piece.side)).unwrap();
+                d.draw_texture_ex(
+                    img,
+                    Vector2 {
+                        x: (self.layout.label_size + screen_x as i32 * size) as f32,
+                        y: (screen_y as i32 * size) as f32,
+                    },
+                    0.0,
+                    size as f32 / img.width as f32,
+                    Color::WHITE,
+                );
+            }
+        }
+    }
+    fn get_tile_actuall_size(&self) -> i32 {
+        self.layout.tile_size
+    }
+    pub fn render_available_moves(&mut self, d: &mut RaylibDrawHandle) {
+        if let Some(coords) = self.hovered_piece_coords.or(self.selected_piece_coords) {
+            let tile_size = self.get_tile_actuall_size();
+
+            let moves = self
+                .board
+                .get_piece_available_moves_with_check((coords.0 as i32, coords.1 as i32));
+            for mov in moves {
+                let (screen_x, screen_y) = self.get_screen_tile(mov);
+                d.draw_circle(
+                    self.layout.label_size + (screen_x as i32) * tile_size + tile_size / 2,
+                    (screen_y as i32) * tile_size + tile_size / 2,
+                    self.layout.scaled(10) as f32,
+                    Color::GRAY,
+                );
+            }
+        }
+    }
+    pub fn render_piece_at_coords(
+        &self,
+        d: &mut RaylibDrawHandle,
+        (piece, side): (PieceType, Side),
+        (x, y): (f32, f32),
+    ) {
+        let piece_img = self.pieces_images.get(&(piece, side));
+        let tile_size = self.get_tile_actuall_size();
+        if let Some(img) = piece_img {
+            d.draw_texture_ex(
+                img,
+                Vector2 {
+                    x: x - (tile_size / 2) as f32,
+                    y: y - (tile_size / 2) as f32,
+                },
+                0.0,
+                tile_size as f32 / img.width as f32,
+                Color::WHITE,
+            )
+        }
+    }
+    pub fn get_tile_on_coords(&self, (x, y): (f32, f32)) -> Option<(&Tile, (usize, usize))> {
+        let tile_x = (x / self.get_tile_actuall_size() as f32) as i32;
+        let tile_y = (y / self.get_tile_actuall_size() as f32) as i32;
+
+        if x >= 0.0 && y >= 0.0 && Board::is_tile_in_board((tile_x, tile_y)) {
+            let (tile_x, tile_y) = self.get_screen_tile((tile_x as usize, tile_y as usize));
+            return Some((
+                &self.get_shown_board().tiles[tile_y][tile_x],
+                (tile_x, tile_y),
+            ));
+        }
+
+        None
+    }
+    /// Appends the game in PGN to the file on `path`, separated from the games
+    /// already saved there by an empty line.
+    pub fn save_pgn(&self, path: &str) -> std::io::Result<()> {
+        let mut file = OpenOptions::new().create(true).append(true).open(path)?;
+        let mut game = PgnGame::from_board(&self.board);
+        game.set_tag("Site", "Chessio");
+
+        writeln!(file, "{}", game.to_pgn())
+    }
+    pub fn clear_tile_bgs(&mut self) {
+        self.tile_bgs = [[None; CHESSBOARD_SIZE]; CHESSBOARD_SIZE];
+    }
+    //TODO:
+    pub fn highlight_tile_by_coords(&mut self, (x, y): (f32, f32)) {
+        if let Some((_, (tile_x, tile_y))) = self.get_tile_on_coords((x, y)) {
+            self.tile_bgs[tile_y][tile_x] = Some(Color::RED);
+        }
+    }
+    //TODO:
+    pub fn start_drag_event(&mut self, (x, y): (f32, f32)) {
+        let tile = self
+            .get_tile_on_coords((x, y))
+            .map(|(tile, coords)| (tile.piece, coords));
+
+        // A click on a destination of the selected piece moves it there.
+        if let (Some(from), Some((_, to))) = (self.selected_piece_coords, tile) {
+            if from != to
+                && self
+                    .board
+                    .get_piece_available_moves_with_check((from.0 as i32, from.1 as i32))
+                    .contains(&to)
+            {
+                self.cancel_move_input();
+                self.move_or_pick_promotion(from, to);
+                return;
+            }
+        }
+
+        let (piece, coords) = match tile {
+            Some((Some(piece), coords)) => (piece, coords),
+            _ => {
+                self.cancel_move_input();
+                return;
+            }
+        };
+
+        if self.is_player_on_move() && piece.side == self.board.get_side_on_move() {
+            self.hovered_piece_coords = Some(coords);
+            self.deselect_on_release = self.selected_piece_coords == Some(coords);
+            self.selected_piece_coords = Some(coords);
+        } else {
+            self.cancel_move_input();
+        }
+    }
+
+    /// Drops the dragged piece. Releasing it where it was picked up leaves it
+    /// selected for a click on its destination, or deselects it when it was
+    /// already selected.
+    pub fn end_drag_event(&mut self, (x, y): (f32, f32)) {
+        let from = if let Some(coords) = self.hovered_piece_coords.take() {
+            coords
+        } else {
+            return;
+        };
+
+        match self.get_tile_on_coords((x, y)) {
+            Some((_, to)) if to == from => {
+                if self.deselect_on_release {
+                    self.selected_piece_coords = None;
+                }
+            }
+            Some((_, to)) => {
+                self.selected_piece_coords = None;
+                self.move_or_pick_promotion(from, to);
+            }
+            None => self.selected_piece_coords = None,
+        }
+    }
+    /// Whether the player at this window can make a move now.
+    pub fn is_player_on_move(&self) -> bool {
+        self.board.result.is_none()
+            && !self.is_viewing_past()
+            && !self.is_computer_on_move()
+            && !self.is_remote_player_on_move()
+    }
+    /// Focuses the move box, or leaves it and throws away what was typed.
+    pub fn toggle_move_text(&mut self) {
+        self.move_text = match self.move_text {
+            Some(_) => None,
+            None => Some(String::new()),
+        };
+        self.move_text_error = None;
+    }
+    pub fn type_move_char(&mut self, c: char) {
+        if let Some(text) = &mut self.move_text {
+            if c.is_ascii_graphic() && text.len() < MOVE_TEXT_MAX_LENGTH {
+                text.push(c);
+            }
+        }
+    }
+    pub fn delete_move_char(&mut self) {
+        if let Some(text) = &mut self.move_text {
+            text.pop();
+        }
+    }
+    /// Plays the typed move, written in SAN or in coordinates. The box stays
+    /// focused for the next move.
+    pub fn submit_move_text(&mut self) {
+        let text = match &mut self.move_text {
+            Some(text) if !text.is_empty() => std::mem::take(text),
+            _ => return,
+        };
+
+        self.move_text_error = if !self.is_player_on_move() {
+            Some("It is not your move".to_string())
+        } else {
+            match parse_move(&self.board, &text) {
+                Ok((from, to, promotion)) => {
+                    self.cancel_move_input();
+                    if self.make_move(from, to, promotion) {
+                        None
+                    } else {
+                        Some(format!("'{}' could not be played", text))
+                    }
+                }
+                Err(err) => Some(err.to_string()),
+            }
+        };
+    }
+    /// Turns the keyboard cursor mode on, starting on the selected piece, or
+    /// off.
+    pub fn toggle_keyboard_cursor(&mut self) {
+        self.keyboard_cursor = match self.keyboard_cursor {
+            Some(_) => None,
+            None => Some(
+                self.selected_piece_coords
+                    .map(|coords| self.get_screen_tile(coords))
+                    .unwrap_or((4, 6)),
+            ),
+        };
+    }
+    /// Moves the keyboard cursor by `(dx, dy)` tiles on the screen, it stays on
+    /// the board.
+    pub fn move_keyboard_cursor(&mut self, (dx, dy): (i32, i32)) {
+        if let Some((x, y)) = &mut self.keyboard_cursor {
+            let last = CHESSBOARD_SIZE as i32 - 1;
+            *x = (*x as i32 + dx).clamp(0, last) as usize;
+            *y = (*y as i32 + dy).clamp(0, last) as usize;
+        }
+    }
+    /// Center of the tile under the keyboard cursor, in the coordinates the
+    /// mouse events take.
+    fn get_keyboard_cursor_coords(&self) -> Option<(f32, f32)> {
+        let tile_size = self.get_tile_actuall_size();
+        self.keyboard_cursor.map(|(x, y)| {
+            (
+                (x as i32 * tile_size + tile_size / 2) as f32,
+                (y as i32 * tile_size + tile_size / 2) as f32,
+            )
+        })
+    }
+    pub fn highlight_keyboard_cursor(&mut self) {
+        if let Some(coords) = self.get_keyboard_cursor_coords() {
+            self.highlight_tile_by_coords(coords);
+        }
+    }
+    /// Picks up the piece under the keyboard cursor or drops the picked up
+    /// piece there, the same as a click on the tile.
+    pub fn press_keyboard_cursor(&mut self) {
+        if let Some(coords) = self.get_keyboard_cursor_coords() {
+            if self.pending_promotion.is_some() {
+                return;
+            }
+            self.start_drag_event(coords);
+            self.end_drag_event(coords);
+        }
+    }
+    /// Plays the move, or lets the player pick the piece first when it is a
+    /// legal promotion.
+    fn move_or_pick_promotion(&mut self, from: (usize, usize), to: (usize, usize)) {
+        if self.board.is_promotion_move(from, to) {
+            let moves = self
+                .board
+                .get_piece_available_moves_with_check((from.0 as i32, from.1 as i32));
+            if moves.contains(&to) {
+                self.pending_promotion = Some((from, to));
+                self.promotion_choice = 0;
+            }
+        } else {
+            self.make_move(from, to, None);
+        }
+    }
+    /// Drops the dragged or selected piece and any promotion waiting for a
+    /// piece to be picked.
+    pub fn cancel_move_input(&mut self) {
+        self.hovered_piece_coords = None;
+        self.selected_piece_coords = None;
+        self.pending_promotion = None;
+    }
+    fn get_promotion_picker_tile_position(&self, index: usize) -> (i32, i32) {
+        let tile_size = self.get_tile_actuall_size();
+        let picker_width = tile_size * PROMOTION_PIECES.len() as i32;
+
+        (
+            (self.layout.board_size - picker_width) / 2 + index as i32 * tile_size,
+            (self.layout.board_size - tile_size) / 2,
+        )
+    }
+    pub fn render_promotion_picker(&self, d: &mut RaylibDrawHandle) {
+        if self.pending_promotion.is_none() {
+            return;
+        }
+
+        let tile_size = self.get_tile_actuall_size();
+        let side = self.board.get_side_on_move();
+
+        d.draw_rectangle(
+            self.layout.label_size,
+            0,
+            self.layout.board_size,
+            self.layout.board_size,
+            Color::new(0, 0, 0, 160),
+        );
+
+        for (index, kind) in PROMOTION_PIECES.iter().enumerate() {
+            let (x, y) = self.get_promotion_picker_tile_position(index);
+            d.draw_rectangle(
+                self.layout.label_size + x,
+                y,
+                tile_size,
+                tile_size,
+                if index == self.promotion_choice {
+                    Color::GOLD
+                } else {
+                    Color::LIGHTGRAY
+                },
+            );
+            d.draw_rectangle_lines(
+                self.layout.label_size + x,
+                y,
+                tile_size,
+                tile_size,
+                Color::DARKGRAY,
+            );
+            self.render_piece_at_coords(
+                d,
+                (*kind, side),
+                (
+                    (self.layout.label_size + x + tile_size / 2) as f32,
+                    (y + tile_size / 2) as f32,
+                ),
+            );
+        }
+    }
+    /// Finishes the pending promotion with the piece under `(x, y)`. Clicking
+    /// anywhere else cancels the move.
+    pub fn pick_promotion_piece(&mut self, (x, y): (f32, f32)) {
+        let tile_size = self.get_tile_actuall_size() as f32;
+        let picked = PROMOTION_PIECES.iter().enumerate().find(|(index, _)| {
+            let (tile_x, tile_y) = self.get_promotion_picker_tile_position(*index);
+            x >= tile_x as f32
+                && x < tile_x as f32 + tile_size
+                && y >= tile_y as f32
+                && y < tile_y as f32 + tile_size
+        });
+
+        match picked {
+            Some((_, kind)) => self.finish_promotion(*kind),
+            None => self.pending_promotion = None,
+        }
+    }
+    /// Moves the keyboard choice in the promotion picker by `step` pieces, it
+    /// wraps around at the ends.
+    pub fn move_promotion_choice(&mut self, step: i32) {
+        self.promotion_choice = (self.promotion_choice as i32 + step)
+            .rem_euclid(PROMOTION_PIECES.len() as i32) as usize;
+    }
+    /// Finishes the pending promotion with the piece chosen by the keyboard.
+    pub fn pick_promotion_choice(&mut self) {
+        self.finish_promotion(PROMOTION_PIECES[self.promotion_choice]);
+    }
+    /// Plays the pending promotion with `kind`.
+    pub fn finish_promotion(&mut self, kind: PieceType) {
+        if let Some((from, to)) = self.pending_promotion.take() {
+            self.make_move(from, to, Some(kind));
+        }
+    }
+    /// Plays a new move, which throws away the moves that could be redone.
+    /// Returns whether the move was legal.
+    fn make_move(
+        &mut self,
+        from: (usize, usize),
+        to: (usize, usize),
+        promotion: Option<PieceType>,
+    ) -> bool {
+        let is_legal = match &mut self.network {
+            Some(peer) => {
+                let result = peer.play_move(&mut self.board, from, to, promotion);
+                self.handle_network_result(result).unwrap_or(false)
+            }
+            None => self.board.move_piece(from, to, promotion),
+        };
+        if is_legal {
+            self.redo_moves.clear();
+        }
+
+        is_legal
+    }
+    pub fn is_computer_on_move(&self) -> bool {
+        self.get_computer_sides()
+            .contains(&Some(self.board.get_side_on_move()))
+    }
+    /// Sides of the computer and the external engine, when they play.
+    fn get_computer_sides(&self) -> [Option<Side>; 2] {
+        [
+            self.computer.as_ref().map(|computer| computer.side),
+            self.engine.as_ref().map(|engine| engine.side),
+        ]
+    }
+    /// Plays the move of the computer once it is found, or lets it start
+    /// thinking when it is on move. Called every frame.
+    pub fn update_computer(&mut self) {
+        let computer = match &mut self.computer {
+            Some(computer) => computer,
+            None => return,
+        };
+
+        if let Some(mv) = computer.poll_move(&self.board) {
+            if self.board.play_move(mv) {
+                self.redo_moves.clear();
+            }
+        } else if self.board.result.is_none()
+            && self.board.get_side_on_move() == computer.side
+            && !computer.is_thinking()
+        {
+            computer.start_thinking(&self.board);
+        }
+    }
+    /// Plays the reply of the external engine through the same path as a
+    /// dragged piece, or sends it the game when it is on move. Called every
+    /// frame. An engine that stops responding is dropped and the player takes
+    /// over its side.
+    pub fn update_engine(&mut self) {
+        let engine = match &mut self.engine {
+            Some(engine) => engine,
+            None => return,
+        };
+
+        let result = match engine.poll_move(&self.board) {
+            Ok(Some((from, to, promotion))) => {
+                if self.make_move(from, to, promotion) {
+                    Ok(())
+                } else {
+                    Err(UciClientError::IllegalMove(get_uci_move_name(
+                        from, to, promotion,
+                    )))
+                }
+            }
+            Ok(None)
+                if self.board.result.is_none()
+                    && self.board.get_side_on_move() == engine.side
+                    && !engine.is_thinking() =>
+            {
+                engine.start_thinking(&self.board, self.clock.as_ref())
+            }
+            Ok(None) => Ok(()),
+            Err(err) => Err(err),
+        };
+
+        if let Err(err) = result {
+            eprintln!("Stopped playing against the engine: {}", err);
+            self.engine = None;
+        }
+    }
+    pub fn is_remote_player_on_move(&self) -> bool {
+        matches!(&self.network, Some(peer) if peer.side != self.board.get_side_on_move())
+    }
+    /// Drops the connection when it fails, the game goes on as hot-seat.
+    fn handle_network_result<T>(&mut self, result: Result<T, NetworkError>) -> Option<T> {
+        match result {
+            Ok(value) => Some(value),
+            Err(err) => {
+                eprintln!("Lost the connection to the other player: {}", err);
+                self.network = None;
+                None
+            }
+        }
+    }
+    /// Applies everything the other player has sent since the last frame.
+    /// Called every frame.
+    pub fn update_network(&mut self) {
+        while let Some(peer) = &mut self.network {
+            let result = peer.poll(&mut self.board);
+            match self.handle_network_result(result) {
+                Some(Some(_)) => {
+                    // A takeback can leave a dragged piece or a promotion in
+                    // a position that no longer exists.
+                    if self.is_remote_player_on_move() || self.board.result.is_some() {
+                        self.cancel_move_input();
+                    }
+                }
+                _ => break,
+            }
+        }
+        self.drop_taken_back_view();
+    }
+    pub fn resign(&mut self) {
+        if let Some(peer) = &mut self.network {
+            let result = peer.resign(&mut self.board);
+            self.handle_network_result(result);
+        }
+    }
+    pub fn offer_draw(&mut self) {
+        if let Some(peer) = &mut self.network {
+            let result = peer.offer_draw(&self.board);
+            self.handle_network_result(result);
+        }
+    }
+    /// Accepts or declines the draw offer or takeback request of the other
+    /// player.
+    pub fn answer_offer(&mut self, accept: bool) {
+        if let Some(peer) = &mut self.network {
+            let result = peer.answer_offer(&mut self.board, accept);
+            self.handle_network_result(result);
+            self.cancel_move_input();
+            self.drop_taken_back_view();
+        }
+    }
+    /// Presses the clock after every move and ends the game when a side runs
+    /// out of time. Called every frame. Taking moves back does not give any
+    /// time back.
+    pub fn update_clock(&mut self) {
+        let clock = match &mut self.clock {
+            Some(clock) => clock,
+            None => return,
+        };
+
+        let move_count = self.board.move_records.len();
+        let side = self.board.get_side_on_move();
+        if self.board.result.is_some() {
+            clock.stop();
+        } else if move_count == self.clock_move_count + 1
+            && clock.get_running_side() == Some(side.opposite())
+        {
+            clock.press();
+        } else if clock.get_running_side() != Some(side) {
+            clock.start(side);
+        }
+        self.clock_move_count = move_count;
+
+        let side = match clock.get_flagged_side() {
+            Some(side) => side,
+            None => return,
+        };
+        let is_lost = match &mut self.network {
+            Some(peer) => {
+                let result = peer.lose_on_time(&mut self.board, side);
+                // Only sending the flag can fail, the game has ended by then.
+                self.handle_network_result(result).unwrap_or(true)
+            }
+            None => self.board.lose_on_time(side),
+        };
+        if is_lost {
+            if let Some(clock) = &mut self.clock {
+                clock.stop();
+            }
+            self.cancel_move_input();
+            self.stop_opponents();
+        }
+    }
+    /// Stops the computer and the engine from thinking about a position that
+    /// is being left.
+    fn stop_opponents(&mut self) {
+        if let Some(computer) = &mut self.computer {
+            computer.stop_thinking();
+        }
+        if let Some(engine) = &mut self.engine {
+            if let Err(err) = engine.stop_thinking() {
+                eprintln!("Stopped playing against the engine: {}", err);
+                self.engine = None;
+            }
+        }
+    }
+    /// Takes back the last move. Against the computer, moves are taken back
+    /// until the player is on move again. Over the network the other player is
+    /// asked for a takeback instead.
+    pub fn undo(&mut self) {
+        if let Some(peer) = &mut self.network {
+            let result = peer.request_takeback(&self.board);
+            self.handle_network_result(result);
+            return;
+        }
+
+        self.cancel_move_input();
+        self.stop_opponents();
+
+        let computer_sides = self.get_computer_sides();
+        self.board.undo_turn(&mut self.redo_moves, |side| {
+            computer_sides.contains(&Some(side))
+        });
+        self.drop_taken_back_view();
+    }
+    pub fn redo(&mut self) {
+        if self.network.is_some() {
+            return;
+        }
+
+        self.cancel_move_input();
+        self.stop_opponents();
+
+        let computer_sides = self.get_computer_sides();
+        self.board.redo_turn(&mut self.redo_moves, |side| {
+            computer_sides.contains(&Some(side))
+        });
+    }
+}
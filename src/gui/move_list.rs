@@ -0,0 +1,189 @@
+use chessio::chess::{piece::Side, Board};
+use raylib::prelude::*;
+
+use super::layout::Layout;
+
+// Sizes in the default window, see `Layout::scaled`.
+const MOVE_LIST_Y: i32 = 360;
+const ROW_HEIGHT: i32 = 30;
+const VISIBLE_ROWS: usize = 14;
+const FONT_SIZE: i32 = 24;
+/// Left edge and width of the columns of White's and Black's moves.
+const MOVE_COLUMNS: [i32; 2] = [70, 190];
+const MOVE_COLUMN_WIDTH: i32 = 110;
+
+/// One line of the move list, with the index of White's and Black's move in
+/// the move records.
+struct MoveListRow {
+    number: u32,
+    moves: [Option<usize>; 2],
+}
+
+/// Rows of `board`, the first one has no White move when the game started
+/// with Black on move.
+fn get_rows(board: &Board) -> Vec<MoveListRow> {
+    let records = &board.move_records;
+    let black_moves = records
+        .iter()
+        .filter(|record| record.side == Side::Black)
+        .count() as u32;
+    let first_number = board.fullmove_number - black_moves;
+    let offset = match records.first() {
+        Some(record) if record.side == Side::Black => 1,
+        _ => 0,
+    };
+
+    let mut rows: Vec<MoveListRow> = Vec::new();
+    for index in 0..records.len() {
+        let column = (index + offset) % 2;
+        if column == 0 || rows.is_empty() {
+            rows.push(MoveListRow {
+                number: first_number + rows.len() as u32,
+                moves: [None; 2],
+            });
+        }
+        rows.last_mut().unwrap().moves[column] = Some(index);
+    }
+
+    rows
+}
+
+/// Two-column list of the moves of the game in the sidebar.
+#[derive(Debug, Default)]
+pub struct MoveList {
+    /// First row shown.
+    scroll: usize,
+    /// Move that was highlighted in the last frame.
+    highlighted: Option<usize>,
+}
+
+impl MoveList {
+    fn get_max_scroll(row_count: usize) -> usize {
+        row_count.saturating_sub(VISIBLE_ROWS)
+    }
+    /// Scrolls by `rows`, up when negative.
+    pub fn scroll(&mut self, board: &Board, rows: i32) {
+        let max_scroll = Self::get_max_scroll(get_rows(board).len());
+        self.scroll = self
+            .scroll
+            .saturating_add_signed(rows as isize)
+            .min(max_scroll);
+    }
+    /// Draws the moves of `board` with the last of the first `shown_ply` moves
+    /// highlighted. When that move changes, the list scrolls to it.
+    pub fn render(
+        &mut self,
+        d: &mut RaylibDrawHandle,
+        layout: &Layout,
+        board: &Board,
+        shown_ply: usize,
+    ) {
+        let rows = get_rows(board);
+        let highlighted = shown_ply.checked_sub(1);
+        if highlighted != self.highlighted {
+            self.highlighted = highlighted;
+            if let Some(row) = rows
+                .iter()
+                .position(|row| highlighted.is_some() && row.moves.contains(&highlighted))
+            {
+                if row < self.scroll {
+                    self.scroll = row;
+                } else if row >= self.scroll + VISIBLE_ROWS {
+                    self.scroll = row + 1 - VISIBLE_ROWS;
+                }
+            }
+        }
+        self.scroll = self.scroll.min(Self::get_max_scroll(rows.len()));
+
+        let x = layout.sidebar_x;
+        let top = layout.scaled(MOVE_LIST_Y);
+        let row_height = layout.scaled(ROW_HEIGHT);
+        let font_size = layout.scaled(FONT_SIZE);
+        let margin = layout.scaled(5);
+        let column_width = layout.scaled(MOVE_COLUMN_WIDTH);
+        for (line, row) in rows.iter().skip(self.scroll).take(VISIBLE_ROWS).enumerate() {
+            let y = top + line as i32 * row_height;
+            d.draw_text(
+                &format!("{}.", row.number),
+                x,
+                y + layout.scaled(3),
+                font_size,
+                Color::WHITE,
+            );
+
+            for (column, index) in row.moves.iter().enumerate() {
+                let x = x + layout.scaled(MOVE_COLUMNS[column]);
+                let index = match index {
+                    Some(index) => *index,
+                    None => {
+                        d.draw_text(
+                            "...",
+                            x + margin,
+                            y + layout.scaled(3),
+                            font_size,
+                            Color::WHITE,
+                        );
+                        continue;
+                    }
+                };
+
+                if Some(index) == highlighted {
+                    d.draw_rectangle(x, y, column_width, row_height, Color::GOLD);
+                }
+                d.draw_text(
+                    &board.move_records[index].san,
+                    x + margin,
+                    y + layout.scaled(3),
+                    font_size,
+                    if Some(index) == highlighted {
+                        Color::BLACK
+                    } else {
+                        Color::WHITE
+                    },
+                );
+            }
+        }
+
+        let arrows_x = x + layout.scaled(300);
+        if self.scroll > 0 {
+            d.draw_text("^", arrows_x, top, font_size, Color::WHITE);
+        }
+        if self.scroll < Self::get_max_scroll(rows.len()) {
+            d.draw_text(
+                "v",
+                arrows_x,
+                top + (VISIBLE_ROWS as i32 - 1) * row_height,
+                font_size,
+                Color::WHITE,
+            );
+        }
+    }
+    pub fn is_over(&self, layout: &Layout, (x, y): (f32, f32)) -> bool {
+        let left = layout.sidebar_x as f32;
+        let top = layout.scaled(MOVE_LIST_Y) as f32;
+        x >= left
+            && x < left + layout.scaled(MOVE_COLUMNS[1] + MOVE_COLUMN_WIDTH) as f32
+            && y >= top
+            && y < top + layout.scaled(VISIBLE_ROWS as i32 * ROW_HEIGHT) as f32
+    }
+    /// Index in the move records of the move under `(x, y)`.
+    pub fn get_move_on_coords(
+        &self,
+        layout: &Layout,
+        board: &Board,
+        (x, y): (f32, f32),
+    ) -> Option<usize> {
+        if !self.is_over(layout, (x, y)) {
+            return None;
+        }
+
+        let row_height = layout.scaled(ROW_HEIGHT) as f32;
+        let line = ((y - layout.scaled(MOVE_LIST_Y) as f32) / row_height) as usize;
+        let column = MOVE_COLUMNS.iter().rposition(|left| {
+            let left = (layout.sidebar_x + layout.scaled(*left)) as f32;
+            x >= left && x < left + layout.scaled(MOVE_COLUMN_WIDTH) as f32
+        })?;
+
+        get_rows(board).get(self.scroll + line)?.moves[column]
+    }
+}
@@ -1,13 +1,255 @@
-pub mod chess;
+mod gui;
 
-use chess::{constants::*, piece::Side, Game};
+use std::{net::TcpListener, time::Duration};
+
+use chessio::chess::{
+    clock::{format_clock_time, ChessClock, TimeControl, WallTime},
+    constants::*,
+    evaluation::EvalParams,
+    network::{NetworkError, Offer, Peer, DEFAULT_PORT},
+    pgn,
+    piece::{PieceType, Side},
+    search::SearchLimits,
+    uci,
+    uci_client::UciClient,
+    Board,
+};
+use gui::{computer::Computer, constants::*, engine::Engine, Game};
 use raylib::prelude::*;
 
+/// Next character typed since the last frame. raylib-rs has no wrapper for
+/// `GetCharPressed`.
+fn get_char_pressed() -> Option<char> {
+    let c = unsafe { raylib::ffi::GetCharPressed() };
+    char::from_u32(c as u32).filter(|c| *c != '\0')
+}
+
 fn main() {
+    let args: Vec<String> = std::env::args().collect();
+    if args.iter().any(|arg| arg == "--uci") {
+        uci::run();
+        return;
+    }
+
+    let board = match args.iter().position(|arg| arg == "--fen") {
+        Some(index) => {
+            let fen = args.get(index + 1).map(String::as_str).unwrap_or_default();
+            match Board::from_fen(fen) {
+                Ok(board) => board,
+                Err(err) => {
+                    eprintln!("Invalid FEN \"{}\": {}", fen, err);
+                    std::process::exit(1);
+                }
+            }
+        }
+        None => Board::new(),
+    };
+    let board = match args.iter().position(|arg| arg == "--pgn") {
+        Some(index) => {
+            let path = args.get(index + 1).map(String::as_str).unwrap_or_default();
+            let games = std::fs::read_to_string(path)
+                .map_err(|err| err.to_string())
+                .and_then(|text| pgn::parse_pgn(&text).map_err(|err| err.to_string()));
+            match games {
+                Ok(mut games) if !games.is_empty() => games.remove(games.len() - 1).board,
+                Ok(_) => board,
+                Err(err) => {
+                    eprintln!("Could not load PGN \"{}\": {}", path, err);
+                    std::process::exit(1);
+                }
+            }
+        }
+        None => board,
+    };
+
+    if let Some(index) = args.iter().position(|arg| arg == "--perft") {
+        let depth = match args.get(index + 1).map(|depth| depth.parse::<u32>()) {
+            Some(Ok(depth)) => depth,
+            _ => {
+                eprintln!("--perft needs a depth");
+                std::process::exit(1);
+            }
+        };
+
+        let divided = board.get_position().divide(depth);
+        for (mv, nodes) in &divided {
+            println!("{}: {}", mv, nodes);
+        }
+        println!();
+        println!(
+            "Nodes searched: {}",
+            divided.iter().map(|(_, nodes)| nodes).sum::<u64>()
+        );
+        return;
+    }
+
+    let eval_params = match args.iter().position(|arg| arg == "--eval") {
+        Some(index) => {
+            let path = args.get(index + 1).map(String::as_str).unwrap_or_default();
+            match EvalParams::load(path) {
+                Ok(params) => params,
+                Err(err) => {
+                    eprintln!("Could not load evaluation weights \"{}\": {}", path, err);
+                    std::process::exit(1);
+                }
+            }
+        }
+        None => EvalParams::default(),
+    };
+
+    if args.iter().any(|arg| arg == "--evaluate") {
+        println!("{}", eval_params.get_breakdown(&board.get_position()));
+        return;
+    }
+
+    let get_number_arg = |name: &str| {
+        let index = args.iter().position(|arg| arg == name)?;
+        match args.get(index + 1).map(|value| value.parse::<u64>()) {
+            Some(Ok(value)) => Some(value),
+            _ => {
+                eprintln!("{} needs a number", name);
+                std::process::exit(1);
+            }
+        }
+    };
+    let clock = args
+        .iter()
+        .position(|arg| arg == "--time-control")
+        .map(|index| {
+            let text = args.get(index + 1).map(String::as_str).unwrap_or_default();
+            match TimeControl::parse(text) {
+                Ok(control) => ChessClock::new(control, WallTime::default()),
+                Err(err) => {
+                    eprintln!("Invalid time control \"{}\": {}", text, err);
+                    std::process::exit(1);
+                }
+            }
+        });
+
+    let get_side_arg = |name: &str| {
+        let index = args.iter().position(|arg| arg == name)?;
+        match args.get(index + 1).map(String::as_str) {
+            Some("white") => Some(Side::White),
+            Some("black") => Some(Side::Black),
+            _ => {
+                eprintln!("{} needs a side, white or black", name);
+                std::process::exit(1);
+            }
+        }
+    };
+    let limits = SearchLimits {
+        depth: get_number_arg("--depth").map(|depth| depth as u32),
+        time: get_number_arg("--movetime").map(Duration::from_millis),
+    };
+    let limits = if limits == SearchLimits::default() {
+        SearchLimits::time(Duration::from_secs(1))
+    } else {
+        limits
+    };
+
+    let computer = get_side_arg("--computer").map(|side| {
+        let mut computer = Computer::new(side, limits);
+        computer.set_eval_params(eval_params.clone());
+        computer
+    });
+    let engine = args.iter().position(|arg| arg == "--engine").map(|index| {
+        let path = args.get(index + 1).map(String::as_str).unwrap_or_default();
+        let side = get_side_arg("--engine-side").unwrap_or(Side::Black);
+        if matches!(&computer, Some(computer) if computer.side == side) {
+            eprintln!("The computer and the engine cannot play the same side");
+            std::process::exit(1);
+        }
+
+        let client = UciClient::spawn(path, &[]).and_then(|mut client| {
+            let options = args
+                .windows(2)
+                .filter(|pair| pair[0] == "--engine-option")
+                .map(|pair| pair[1].as_str());
+            for option in options {
+                match option.split_once('=') {
+                    Some((name, value)) => client.set_option(name.trim(), Some(value.trim())),
+                    None => client.set_option(option.trim(), None),
+                }?;
+            }
+            client.new_game()?;
+            Ok(client)
+        });
+        match client {
+            Ok(client) => Engine::new(client, side, limits),
+            Err(err) => {
+                eprintln!("Could not use the engine \"{}\": {}", path, err);
+                std::process::exit(1);
+            }
+        }
+    });
+
+    let host_port = args.iter().position(|arg| arg == "--host").map(|index| {
+        match args.get(index + 1).filter(|arg| !arg.starts_with("--")) {
+            Some(port) => port.parse::<u16>().unwrap_or_else(|_| {
+                eprintln!("--host needs a port");
+                std::process::exit(1);
+            }),
+            None => DEFAULT_PORT,
+        }
+    });
+    let join_index = args.iter().position(|arg| arg == "--join");
+    if (host_port.is_some() || join_index.is_some()) && (computer.is_some() || engine.is_some()) {
+        eprintln!("The computer and the engine cannot play over the network");
+        std::process::exit(1);
+    }
+    let (board, network) = if let Some(port) = host_port {
+        let side = get_side_arg("--side").unwrap_or(Side::White);
+        let peer = TcpListener::bind(("0.0.0.0", port))
+            .map_err(NetworkError::from)
+            .and_then(|listener| {
+                println!("Waiting for the other player on port {}...", port);
+                Peer::host(&listener, side, &board)
+            });
+        match peer {
+            Ok(peer) => (board, Some(peer)),
+            Err(err) => {
+                eprintln!("Could not host a game on port {}: {}", port, err);
+                std::process::exit(1);
+            }
+        }
+    } else if let Some(index) = join_index {
+        let address = args.get(index + 1).cloned().unwrap_or_default();
+        let address = if address.contains(':') {
+            address
+        } else {
+            format!("{}:{}", address, DEFAULT_PORT)
+        };
+        match Peer::join(address.as_str()) {
+            Ok((peer, board)) => (board, Some(peer)),
+            Err(err) => {
+                eprintln!("Could not join the game on \"{}\": {}", address, err);
+                std::process::exit(1);
+            }
+        }
+    } else {
+        (board, None)
+    };
+
     let (mut rl, thread) = raylib::init()
         .size(WINDOW_WIDTH, WINDOW_HEIGHT)
+        .resizable()
         .title("Chessio")
         .build();
+    rl.set_window_min_size(MIN_WINDOW_WIDTH, MIN_WINDOW_HEIGHT);
+
+    // The default size does not fit on every screen.
+    let monitor = get_current_monitor();
+    let (monitor_width, monitor_height) = (get_monitor_width(monitor), get_monitor_height(monitor));
+    if monitor_width > 0 && monitor_height > 0 {
+        let scale = (monitor_width as f32 * 0.9 / WINDOW_WIDTH as f32)
+            .min(monitor_height as f32 * 0.9 / WINDOW_HEIGHT as f32);
+        if scale < 1.0 {
+            rl.set_window_size(
+                ((WINDOW_WIDTH as f32 * scale) as i32).max(MIN_WINDOW_WIDTH),
+                ((WINDOW_HEIGHT as f32 * scale) as i32).max(MIN_WINDOW_HEIGHT),
+            );
+        }
+    }
 
     let icon = Image::load_image("./static/other/logo.png").unwrap();
     rl.set_window_icon(icon);
@@ -19,250 +261,456 @@ fn main() {
         .unwrap();
 
     let mut game = Game::new(&mut rl, &thread, None, None);
+    game.board = board;
+    game.computer = computer;
+    game.engine = engine;
+    game.network = network;
+    game.clock = clock;
+    match get_side_arg("--orientation") {
+        Some(side) => game.orientation = side,
+        None => game.orient_to_player(),
+    }
 
     while !rl.window_should_close() {
+        game.resize(rl.get_screen_width(), rl.get_screen_height());
+        let layout = game.layout;
+
         let Vector2 {
             x: mouse_x,
             y: mouse_y,
         } = rl.get_mouse_position();
+        let board_mouse = layout.get_board_coords((mouse_x, mouse_y));
 
         if rl.is_mouse_button_pressed(MouseButton::MOUSE_LEFT_BUTTON) {
-            game.start_drag_event((mouse_x - (LEFT_SIDE_PADDING as f32), mouse_y));
+            if game.pending_promotion.is_some() {
+                game.pick_promotion_piece(board_mouse);
+            } else if !game.click_move_list((mouse_x, mouse_y)) {
+                game.start_drag_event(board_mouse);
+            }
         }
 
         if rl.is_mouse_button_released(MouseButton::MOUSE_LEFT_BUTTON) {
-            game.end_drag_event((mouse_x - (LEFT_SIDE_PADDING as f32), mouse_y));
+            game.end_drag_event(board_mouse);
+        }
+
+        if rl.is_mouse_button_pressed(MouseButton::MOUSE_RIGHT_BUTTON) {
+            game.cancel_move_input();
+        }
+
+        let typed_chars: Vec<char> = std::iter::from_fn(get_char_pressed).collect();
+        let is_typing = game.move_text.is_some();
+        if is_typing {
+            for c in typed_chars {
+                game.type_move_char(c);
+            }
+            if rl.is_key_pressed(KeyboardKey::KEY_BACKSPACE) {
+                game.delete_move_char();
+            }
+            if rl.is_key_pressed(KeyboardKey::KEY_ENTER) {
+                game.submit_move_text();
+            }
+        }
+        if rl.is_key_pressed(KeyboardKey::KEY_TAB) {
+            game.toggle_move_text();
+        }
+
+        let wheel = rl.get_mouse_wheel_move();
+        if wheel != 0.0 && game.move_list.is_over(&layout, (mouse_x, mouse_y)) {
+            game.move_list.scroll(&game.board, -wheel.signum() as i32);
+        }
+
+        let is_ctrl_down = rl.is_key_down(KeyboardKey::KEY_LEFT_CONTROL)
+            || rl.is_key_down(KeyboardKey::KEY_RIGHT_CONTROL);
+        let is_picking_promotion = !is_typing && game.pending_promotion.is_some();
+        if is_picking_promotion {
+            for (key, kind) in [
+                (KeyboardKey::KEY_Q, PieceType::Queen),
+                (KeyboardKey::KEY_R, PieceType::Rook),
+                (KeyboardKey::KEY_B, PieceType::Bishop),
+                (KeyboardKey::KEY_N, PieceType::Knight),
+            ] {
+                if rl.is_key_pressed(key) {
+                    game.finish_promotion(kind);
+                }
+            }
+            if rl.is_key_pressed(KeyboardKey::KEY_LEFT) {
+                game.move_promotion_choice(-1);
+            }
+            if rl.is_key_pressed(KeyboardKey::KEY_RIGHT) {
+                game.move_promotion_choice(1);
+            }
+            if rl.is_key_pressed(KeyboardKey::KEY_ENTER) {
+                game.pick_promotion_choice();
+            }
+            if rl.is_key_pressed(KeyboardKey::KEY_ESCAPE) {
+                game.cancel_move_input();
+            }
+        } else if !is_typing {
+            if rl.is_key_pressed(KeyboardKey::KEY_C) {
+                game.toggle_keyboard_cursor();
+            }
+
+            // The arrows move the keyboard cursor while it is on.
+            if game.keyboard_cursor.is_some() {
+                for (key, step) in [
+                    (KeyboardKey::KEY_LEFT, (-1, 0)),
+                    (KeyboardKey::KEY_RIGHT, (1, 0)),
+                    (KeyboardKey::KEY_UP, (0, -1)),
+                    (KeyboardKey::KEY_DOWN, (0, 1)),
+                ] {
+                    if rl.is_key_pressed(key) {
+                        game.move_keyboard_cursor(step);
+                    }
+                }
+                if rl.is_key_pressed(KeyboardKey::KEY_ENTER) {
+                    game.press_keyboard_cursor();
+                }
+            } else {
+                if rl.is_key_pressed(KeyboardKey::KEY_LEFT) {
+                    game.undo();
+                }
+                if rl.is_key_pressed(KeyboardKey::KEY_RIGHT) {
+                    game.redo();
+                }
+                if rl.is_key_pressed(KeyboardKey::KEY_UP) {
+                    game.show_ply(game.get_shown_ply().saturating_sub(1));
+                }
+                if rl.is_key_pressed(KeyboardKey::KEY_DOWN) {
+                    game.show_ply(game.get_shown_ply() + 1);
+                }
+            }
+            if is_ctrl_down && rl.is_key_pressed(KeyboardKey::KEY_Z) {
+                game.undo();
+            }
+            if is_ctrl_down && rl.is_key_pressed(KeyboardKey::KEY_Y) {
+                game.redo();
+            }
+            if rl.is_key_pressed(KeyboardKey::KEY_END) {
+                game.show_ply(game.board.move_records.len());
+            }
+
+            if rl.is_key_pressed(KeyboardKey::KEY_F) {
+                game.flip_board();
+            }
         }
 
-        for (_, _, tile) in game.tiles_iter_mut() {
-            tile.clear_bg();
+        game.update_computer();
+        game.update_engine();
+        game.update_network();
+        game.update_clock();
+
+        // A claimed draw would not reach the other player, over the network
+        // it is offered instead.
+        if is_typing || is_picking_promotion {
+            // The keys are typed into the move box or pick the promotion piece.
+        } else if game.network.is_some() {
+            if rl.is_key_pressed(KeyboardKey::KEY_R) {
+                game.resign();
+            }
+            if rl.is_key_pressed(KeyboardKey::KEY_O) {
+                game.offer_draw();
+            }
+            if !is_ctrl_down && rl.is_key_pressed(KeyboardKey::KEY_Y) {
+                game.answer_offer(true);
+            }
+            if rl.is_key_pressed(KeyboardKey::KEY_N) {
+                game.answer_offer(false);
+            }
+        } else if rl.is_key_pressed(KeyboardKey::KEY_D) {
+            game.board.claim_draw();
         }
 
+        if !is_typing && rl.is_key_pressed(KeyboardKey::KEY_S) {
+            if let Err(err) = game.save_pgn(SAVED_GAMES_PATH) {
+                eprintln!(
+                    "Could not save the game to \"{}\": {}",
+                    SAVED_GAMES_PATH, err
+                );
+            }
+        }
+
+        // Esc cancels a pending promotion instead of closing the window.
+        rl.set_exit_key(match game.pending_promotion {
+            Some(_) => None,
+            None => Some(KeyboardKey::KEY_ESCAPE),
+        });
+
+        game.clear_tile_bgs();
+
         let mut d = rl.begin_drawing(&thread);
 
         d.draw_texture_ex(
             &background_img_texture,
             Vector2 { x: 0.0, y: 0.0 },
             0.0,
-            1.0,
+            // Covers the whole window, whatever its size.
+            (layout.window_width as f32 / background_img_texture.width as f32)
+                .max(layout.window_height as f32 / background_img_texture.height as f32),
             Color::WHITE,
         );
 
-        let (side_on_turn, side_on_turn_color) = match game.move_records.last() {
-            Some(record) => match record.side {
-                Side::Black => ("White", Color::WHITE),
-                Side::White => ("Black", Color::BLACK),
-            },
-            None => ("White", Color::WHITE),
+        let (side_on_turn, side_on_turn_color) = match game.board.get_side_on_move() {
+            Side::White => ("White", Color::WHITE),
+            Side::Black => ("Black", Color::BLACK),
         };
 
         d.draw_text(
             "Turn:",
-            CHESSBOARD_WIDTH + LEFT_SIDE_PADDING + 20,
-            10,
-            46,
+            layout.sidebar_x,
+            layout.scaled(10),
+            layout.scaled(46),
             Color::WHITE,
         );
         d.draw_text(
-            &(game.move_records.len() + 1).to_string(),
-            CHESSBOARD_WIDTH + 220,
-            10,
-            46,
+            &(game.board.move_records.len() + 1).to_string(),
+            layout.sidebar_x + layout.scaled(150),
+            layout.scaled(10),
+            layout.scaled(46),
             Color::WHITE,
         );
         d.draw_text(
             "Move:",
-            CHESSBOARD_WIDTH + LEFT_SIDE_PADDING + 20,
-            60,
-            46,
+            layout.sidebar_x,
+            layout.scaled(60),
+            layout.scaled(46),
             Color::WHITE,
         );
         d.draw_text(
             side_on_turn,
-            CHESSBOARD_WIDTH + 220,
-            60,
-            46,
+            layout.sidebar_x + layout.scaled(150),
+            layout.scaled(60),
+            layout.scaled(46),
             side_on_turn_color,
         );
 
-        match game.is_check {
-            Some(side) => {
+        if let Some(clock) = &game.clock {
+            for (index, (side, color)) in [(Side::White, Color::WHITE), (Side::Black, Color::BLACK)]
+                .into_iter()
+                .enumerate()
+            {
+                let remaining = clock.get_remaining(side);
+                let x = layout.sidebar_x + index as i32 * layout.scaled(160);
                 d.draw_text(
-                    "CHECK",
-                    CHESSBOARD_WIDTH + LEFT_SIDE_PADDING + 20,
-                    WINDOW_HEIGHT - 50,
-                    46,
-                    if side == Side::Black {
-                        Color::BLACK
+                    &format_clock_time(remaining),
+                    x,
+                    layout.scaled(175),
+                    layout.scaled(28),
+                    if remaining.is_zero() {
+                        Color::RED
                     } else {
-                        Color::WHITE
+                        color
                     },
                 );
+                if clock.get_running_side() == Some(side) {
+                    d.draw_rectangle(
+                        x,
+                        layout.scaled(205),
+                        layout.scaled(120),
+                        layout.scaled(4),
+                        Color::GOLD,
+                    );
+                }
             }
-            None => {}
         }
 
-        match game.move_records.last() {
-            Some(lm) => {
-                let last_move_piece = format!("{:?} {:?}", lm.side, lm.kind);
-                d.draw_text(
-                    "Last move:",
-                    CHESSBOARD_WIDTH + LEFT_SIDE_PADDING + 20,
-                    110,
-                    28,
-                    Color::WHITE,
-                );
+        if let Some(peer) = &game.network {
+            let offer_text = match (peer.get_received_offer(), peer.get_sent_offer()) {
+                (Some(Offer::Draw), _) => Some("Draw offered, Y/N?"),
+                (Some(Offer::Takeback), _) => Some("Takeback asked, Y/N?"),
+                (_, Some(Offer::Draw)) => Some("Draw offer sent"),
+                (_, Some(Offer::Takeback)) => Some("Takeback asked"),
+                (None, None) => None,
+            };
+            if let Some(text) = offer_text {
                 d.draw_text(
-                    &last_move_piece,
-                    CHESSBOARD_WIDTH + LEFT_SIDE_PADDING + 20,
-                    140,
-                    28,
+                    text,
+                    layout.sidebar_x,
+                    layout.scaled(290),
+                    layout.scaled(28),
                     Color::WHITE,
                 );
-                let last_move_text = format!(
-                    "{}{} -> {}{}",
-                    X_AXIS_LABELS[lm.from.0],
-                    Y_AXIS_LABELS[lm.from.1],
-                    X_AXIS_LABELS[lm.to.0],
-                    Y_AXIS_LABELS[lm.to.1]
-                );
+            }
+            if game.board.result.is_none() {
                 d.draw_text(
-                    &last_move_text,
-                    CHESSBOARD_WIDTH + LEFT_SIDE_PADDING + 20,
-                    170,
-                    28,
+                    "R resign, O offer draw",
+                    layout.sidebar_x,
+                    layout.scaled(320),
+                    layout.scaled(28),
                     Color::WHITE,
                 );
             }
-            None => {}
+        } else if let Some(draw) = game.board.get_claimable_draw() {
+            d.draw_text(
+                &draw.to_string(),
+                layout.sidebar_x,
+                layout.scaled(220),
+                layout.scaled(28),
+                Color::WHITE,
+            );
+            d.draw_text(
+                "Press D to claim a draw",
+                layout.sidebar_x,
+                layout.scaled(250),
+                layout.scaled(28),
+                Color::WHITE,
+            );
+        }
+
+        if matches!(&game.computer, Some(computer) if computer.is_thinking()) {
+            d.draw_text(
+                "Computer is thinking...",
+                layout.sidebar_x,
+                layout.scaled(WINDOW_HEIGHT - 100),
+                layout.scaled(28),
+                Color::WHITE,
+            );
+        }
+        if let Some(engine) = game.engine.as_ref().filter(|engine| engine.is_thinking()) {
+            d.draw_text(
+                &format!("{} is thinking...", engine.get_name()),
+                layout.sidebar_x,
+                layout.scaled(WINDOW_HEIGHT - 100),
+                layout.scaled(28),
+                Color::WHITE,
+            );
+        }
+
+        if let Some(side) = game.board.is_check {
+            d.draw_text(
+                "CHECK",
+                layout.sidebar_x,
+                layout.scaled(WINDOW_HEIGHT - 50),
+                layout.scaled(46),
+                if side == Side::Black {
+                    Color::BLACK
+                } else {
+                    Color::WHITE
+                },
+            );
+        }
+
+        if game.is_viewing_past() {
+            d.draw_text(
+                "Viewing an earlier move",
+                layout.sidebar_x,
+                layout.scaled(110),
+                layout.scaled(28),
+                Color::WHITE,
+            );
+            d.draw_text(
+                "Press End to return",
+                layout.sidebar_x,
+                layout.scaled(140),
+                layout.scaled(28),
+                Color::WHITE,
+            );
         }
+        game.render_move_list(&mut d);
+        game.render_captured_pieces(&mut d);
+        game.render_move_text(&mut d);
 
-        for (x, y, _) in game.tiles_iter() {
+        let label_font_size = layout.scaled(28);
+        for y in 0..CHESSBOARD_SIZE {
             d.draw_rectangle(
                 0,
-                (y as i32) * (CHESSBOARD_HEIGHT / 8),
-                LEFT_SIDE_PADDING,
-                CHESSBOARD_HEIGHT / 8,
+                (y as i32) * layout.tile_size,
+                layout.label_size,
+                layout.tile_size,
                 Color::GRAY,
             );
+            let (_, rank) = game.get_screen_tile((0, y));
+            let label = Y_AXIS_LABELS[7 - rank];
             d.draw_text(
-                Y_AXIS_LABELS[7 - y],
-                LEFT_SIDE_PADDING / 2 - 9,
-                (y as i32) * (CHESSBOARD_HEIGHT / 8) + (CHESSBOARD_HEIGHT / 8 / 2) - 14,
-                28,
+                label,
+                (layout.label_size - measure_text(label, label_font_size)) / 2,
+                (y as i32) * layout.tile_size + (layout.tile_size - label_font_size) / 2,
+                label_font_size,
                 Color::WHITE,
             );
         }
 
-        for (x, y, _) in game.tiles_iter() {
+        for x in 0..CHESSBOARD_SIZE {
+            let (file, _) = game.get_screen_tile((x, 0));
             d.draw_rectangle(
-                LEFT_SIDE_PADDING + (x as i32) * (CHESSBOARD_WIDTH / 8),
-                CHESSBOARD_HEIGHT,
-                CHESSBOARD_WIDTH / 8,
-                WINDOW_HEIGHT - CHESSBOARD_HEIGHT,
+                layout.label_size + (x as i32) * layout.tile_size,
+                layout.board_size,
+                layout.tile_size,
+                layout.label_size,
                 Color::GRAY,
             );
+            let label = X_AXIS_LABELS[file];
             d.draw_text(
-                X_AXIS_LABELS[x],
-                LEFT_SIDE_PADDING
-                    + (x as i32) * (CHESSBOARD_WIDTH / 8)
-                    + (CHESSBOARD_WIDTH / 8) / 2
-                    - 9,
-                WINDOW_HEIGHT - (WINDOW_HEIGHT - CHESSBOARD_HEIGHT) / 2 - 14,
-                28,
+                label,
+                layout.label_size
+                    + (x as i32) * layout.tile_size
+                    + (layout.tile_size - measure_text(label, label_font_size)) / 2,
+                layout.board_size + (layout.label_size - label_font_size) / 2,
+                label_font_size,
                 Color::WHITE,
             );
         }
 
         if game.hovered_piece_coords.is_none() {
-            let hovered_tile =
-                game.get_tile_on_coords_mut((mouse_x - (LEFT_SIDE_PADDING as f32), mouse_y));
+            let hovered_tile = game.get_tile_on_coords(board_mouse);
 
             match hovered_tile {
-                Some(t) => {
-                    if t.0.piece.is_some() {
-                        d.set_mouse_cursor(MouseCursor::MOUSE_CURSOR_POINTING_HAND);
-                    } else {
-                        d.set_mouse_cursor(MouseCursor::MOUSE_CURSOR_DEFAULT);
-                    }
+                Some(t) if t.0.piece.is_some() => {
+                    d.set_mouse_cursor(MouseCursor::MOUSE_CURSOR_POINTING_HAND);
                 }
-                None => {
+                _ => {
                     d.set_mouse_cursor(MouseCursor::MOUSE_CURSOR_DEFAULT);
                 }
             }
         }
 
-        let hovered_tile_coords: Option<(usize, usize)> = if let Some((_, (x, y))) =
-            game.get_tile_on_coords((mouse_x - (LEFT_SIDE_PADDING as f32), mouse_y))
-        {
-            Some((x, y))
-        } else {
-            None
-        };
+        let hovered_tile_coords: Option<(usize, usize)> = game
+            .get_tile_on_coords(board_mouse)
+            .map(|(_, coords)| coords);
 
-        match hovered_tile_coords {
-            Some(coords) => {
-                let text = format!("{}{}", X_AXIS_LABELS[coords.0], Y_AXIS_LABELS[7 - coords.1]);
-                d.draw_text(
-                    &text,
-                    WINDOW_WIDTH - 100,
-                    WINDOW_HEIGHT - 88,
-                    68,
-                    Color::WHITE,
-                );
-            }
-            _ => {}
+        if let Some(coords) = hovered_tile_coords {
+            let text = format!("{}{}", X_AXIS_LABELS[coords.0], Y_AXIS_LABELS[7 - coords.1]);
+            d.draw_text(
+                &text,
+                layout.window_width - layout.scaled(100),
+                layout.scaled(WINDOW_HEIGHT - 88),
+                layout.scaled(68),
+                Color::WHITE,
+            );
         }
 
-        game.highlight_tile_by_coords((mouse_x - (LEFT_SIDE_PADDING as f32), mouse_y));
+        game.highlight_tile_by_coords(board_mouse);
+        game.highlight_keyboard_cursor();
         game.render(&mut d);
 
-        for y in 0..CHESSBOARD_SIZE {
-            for x in 0..CHESSBOARD_SIZE {
-                if game.hovered_piece_coords == Some((x, y)) {
-                    continue;
-                }
-
-                game.tiles[y][x].render(
-                    &mut d,
-                    (
-                        LEFT_SIDE_PADDING + x as i32 * (CHESSBOARD_WIDTH / CHESSBOARD_SIZE as i32),
-                        y as i32 * (CHESSBOARD_HEIGHT / CHESSBOARD_SIZE as i32),
-                    ),
-                    &game.pieces_images,
-                );
-            }
-        }
+        game.render_pieces(&mut d);
 
         game.render_available_moves(&mut d);
 
-        match game.hovered_piece_coords {
-            Some(coords) => {
-                let tile_at_mouse_coords = game.tiles[coords.1][coords.0];
-                // println!("{:#?}", tile_at_mouse_coords);
-                match tile_at_mouse_coords.piece {
-                    Some(piece) => game.render_piece_at_coords(
-                        &mut d,
-                        (piece.kind, piece.side),
-                        (mouse_x, mouse_y),
-                    ),
-                    None => {}
-                }
+        if let Some(coords) = game.hovered_piece_coords {
+            let tile_at_mouse_coords = game.board.tiles[coords.1][coords.0];
+            // println!("{:#?}", tile_at_mouse_coords);
+            if let Some(piece) = tile_at_mouse_coords.piece {
+                game.render_piece_at_coords(&mut d, (piece.kind, piece.side), (mouse_x, mouse_y));
             }
-            _ => {}
         }
 
-        match game.victor {
-            Some(v) => {
-                d.draw_text(
-                    "CHECKMATE",
-                    LEFT_SIDE_PADDING + (CHESSBOARD_WIDTH / 2) - 50,
-                    (CHESSBOARD_HEIGHT / 2) - 10,
-                    48,
-                    Color::RED,
-                );
+        if let Some(result) = game.board.result {
+            let result_text = match result.winner() {
+                Some(Side::White) => format!("{} - White wins", result),
+                Some(Side::Black) => format!("{} - Black wins", result),
+                None => format!("{} - Draw", result),
             }
-            None => {}
+            .to_uppercase();
+            d.draw_text(
+                &result_text,
+                layout.label_size
+                    + (layout.board_size - measure_text(&result_text, layout.scaled(48))) / 2,
+                layout.board_size / 2 - layout.scaled(10),
+                layout.scaled(48),
+                Color::RED,
+            );
         }
+
+        game.render_promotion_picker(&mut d);
     }
 }
@@ -0,0 +1,88 @@
+use chessio::chess::{
+    bitboard::*,
+    piece::{PieceType, Side},
+    tile::get_tile_coords,
+    Board,
+};
+
+fn square(name: &str) -> Square {
+    get_square(get_tile_coords(name).unwrap())
+}
+
+fn squares(names: &[&str]) -> Bitboard {
+    names
+        .iter()
+        .fold(0, |bitboard, name| bitboard | square_bitboard(square(name)))
+}
+
+#[test]
+fn squares_match_tile_names() {
+    assert_eq!(square("a1"), 0);
+    assert_eq!(square("h1"), 7);
+    assert_eq!(square("e4"), 28);
+    assert_eq!(square("h8"), 63);
+
+    for index in 0..64 {
+        assert_eq!(get_square(get_square_coords(index)), index);
+    }
+}
+
+#[test]
+fn leaper_attacks() {
+    assert_eq!(get_knight_attacks(square("a1")), squares(&["b3", "c2"]));
+    assert_eq!(get_knight_attacks(square("e4")).count_ones(), 8);
+    assert_eq!(get_king_attacks(square("h8")), squares(&["g8", "g7", "h7"]));
+    assert_eq!(
+        get_pawn_attacks(Side::White, square("e4")),
+        squares(&["d5", "f5"])
+    );
+    assert_eq!(
+        get_pawn_attacks(Side::Black, square("a5")),
+        squares(&["b4"])
+    );
+}
+
+#[test]
+fn slider_attacks_stop_at_blockers() {
+    assert_eq!(get_rook_attacks(square("a1"), 0).count_ones(), 14);
+    assert_eq!(get_bishop_attacks(square("d4"), 0).count_ones(), 13);
+
+    let occupancy = squares(&["d6", "b4", "f2", "g7"]);
+    assert_eq!(
+        get_rook_attacks(square("d4"), occupancy),
+        squares(&["d5", "d6", "d3", "d2", "d1", "c4", "b4", "e4", "f4", "g4", "h4"])
+    );
+    assert_eq!(
+        get_bishop_attacks(square("d4"), occupancy),
+        squares(&["e5", "f6", "g7", "c5", "b6", "a7", "c3", "b2", "a1", "e3", "f2"])
+    );
+}
+
+#[test]
+fn between_and_line() {
+    assert_eq!(
+        get_between(square("a1"), square("d4")),
+        squares(&["b2", "c3"])
+    );
+    assert_eq!(get_between(square("e1"), square("e2")), 0);
+    assert_eq!(get_between(square("a1"), square("b3")), 0);
+    assert_eq!(get_line(square("c1"), square("c5")).count_ones(), 8);
+    assert_eq!(get_line(square("a1"), square("b3")), 0);
+}
+
+#[test]
+fn position_matches_board() {
+    let board = Board::new();
+    let position = board.get_position();
+
+    assert_eq!(
+        position.get_occupancy(),
+        RANK_1 | RANK_1 << 8 | RANK_8 | RANK_8 >> 8
+    );
+    assert_eq!(
+        position.get_piece_at(square("d8")),
+        Some((PieceType::Queen, Side::Black))
+    );
+    assert_eq!(position.get_king_square(Side::White), Some(square("e1")));
+    assert_eq!(position.legal_moves().len(), 20);
+}
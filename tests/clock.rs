@@ -0,0 +1,282 @@
+use std::{cell::Cell, rc::Rc, time::Duration};
+
+use chessio::chess::{
+    clock::{
+        format_clock_time, ChessClock, Increment, TimeControl, TimeControlError, TimeSource,
+        TimeStage,
+    },
+    pgn::get_result_token,
+    piece::Side,
+    result::GameResult,
+    Board,
+};
+
+/// Time that only moves when the test says so.
+#[derive(Clone, Default)]
+struct ManualTime(Rc<Cell<Duration>>);
+
+impl ManualTime {
+    fn advance(&self, seconds: f64) {
+        self.0.set(self.0.get() + Duration::from_secs_f64(seconds));
+    }
+}
+
+impl TimeSource for ManualTime {
+    fn now(&self) -> Duration {
+        self.0.get()
+    }
+}
+
+fn new_clock(control: &str) -> (ChessClock<ManualTime>, ManualTime) {
+    let time = ManualTime::default();
+    let clock = ChessClock::new(TimeControl::parse(control).unwrap(), time.clone());
+    (clock, time)
+}
+
+fn secs(seconds: f64) -> Duration {
+    Duration::from_secs_f64(seconds)
+}
+
+#[test]
+fn parse_time_controls() {
+    assert_eq!(
+        TimeControl::parse("40/90+30:30+30").unwrap().stages,
+        [
+            TimeStage {
+                moves: Some(40),
+                time: secs(90.0 * 60.0),
+                increment: Increment::Fischer(secs(30.0)),
+            },
+            TimeStage {
+                moves: None,
+                time: secs(30.0 * 60.0),
+                increment: Increment::Fischer(secs(30.0)),
+            },
+        ]
+    );
+    assert_eq!(
+        TimeControl::parse("15d5").unwrap().stages[0].increment,
+        Increment::Delay(secs(5.0))
+    );
+    assert_eq!(
+        TimeControl::parse("0.5b2").unwrap().stages[0],
+        TimeStage {
+            moves: None,
+            time: secs(30.0),
+            increment: Increment::Bronstein(secs(2.0)),
+        }
+    );
+
+    for text in [
+        "5",
+        "3+2",
+        "40/90+30:30+30",
+        "40/120:20/60",
+        "15d5",
+        "0.5b2.5",
+    ] {
+        assert_eq!(TimeControl::parse(text).unwrap().to_string(), text);
+    }
+}
+
+#[test]
+fn invalid_time_controls() {
+    assert_eq!(TimeControl::parse(" "), Err(TimeControlError::Empty));
+    for text in ["x", "5+", "+3", "0/5", "40/", "-5", "5+-1", "40/90:"] {
+        assert!(
+            matches!(
+                TimeControl::parse(text),
+                Err(TimeControlError::InvalidStage(_))
+            ),
+            "{}",
+            text
+        );
+    }
+    assert_eq!(
+        TimeControl::parse("90:30"),
+        Err(TimeControlError::StageAfterSuddenDeath("30".to_string()))
+    );
+}
+
+#[test]
+fn only_the_side_on_move_loses_time() {
+    let (mut clock, time) = new_clock("5");
+    time.advance(3.0);
+    assert_eq!(clock.get_remaining(Side::White), secs(300.0));
+
+    clock.start(Side::White);
+    time.advance(10.0);
+    assert_eq!(clock.get_remaining(Side::White), secs(290.0));
+    assert_eq!(clock.get_remaining(Side::Black), secs(300.0));
+
+    assert!(clock.press());
+    assert_eq!(clock.get_running_side(), Some(Side::Black));
+    time.advance(4.5);
+    assert_eq!(clock.get_remaining(Side::White), secs(290.0));
+    assert_eq!(clock.get_remaining(Side::Black), secs(295.5));
+
+    clock.stop();
+    time.advance(60.0);
+    assert_eq!(clock.get_running_side(), None);
+    assert_eq!(clock.get_remaining(Side::Black), secs(295.5));
+}
+
+#[test]
+fn fischer_increment() {
+    let (mut clock, time) = new_clock("3+2");
+    clock.start(Side::White);
+    time.advance(5.0);
+    clock.press();
+    assert_eq!(clock.get_remaining(Side::White), secs(177.0));
+
+    // Moving quickly gains time.
+    time.advance(1.0);
+    clock.press();
+    assert_eq!(clock.get_remaining(Side::Black), secs(181.0));
+}
+
+#[test]
+fn simple_delay() {
+    let (mut clock, time) = new_clock("1d5");
+    clock.start(Side::White);
+    time.advance(3.0);
+    assert_eq!(clock.get_remaining(Side::White), secs(60.0));
+    clock.press();
+    assert_eq!(clock.get_remaining(Side::White), secs(60.0));
+
+    time.advance(8.0);
+    assert_eq!(clock.get_remaining(Side::Black), secs(57.0));
+    clock.press();
+    assert_eq!(clock.get_remaining(Side::Black), secs(57.0));
+}
+
+#[test]
+fn bronstein_delay() {
+    let (mut clock, time) = new_clock("1b5");
+    clock.start(Side::White);
+    time.advance(3.0);
+    // The time runs and is given back after the move.
+    assert_eq!(clock.get_remaining(Side::White), secs(57.0));
+    clock.press();
+    assert_eq!(clock.get_remaining(Side::White), secs(60.0));
+
+    time.advance(8.0);
+    clock.press();
+    assert_eq!(clock.get_remaining(Side::Black), secs(57.0));
+}
+
+#[test]
+fn multi_stage_controls() {
+    let (mut clock, time) = new_clock("2/10+1:5");
+    clock.start(Side::White);
+    for _ in 0..2 {
+        time.advance(30.0);
+        clock.press();
+        time.advance(10.0);
+        clock.press();
+    }
+    // Two moves of 30 seconds with the increment, then the next stage.
+    assert_eq!(clock.get_remaining(Side::White), secs(600.0 - 58.0 + 300.0));
+    assert_eq!(clock.get_remaining(Side::Black), secs(600.0 - 18.0 + 300.0));
+
+    // The last stage has no increment and never adds more time.
+    for _ in 0..3 {
+        time.advance(30.0);
+        clock.press();
+        time.advance(10.0);
+        clock.press();
+    }
+    assert_eq!(clock.get_remaining(Side::White), secs(842.0 - 90.0));
+}
+
+#[test]
+fn last_stage_with_moves_repeats() {
+    let (mut clock, time) = new_clock("2/1");
+    clock.start(Side::White);
+    for _ in 0..4 {
+        time.advance(10.0);
+        clock.press();
+        clock.press();
+    }
+    assert_eq!(clock.get_remaining(Side::White), secs(60.0 - 40.0 + 120.0));
+    assert_eq!(clock.get_remaining(Side::Black), secs(180.0));
+}
+
+#[test]
+fn running_out_of_time() {
+    let (mut clock, time) = new_clock("0.5+10");
+    clock.start(Side::White);
+    time.advance(29.9);
+    assert_eq!(clock.get_flagged_side(), None);
+
+    time.advance(0.2);
+    assert_eq!(clock.get_remaining(Side::White), Duration::ZERO);
+    assert_eq!(clock.get_flagged_side(), Some(Side::White));
+
+    // A move after the flag fell gets no increment.
+    assert!(!clock.press());
+    assert_eq!(clock.get_remaining(Side::White), Duration::ZERO);
+    assert_eq!(clock.get_flagged_side(), Some(Side::White));
+}
+
+#[test]
+fn losing_on_time() {
+    let mut board = Board::new();
+    assert!(board.lose_on_time(Side::White));
+    assert_eq!(
+        board.result,
+        Some(GameResult::Timeout {
+            winner: Side::Black
+        })
+    );
+    assert_eq!(get_result_token(board.result), "0-1");
+    // The game is already over.
+    assert!(!board.lose_on_time(Side::Black));
+
+    // A lone king cannot checkmate.
+    for fen in [
+        "4k3/8/8/8/8/8/PPPPPPPP/4K3 w - - 0 1",
+        "4k3/8/8/8/8/8/8/R3K3 w - - 0 1",
+    ] {
+        let mut board = Board::from_fen(fen).unwrap();
+        assert!(board.lose_on_time(Side::White));
+        assert_eq!(
+            board.result,
+            Some(GameResult::TimeoutVsInsufficientMaterial),
+            "{}",
+            fen
+        );
+        assert_eq!(get_result_token(board.result), "1/2-1/2");
+    }
+
+    // Black can checkmate with a pawn or two minor pieces, and with a single
+    // one as long as White has a piece to block its own king with.
+    for fen in [
+        "4k3/7p/8/8/8/8/8/4K3 w - - 0 1",
+        "4k3/8/3nn3/8/8/8/8/4K3 w - - 0 1",
+        "4k3/8/3bn3/8/8/8/8/4K3 w - - 0 1",
+        "4k3/8/5n2/8/8/8/PPPPPPPP/4K3 w - - 0 1",
+        "4k3/2b5/8/8/8/8/8/1B2K3 w - - 0 1",
+        "4k3/2b5/8/8/8/8/8/1N2K3 w - - 0 1",
+    ] {
+        let mut board = Board::from_fen(fen).unwrap();
+        assert!(board.lose_on_time(Side::White));
+        assert_eq!(
+            board.result,
+            Some(GameResult::Timeout {
+                winner: Side::Black
+            }),
+            "{}",
+            fen
+        );
+    }
+}
+
+#[test]
+fn clock_time_format() {
+    assert_eq!(format_clock_time(secs(5400.0)), "1:30:00");
+    assert_eq!(format_clock_time(secs(3599.0)), "59:59");
+    assert_eq!(format_clock_time(secs(10.0)), "0:10");
+    assert_eq!(format_clock_time(secs(9.87)), "9.8");
+    assert_eq!(format_clock_time(Duration::ZERO), "0.0");
+}
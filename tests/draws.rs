@@ -0,0 +1,166 @@
+use chessio::chess::{piece::Side, result::GameResult, Board};
+
+fn play(board: &mut Board, moves: &[&str]) {
+    for san in moves {
+        board.move_piece_san(san).unwrap();
+    }
+}
+
+const KNIGHT_SHUFFLE: [&str; 4] = ["Nf3", "Nf6", "Ng1", "Ng8"];
+
+#[test]
+fn threefold_and_fivefold_repetition() {
+    let mut board = Board::new();
+    play(&mut board, &KNIGHT_SHUFFLE);
+    assert_eq!(board.get_repetition_count(), 2);
+    assert_eq!(board.get_claimable_draw(), None);
+
+    play(&mut board, &KNIGHT_SHUFFLE);
+    assert_eq!(board.get_repetition_count(), 3);
+    assert_eq!(
+        board.get_claimable_draw(),
+        Some(GameResult::ThreefoldRepetition)
+    );
+    assert_eq!(board.result, None);
+
+    // The draw has to be claimed until the fifth repetition.
+    let mut claimed = board.clone();
+    assert!(claimed.claim_draw());
+    assert_eq!(claimed.result, Some(GameResult::ThreefoldRepetition));
+    assert!(!claimed.claim_draw());
+
+    play(&mut board, &KNIGHT_SHUFFLE);
+    assert_eq!(board.result, None);
+    play(&mut board, &KNIGHT_SHUFFLE);
+    assert_eq!(board.get_repetition_count(), 5);
+    assert_eq!(board.result, Some(GameResult::FivefoldRepetition));
+    assert_eq!(board.get_claimable_draw(), None);
+}
+
+#[test]
+fn repetition_needs_the_same_castling_rights() {
+    // The kings walk back, but have lost the right to castle.
+    let mut board = Board::from_fen("r3k2r/8/8/8/8/8/8/R3K2R w KQkq - 0 1").unwrap();
+    for _ in 0..2 {
+        play(&mut board, &["Ke2", "Ke7", "Ke1", "Ke8"]);
+    }
+    assert_eq!(board.get_repetition_count(), 2);
+
+    play(&mut board, &["Ke2", "Ke7", "Ke1", "Ke8"]);
+    assert_eq!(board.get_repetition_count(), 3);
+}
+
+#[test]
+fn fifty_and_seventy_five_move_rules() {
+    let mut board = Board::from_fen("4k3/8/8/8/8/8/8/R3K3 w - - 99 80").unwrap();
+    assert_eq!(board.get_claimable_draw(), None);
+    play(&mut board, &["Ra2"]);
+    assert_eq!(board.halfmove_clock, 100);
+    assert_eq!(board.get_claimable_draw(), Some(GameResult::FiftyMoveRule));
+    assert_eq!(board.result, None);
+
+    // A capture or pawn move resets the clock.
+    let mut board = Board::from_fen("4k3/4p3/8/8/8/8/8/R3K3 b - - 99 80").unwrap();
+    play(&mut board, &["e5"]);
+    assert_eq!(board.halfmove_clock, 0);
+    assert_eq!(board.get_claimable_draw(), None);
+
+    let mut board = Board::from_fen("4k3/8/8/8/8/8/8/R3K3 w - - 149 80").unwrap();
+    play(&mut board, &["Ra2"]);
+    assert_eq!(board.result, Some(GameResult::SeventyFiveMoveRule));
+    assert_eq!(board.get_claimable_draw(), None);
+}
+
+#[test]
+fn insufficient_material() {
+    for fen in [
+        "4k3/8/8/8/8/8/8/4K3 w - - 0 1",
+        "4k3/8/8/8/8/8/8/2B1K3 w - - 0 1",
+        "4k3/8/8/8/8/8/8/1N2K3 b - - 0 1",
+        "2b1k3/8/8/8/8/8/8/4KB2 w - - 0 1",
+    ] {
+        let board = Board::from_fen(fen).unwrap();
+        assert!(board.is_insufficient_material(), "{}", fen);
+        assert_eq!(
+            board.result,
+            Some(GameResult::InsufficientMaterial),
+            "{}",
+            fen
+        );
+    }
+
+    for fen in [
+        "4k3/8/8/8/8/8/8/R3K3 w - - 0 1",
+        "4k3/8/8/8/8/8/4P3/4K3 w - - 0 1",
+        "4k3/8/8/8/8/8/8/1NB1K3 w - - 0 1",
+        // Bishops on tiles of different colors.
+        "3bk3/8/8/8/8/8/8/4KB2 w - - 0 1",
+        "4k3/8/8/8/8/8/8/1NN1K3 w - - 0 1",
+    ] {
+        let board = Board::from_fen(fen).unwrap();
+        assert!(!board.is_insufficient_material(), "{}", fen);
+        assert_eq!(board.result, None, "{}", fen);
+    }
+
+    // Capturing the last pawn ends the game.
+    let mut board = Board::from_fen("4k3/8/8/8/8/8/3p4/4K3 w - - 0 1").unwrap();
+    play(&mut board, &["Kxd2"]);
+    assert_eq!(board.result, Some(GameResult::InsufficientMaterial));
+}
+
+#[test]
+fn mating_material() {
+    let board = Board::from_fen("4k3/8/8/8/8/8/8/1N2K3 w - - 0 1").unwrap();
+    assert!(!board.has_mating_material(Side::White));
+    assert!(!board.has_mating_material(Side::Black));
+
+    let board = Board::from_fen("4k3/8/8/8/8/8/8/R3K3 w - - 0 1").unwrap();
+    assert!(board.has_mating_material(Side::White));
+    assert!(!board.has_mating_material(Side::Black));
+
+    let board = Board::from_fen("4k3/8/8/8/8/8/8/1NB1K3 w - - 0 1").unwrap();
+    assert!(board.has_mating_material(Side::White));
+
+    // A knight can mate a king hemmed in by its own pawn.
+    let board = Board::from_fen("4k3/4p3/8/8/8/8/8/1N2K3 w - - 0 1").unwrap();
+    assert!(board.has_mating_material(Side::White));
+    assert!(board.has_mating_material(Side::Black));
+
+    let board = Board::from_fen("2b1k3/8/8/8/8/8/8/3BK3 w - - 0 1").unwrap();
+    assert!(!board.has_mating_material(Side::White));
+    assert!(!board.has_mating_material(Side::Black));
+    let board = Board::from_fen("3bk3/8/8/8/8/8/8/3BK3 w - - 0 1").unwrap();
+    assert!(board.has_mating_material(Side::White));
+}
+
+#[test]
+fn losing_on_time_against_a_lone_minor_piece() {
+    // K+N against K is a draw before anyone runs out of time, against K+P
+    // the side with the pawn loses.
+    let mut board = Board::from_fen("4k3/8/8/8/8/8/8/1N2K3 w - - 0 1").unwrap();
+    assert_eq!(board.result, Some(GameResult::InsufficientMaterial));
+    assert!(!board.lose_on_time(Side::Black));
+
+    let mut board = Board::from_fen("4k3/4p3/8/8/8/8/8/1N2K3 w - - 0 1").unwrap();
+    assert!(board.lose_on_time(Side::Black));
+    assert_eq!(
+        board.result,
+        Some(GameResult::Timeout {
+            winner: Side::White
+        })
+    );
+}
+
+#[test]
+fn stalemate() {
+    let board = Board::from_fen("7k/5Q2/6K1/8/8/8/8/8 b - - 0 1").unwrap();
+    assert_eq!(board.result, Some(GameResult::Stalemate));
+    assert!(board.get_legal_moves().is_empty());
+    assert_eq!(board.get_claimable_draw(), None);
+
+    // Stalemating with the last move.
+    let mut board = Board::from_fen("7k/8/6K1/8/8/8/8/5Q2 w - - 0 1").unwrap();
+    play(&mut board, &["Qf7"]);
+    assert_eq!(board.result, Some(GameResult::Stalemate));
+    assert_eq!(board.is_check, None);
+}
@@ -0,0 +1,250 @@
+use chessio::chess::{
+    evaluation::{EvalConfigError, EvalParams, Score, MAX_PHASE},
+    fen::STARTING_POSITION_FEN,
+    position::Position,
+    Board,
+};
+
+const KIWIPETE_FEN: &str = "r3k2r/p1ppqpb1/bn2pnp1/3PN3/1p2P3/2N2Q1p/PPPBBPPP/R3K2R w KQkq - 0 1";
+
+fn get_position(fen: &str) -> Position {
+    Board::from_fen(fen).unwrap().get_position()
+}
+
+/// The same position with the colors swapped.
+fn mirror_fen(fen: &str) -> String {
+    let fields: Vec<&str> = fen.split(' ').collect();
+    let swap_case = |text: &str| -> String {
+        text.chars()
+            .map(|c| {
+                if c.is_ascii_uppercase() {
+                    c.to_ascii_lowercase()
+                } else {
+                    c.to_ascii_uppercase()
+                }
+            })
+            .collect()
+    };
+
+    let placement: Vec<String> = fields[0].split('/').rev().map(swap_case).collect();
+    let side = if fields[1] == "w" { "b" } else { "w" };
+    let en_passant = match fields[3] {
+        "-" => "-".to_string(),
+        target => target
+            .chars()
+            .map(|c| match c {
+                '3' => '6',
+                '6' => '3',
+                c => c,
+            })
+            .collect(),
+    };
+
+    format!(
+        "{} {} {} {} {} {}",
+        placement.join("/"),
+        side,
+        swap_case(fields[2]),
+        en_passant,
+        fields[4],
+        fields[5]
+    )
+}
+
+#[test]
+fn starting_position_is_equal() {
+    let params = EvalParams::default();
+    let breakdown = params.get_breakdown(&get_position(STARTING_POSITION_FEN));
+
+    assert_eq!(breakdown.phase, MAX_PHASE);
+    for (name, score) in breakdown.get_terms() {
+        assert_eq!(score, Score::default(), "{}", name);
+    }
+    assert_eq!(params.evaluate(&get_position(STARTING_POSITION_FEN)), 0);
+}
+
+#[test]
+fn mirrored_positions_have_the_same_score() {
+    let params = EvalParams::default();
+    for fen in [
+        KIWIPETE_FEN,
+        "8/2p5/3p4/KP5r/1R3p1k/8/4P1P1/8 w - - 0 1",
+        "r2q1rk1/pP1p2pp/Q4n2/bbp1p3/Np6/1B3NBn/pPPP1PPP/R3K2R b KQ - 0 1",
+        "rnbq1k1r/pp1Pbppp/2p5/8/2B5/8/PPP1NnPP/RNBQK2R w KQ - 1 8",
+        "rnbqkbnr/ppp1p1pp/8/3pPp2/8/8/PPPP1PPP/RNBQKBNR w KQkq f6 0 3",
+    ] {
+        let position = get_position(fen);
+        let mirrored = get_position(&mirror_fen(fen));
+        let breakdown = params.get_breakdown(&position);
+        let mirrored_breakdown = params.get_breakdown(&mirrored);
+
+        assert_eq!(
+            breakdown.get_total(),
+            -mirrored_breakdown.get_total(),
+            "{}",
+            fen
+        );
+        assert_eq!(
+            params.evaluate(&position),
+            params.evaluate(&mirrored),
+            "{}",
+            fen
+        );
+    }
+}
+
+#[test]
+fn terms_add_up_to_the_evaluation() {
+    let params = EvalParams::default();
+    let position = get_position(KIWIPETE_FEN);
+    let breakdown = params.get_breakdown(&position);
+
+    let total = breakdown
+        .get_terms()
+        .iter()
+        .fold(Score::default(), |total, (_, score)| total + *score);
+    assert_eq!(breakdown.get_total(), total.taper(breakdown.phase));
+    assert_eq!(params.evaluate(&position), breakdown.get_total());
+
+    let table = breakdown.to_string();
+    for (name, _) in breakdown.get_terms() {
+        assert!(table.contains(name), "{}", table);
+    }
+}
+
+#[test]
+fn phase_goes_from_middlegame_to_endgame() {
+    let params = EvalParams::default();
+    assert_eq!(
+        params
+            .get_breakdown(&get_position("4k3/pppppppp/8/8/8/8/PPPPPPPP/4K3 w - - 0 1"))
+            .phase,
+        0
+    );
+    assert_eq!(
+        params
+            .get_breakdown(&get_position("3qk3/8/8/8/8/8/8/3QK3 w - - 0 1"))
+            .phase,
+        8
+    );
+}
+
+#[test]
+fn pawn_structure() {
+    let params = EvalParams {
+        doubled_pawn: Score::new(-1, 0),
+        isolated_pawn: Score::new(-10, 0),
+        passed_pawn: [Score::new(100, 0); 8],
+        ..EvalParams::default()
+    };
+
+    // White: doubled and isolated pawns on the c-file, the front one is
+    // passed. Black: a pawn on h7 with nothing against it, passed as well.
+    let breakdown = params.get_breakdown(&get_position("4k3/7p/8/8/2P5/2P5/8/4K3 w - - 0 1"));
+    assert_eq!(breakdown.pawn_structure.mg, (-1 - 20 + 100) - (-10 + 100));
+
+    // Connected pawns blocked by an enemy pawn in front.
+    let breakdown = params.get_breakdown(&get_position("4k3/8/3p4/8/3PP3/8/8/4K3 w - - 0 1"));
+    assert_eq!(breakdown.pawn_structure.mg, 0 - (-10));
+}
+
+#[test]
+fn king_safety_prefers_a_pawn_shield() {
+    let params = EvalParams::default();
+    let sheltered = params.get_breakdown(&get_position("6k1/5ppp/8/8/8/8/5PPP/6K1 w - - 0 1"));
+    let exposed = params.get_breakdown(&get_position("6k1/5ppp/8/8/5PPP/8/8/6K1 w - - 0 1"));
+
+    assert_eq!(sheltered.king_safety, Score::default());
+    assert!(exposed.king_safety.mg < 0);
+}
+
+#[test]
+fn mobility_counts_reachable_squares() {
+    let params = EvalParams::default();
+    let centralized = params.get_breakdown(&get_position("4k3/8/8/8/3N4/8/8/4K3 w - - 0 1"));
+    let cornered = params.get_breakdown(&get_position("4k3/8/8/8/8/8/8/N3K3 w - - 0 1"));
+
+    assert_eq!(centralized.mobility, Score::new(8 * 4, 8 * 4));
+    assert_eq!(cornered.mobility, Score::new(2 * 4, 2 * 4));
+}
+
+#[test]
+fn config_round_trip() {
+    let params = EvalParams::default();
+    assert_eq!(
+        EvalParams::from_config(&params.to_config()),
+        Ok(params.clone())
+    );
+
+    let mut tuned = params.clone();
+    tuned.material[0] = Score::new(90, 130);
+    tuned.piece_squares[2][27] = Score::new(33, -7);
+    tuned.passed_pawn[6] = Score::new(70, 200);
+    assert_eq!(EvalParams::from_config(&tuned.to_config()), Ok(tuned));
+}
+
+#[test]
+fn config_overrides_only_the_given_keys() {
+    let config = "
+        # Cheaper pawns.
+        material.pawn = 80 100
+        pawns.passed.eg = 0 1 2 3
+            4 5 6 7
+    ";
+    let params = EvalParams::from_config(config).unwrap();
+    let default = EvalParams::default();
+
+    assert_eq!(params.material[0], Score::new(80, 100));
+    assert_eq!(params.material[1], default.material[1]);
+    assert_eq!(
+        params.passed_pawn[5],
+        Score::new(default.passed_pawn[5].mg, 5)
+    );
+    assert_eq!(params.mobility, default.mobility);
+}
+
+#[test]
+fn config_errors() {
+    assert_eq!(
+        EvalParams::from_config("42"),
+        Err(EvalConfigError::InvalidLine { line: 1 })
+    );
+    assert_eq!(
+        EvalParams::from_config("material.pawn = 100 x"),
+        Err(EvalConfigError::InvalidNumber {
+            line: 1,
+            value: "x".to_string()
+        })
+    );
+    assert_eq!(
+        EvalParams::from_config("\nmaterial.pawnn = 1 2"),
+        Err(EvalConfigError::UnknownKey {
+            line: 2,
+            key: "material.pawnn".to_string()
+        })
+    );
+    assert_eq!(
+        EvalParams::from_config("pst.knight.mg = 1 2 3"),
+        Err(EvalConfigError::WrongValueCount {
+            line: 1,
+            key: "pst.knight.mg".to_string(),
+            expected: 64,
+            found: 3
+        })
+    );
+}
+
+#[test]
+fn load_weights_from_a_file() {
+    let path = std::env::temp_dir().join(format!("chessio-eval-{}.cfg", std::process::id()));
+    std::fs::write(&path, "king.open_file = -50 -5\n").unwrap();
+
+    let params = EvalParams::load(path.to_str().unwrap()).unwrap();
+    std::fs::remove_file(&path).unwrap();
+    assert_eq!(params.king_open_file, Score::new(-50, -5));
+
+    assert!(matches!(
+        EvalParams::load(path.to_str().unwrap()),
+        Err(EvalConfigError::Io(_))
+    ));
+}
@@ -0,0 +1,115 @@
+use chessio::chess::{fen::FenError, piece::Side, Board};
+
+#[test]
+fn malformed_fields() {
+    for (fen, error) in [
+        ("8/8/8/8/8/8/8/4K2k w -", FenError::WrongFieldCount(3)),
+        ("4k3/8/8/8/8/8/4K3 w - - 0 1", FenError::WrongRowCount(7)),
+        ("4k3/8/8/8/8/9/8/4K3 w - - 0 1", FenError::InvalidPiece('9')),
+        (
+            "4k3/8/8/8/8/7/8/4K3 w - - 0 1",
+            FenError::WrongRowLength { row: 3, length: 7 },
+        ),
+        (
+            "4k3/8/8/8/8/8/8/4K3 x - - 0 1",
+            FenError::InvalidSideOnMove("x".to_string()),
+        ),
+        (
+            "4k3/8/8/8/8/8/8/4K3 w KK - 0 1",
+            FenError::InvalidCastlingRights("KK".to_string()),
+        ),
+        // No rook on h1.
+        (
+            "4k3/8/8/8/8/8/8/4K3 w K - 0 1",
+            FenError::InvalidCastlingRights("K".to_string()),
+        ),
+        // No king on e8.
+        (
+            "r2k4/8/8/8/8/8/8/4K3 w q - 0 1",
+            FenError::InvalidCastlingRights("q".to_string()),
+        ),
+        (
+            "4k3/8/8/8/8/8/8/4K3 w - e9 0 1",
+            FenError::InvalidEnPassantTarget("e9".to_string()),
+        ),
+        (
+            "4k3/8/8/8/8/8/8/4K3 w - - x 1",
+            FenError::InvalidHalfmoveClock("x".to_string()),
+        ),
+        (
+            "4k3/8/8/8/8/8/8/4K3 w - - 0 0",
+            FenError::InvalidFullmoveNumber("0".to_string()),
+        ),
+        (
+            "8/8/8/8/8/8/8/4K3 w - - 0 1",
+            FenError::WrongKingCount {
+                side: Side::Black,
+                count: 0,
+            },
+        ),
+        (
+            "4k2R/8/8/8/8/8/8/4K3 w - - 0 1",
+            FenError::SideNotOnMoveInCheck,
+        ),
+    ] {
+        assert_eq!(Board::from_fen(fen).err(), Some(error), "{}", fen);
+    }
+}
+
+#[test]
+fn pawns_on_back_rows() {
+    assert_eq!(
+        Board::from_fen("P3k3/8/8/8/8/8/8/4K3 w - - 0 1").err(),
+        Some(FenError::PawnOnBackRow { row: 8 })
+    );
+    assert_eq!(
+        Board::from_fen("4k3/8/8/8/8/8/8/4K2p w - - 0 1").err(),
+        Some(FenError::PawnOnBackRow { row: 1 })
+    );
+}
+
+#[test]
+fn en_passant_target_needs_the_pawn_that_skipped_it() {
+    assert!(Board::from_fen("4k3/8/8/3Pp3/8/8/8/4K3 w - e6 0 1").is_ok());
+    assert!(Board::from_fen("4k3/8/8/8/3pP3/8/8/4K3 b - e3 0 1").is_ok());
+
+    for fen in [
+        // No pawn in front of the target.
+        "4k3/8/8/3P4/8/8/8/4K3 w - e6 0 1",
+        // A pawn of the side on move.
+        "4k3/8/8/3PP3/8/8/8/4K3 w - e6 0 1",
+        // The target or the tile the pawn came from is taken.
+        "4k3/8/4n3/3Pp3/8/8/8/4K3 w - e6 0 1",
+        "4k3/4n3/8/3Pp3/8/8/8/4K3 w - e6 0 1",
+        // The wrong row for the side on move.
+        "4k3/8/8/3Pp3/8/8/8/4K3 b - e6 0 1",
+    ] {
+        assert_eq!(
+            Board::from_fen(fen).err(),
+            Some(FenError::InvalidEnPassantTarget("e6".to_string())),
+            "{}",
+            fen
+        );
+    }
+}
+
+#[test]
+fn round_trip() {
+    for fen in [
+        "rnbqkbnr/pppppppp/8/8/8/8/PPPPPPPP/RNBQKBNR w KQkq - 0 1",
+        "r3k2r/p1ppqpb1/bn2pnp1/3PN3/1p2P3/2N2Q1p/PPPBBPPP/R3K2R w KQkq - 0 1",
+        "rnbqkbnr/ppp1pppp/8/3pP3/8/8/PPPP1PPP/RNBQKBNR w Kq d6 0 3",
+        "8/2p5/3p4/KP5r/1R3p1k/8/4P1P1/8 b - - 12 41",
+    ] {
+        assert_eq!(Board::from_fen(fen).unwrap().to_fen(), fen);
+    }
+
+    let mut board = Board::new();
+    board.move_piece_san("e4").unwrap();
+    let fen = board.to_fen();
+    assert_eq!(
+        fen,
+        "rnbqkbnr/pppppppp/8/8/4P3/8/PPPP1PPP/RNBQKBNR b KQkq e3 0 1"
+    );
+    assert_eq!(Board::from_fen(&fen).unwrap().to_fen(), fen);
+}
@@ -0,0 +1,54 @@
+use chessio::chess::{
+    piece::{PieceType, Side},
+    Board,
+};
+
+fn play(board: &mut Board, moves: &[&str]) {
+    for san in moves {
+        board.move_piece_san(san).unwrap();
+    }
+}
+
+#[test]
+fn captured_pieces() {
+    let mut board = Board::new();
+    assert_eq!(board.get_captured_pieces(Side::White), []);
+    assert_eq!(board.get_material_balance(), 0);
+
+    // Scandinavian with an early queen trade.
+    play(
+        &mut board,
+        &["e4", "d5", "exd5", "Qxd5", "Nc3", "Qxd2+", "Qxd2", "Bg4"],
+    );
+    assert_eq!(
+        board.get_captured_pieces(Side::White),
+        [PieceType::Pawn, PieceType::Queen]
+    );
+    assert_eq!(
+        board.get_captured_pieces(Side::Black),
+        [PieceType::Pawn, PieceType::Pawn]
+    );
+    assert_eq!(board.get_material_balance(), 8);
+}
+
+#[test]
+fn promotions_are_not_captures() {
+    let fen = "1r2k3/P7/8/8/8/8/8/4K3 w - - 0 1";
+    let mut board = Board::from_fen(fen).unwrap();
+    assert_eq!(board.get_material_balance(), -4);
+
+    play(&mut board, &["a8=Q"]);
+    assert!(board.get_captured_pieces(Side::White).is_empty());
+    assert_eq!(board.get_material_balance(), 4);
+
+    // The promoted queen is captured as a queen.
+    play(&mut board, &["Rxa8"]);
+    assert_eq!(board.get_captured_pieces(Side::Black), [PieceType::Queen]);
+    assert_eq!(board.get_material_balance(), -5);
+
+    // Promoting with a capture takes the captured piece only.
+    let mut board = Board::from_fen(fen).unwrap();
+    play(&mut board, &["axb8=Q+"]);
+    assert_eq!(board.get_captured_pieces(Side::White), [PieceType::Rook]);
+    assert_eq!(board.get_material_balance(), 9);
+}
@@ -0,0 +1,382 @@
+use std::{
+    io::{BufRead, BufReader, Write},
+    net::{TcpListener, TcpStream},
+    thread,
+    time::{Duration, Instant},
+};
+
+use chessio::chess::{
+    network::{Message, NetworkError, Offer, Peer, PeerEvent, PROTOCOL_VERSION},
+    piece::Side,
+    result::GameResult,
+    tile::get_tile_coords,
+    Board,
+};
+
+/// One of the two players, with its own board.
+struct Player {
+    peer: Peer,
+    board: Board,
+}
+
+impl Player {
+    fn play(&mut self, from: &str, to: &str) -> bool {
+        let (from, to) = (get_tile_coords(from).unwrap(), get_tile_coords(to).unwrap());
+        self.peer
+            .play_move(&mut self.board, from, to, None)
+            .unwrap()
+    }
+    fn wait_for_event(&mut self) -> Result<PeerEvent, NetworkError> {
+        let started = Instant::now();
+        while started.elapsed() < Duration::from_secs(10) {
+            if let Some(event) = self.peer.poll(&mut self.board)? {
+                return Ok(event);
+            }
+            thread::sleep(Duration::from_millis(1));
+        }
+
+        panic!("the other player sent nothing");
+    }
+}
+
+fn get_listener() -> (TcpListener, String) {
+    let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+    let address = listener.local_addr().unwrap().to_string();
+    (listener, address)
+}
+
+/// Host playing `host_side` on `board` and the player who joined it.
+fn connect(board: Board, host_side: Side) -> (Player, Player) {
+    let (listener, address) = get_listener();
+    let host = thread::spawn(move || {
+        let peer = Peer::host(&listener, host_side, &board).unwrap();
+        Player { peer, board }
+    });
+
+    let (peer, board) = Peer::join(address.as_str()).unwrap();
+    (host.join().unwrap(), Player { peer, board })
+}
+
+/// Other end of the connection to a host, speaking the protocol by hand.
+fn connect_raw(board: Board, host_side: Side) -> (Player, BufReader<TcpStream>) {
+    let (listener, address) = get_listener();
+    let host = thread::spawn(move || {
+        let peer = Peer::host(&listener, host_side, &board).unwrap();
+        Player { peer, board }
+    });
+
+    let mut stream = BufReader::new(TcpStream::connect(address).unwrap());
+    send_raw(&mut stream, &format!("hello {}", PROTOCOL_VERSION));
+    assert_eq!(read_raw(&mut stream), format!("hello {}", PROTOCOL_VERSION));
+    assert!(read_raw(&mut stream).starts_with("game "));
+    (host.join().unwrap(), stream)
+}
+
+fn send_raw(stream: &mut BufReader<TcpStream>, line: &str) {
+    writeln!(stream.get_mut(), "{}", line).unwrap();
+}
+
+fn read_raw(stream: &mut BufReader<TcpStream>) -> String {
+    let mut line = String::new();
+    stream.read_line(&mut line).unwrap();
+    line.trim_end().to_string()
+}
+
+/// Lets `receiver` get the move that crossed the end of the game and waits
+/// for `sender` to hear that it was not played.
+fn wait_for_ignored_move(receiver: &mut Player, sender: &mut Player) -> PeerEvent {
+    let started = Instant::now();
+    while started.elapsed() < Duration::from_secs(10) {
+        assert_eq!(receiver.peer.poll(&mut receiver.board), Ok(None));
+        if let Some(event) = sender.peer.poll(&mut sender.board).unwrap() {
+            return event;
+        }
+        thread::sleep(Duration::from_millis(1));
+    }
+
+    panic!("the move was not ignored");
+}
+
+#[test]
+fn messages() {
+    for line in [
+        "hello 1",
+        "game white rnbqkbnr/pppppppp/8/8/8/8/PPPPPPPP/RNBQKBNR w KQkq - 0 1",
+        "game black 8/4P1k1/8/8/8/8/8/4K3 w - - 0 1 moves e7e8q g7g6",
+        "move e7e8n",
+        "stale e2e4",
+        "resign",
+        "flag black",
+        "draw offer",
+        "draw accept",
+        "draw decline",
+        "takeback request",
+        "takeback accept 12",
+        "takeback decline",
+    ] {
+        let message = Message::parse(line).unwrap();
+        assert_eq!(message.to_string(), line);
+    }
+
+    for line in [
+        "",
+        "hello",
+        "hello x",
+        "game red",
+        "game white",
+        "move",
+        "draw",
+        "flag red",
+    ] {
+        assert_eq!(Message::parse(line), None, "{}", line);
+    }
+}
+
+#[test]
+fn joining_player_gets_the_game() {
+    let mut board = Board::from_fen("r3k2r/8/8/8/8/8/8/R3K2R w KQkq - 0 1").unwrap();
+    for san in ["O-O", "O-O-O"] {
+        board.move_piece_san(san).unwrap();
+    }
+    let (host, guest) = connect(board, Side::Black);
+
+    assert_eq!(host.peer.side, Side::Black);
+    assert_eq!(guest.peer.side, Side::White);
+    assert_eq!(guest.board.starting_fen, host.board.starting_fen);
+    assert_eq!(guest.board.move_records.len(), 2);
+    assert_eq!(
+        guest.board.get_position().get_hash(),
+        host.board.get_position().get_hash()
+    );
+}
+
+#[test]
+fn players_move_only_their_own_pieces() {
+    let (mut white, mut black) = connect(Board::new(), Side::White);
+
+    assert!(!black.play("e7", "e5"));
+    assert!(!white.play("e2", "e5"));
+    assert!(white.play("e2", "e4"));
+    assert!(!white.play("d2", "d4"));
+    assert_eq!(black.wait_for_event(), Ok(PeerEvent::Moved));
+
+    assert!(black.play("e7", "e5"));
+    assert_eq!(white.wait_for_event(), Ok(PeerEvent::Moved));
+    assert!(white.play("g1", "f3"));
+    assert_eq!(black.wait_for_event(), Ok(PeerEvent::Moved));
+
+    assert_eq!(white.board.move_records.len(), 3);
+    assert_eq!(
+        white.board.get_position().get_hash(),
+        black.board.get_position().get_hash()
+    );
+}
+
+#[test]
+fn moves_of_the_other_player_are_validated() {
+    let (mut host, mut guest) = connect_raw(Board::new(), Side::White);
+
+    // Black moving while White is on move.
+    send_raw(&mut guest, "move e7e5");
+    assert_eq!(
+        host.wait_for_event(),
+        Err(NetworkError::UnexpectedMessage("move e7e5".to_string()))
+    );
+
+    assert!(host.play("e2", "e4"));
+    assert_eq!(read_raw(&mut guest), "move e2e4");
+    send_raw(&mut guest, "move e7e4");
+    assert_eq!(
+        host.wait_for_event(),
+        Err(NetworkError::IllegalMove("e7e4".to_string()))
+    );
+    send_raw(&mut guest, "castle kingside");
+    assert_eq!(
+        host.wait_for_event(),
+        Err(NetworkError::InvalidMessage("castle kingside".to_string()))
+    );
+    send_raw(&mut guest, "draw accept");
+    assert!(matches!(
+        host.wait_for_event(),
+        Err(NetworkError::UnexpectedMessage(_))
+    ));
+
+    assert_eq!(host.board.move_records.len(), 1);
+    send_raw(&mut guest, "move e7e5");
+    assert_eq!(host.wait_for_event(), Ok(PeerEvent::Moved));
+}
+
+#[test]
+fn protocol_versions_have_to_match() {
+    let (listener, address) = get_listener();
+    let host = thread::spawn(move || Peer::host(&listener, Side::White, &Board::new()).err());
+
+    let mut stream = BufReader::new(TcpStream::connect(&address).unwrap());
+    send_raw(&mut stream, "hello 999");
+    assert_eq!(
+        host.join().unwrap(),
+        Some(NetworkError::VersionMismatch { theirs: 999 })
+    );
+
+    // The same from the side of the joining player.
+    let (listener, address) = get_listener();
+    let fake_host = thread::spawn(move || {
+        let (stream, _) = listener.accept().unwrap();
+        let mut stream = BufReader::new(stream);
+        send_raw(&mut stream, "hello 0");
+        read_raw(&mut stream)
+    });
+    assert_eq!(
+        Peer::join(address.as_str()).err(),
+        Some(NetworkError::VersionMismatch { theirs: 0 })
+    );
+    assert_eq!(
+        fake_host.join().unwrap(),
+        format!("hello {}", PROTOCOL_VERSION)
+    );
+}
+
+#[test]
+fn resigning() {
+    let (mut white, mut black) = connect(Board::new(), Side::White);
+
+    black.peer.resign(&mut black.board).unwrap();
+    let result = Some(GameResult::Resignation {
+        winner: Side::White,
+    });
+    assert_eq!(black.board.result, result);
+    assert_eq!(white.wait_for_event(), Ok(PeerEvent::Resigned));
+    assert_eq!(white.board.result, result);
+    assert!(!white.play("e2", "e4"));
+}
+
+#[test]
+fn moves_crossing_the_end_of_the_game() {
+    // Black mates while White resigns, both boards end with the resignation
+    // and without the mating move.
+    let (mut white, mut black) = connect(Board::new(), Side::White);
+    for (from, to) in [("f2", "f3"), ("e7", "e5"), ("g2", "g4")] {
+        let (player, other) = match white.board.get_side_on_move() {
+            Side::White => (&mut white, &mut black),
+            Side::Black => (&mut black, &mut white),
+        };
+        assert!(player.play(from, to));
+        assert_eq!(other.wait_for_event(), Ok(PeerEvent::Moved));
+    }
+    assert!(black.play("d8", "h4"));
+    assert!(matches!(
+        black.board.result,
+        Some(GameResult::Checkmate { .. })
+    ));
+    white.peer.resign(&mut white.board).unwrap();
+    assert_eq!(black.wait_for_event(), Ok(PeerEvent::Resigned));
+    assert_eq!(
+        wait_for_ignored_move(&mut white, &mut black),
+        PeerEvent::IgnoredMove
+    );
+    let result = Some(GameResult::Resignation {
+        winner: Side::Black,
+    });
+    assert_eq!(white.board.result, result);
+    assert_eq!(black.board.result, result);
+    assert_eq!(black.board.move_records.len(), 3);
+    assert_eq!(black.board.to_fen(), white.board.to_fen());
+
+    // White plays just as its time runs out on the clock of Black.
+    let (mut white, mut black) = connect(Board::new(), Side::White);
+    assert!(white.play("e2", "e4"));
+    assert_eq!(
+        black.peer.lose_on_time(&mut black.board, Side::White),
+        Ok(true)
+    );
+    assert_eq!(white.wait_for_event(), Ok(PeerEvent::Flagged));
+    assert_eq!(
+        wait_for_ignored_move(&mut black, &mut white),
+        PeerEvent::IgnoredMove
+    );
+    let result = Some(GameResult::Timeout {
+        winner: Side::Black,
+    });
+    assert_eq!(white.board.result, result);
+    assert_eq!(black.board.result, result);
+    assert!(white.board.move_records.is_empty());
+}
+
+#[test]
+fn draw_offers() {
+    let (mut white, mut black) = connect(Board::new(), Side::White);
+
+    assert_eq!(white.peer.offer_draw(&white.board), Ok(true));
+    assert_eq!(white.peer.get_sent_offer(), Some(Offer::Draw));
+    // Only one offer at a time.
+    assert_eq!(white.peer.offer_draw(&white.board), Ok(false));
+    assert_eq!(black.wait_for_event(), Ok(PeerEvent::OfferedDraw));
+    assert_eq!(black.peer.get_received_offer(), Some(Offer::Draw));
+
+    black.peer.answer_offer(&mut black.board, false).unwrap();
+    assert_eq!(white.wait_for_event(), Ok(PeerEvent::DeclinedDraw));
+    assert_eq!(white.peer.get_sent_offer(), None);
+    assert_eq!(white.board.result, None);
+
+    assert_eq!(black.peer.offer_draw(&black.board), Ok(true));
+    assert_eq!(white.wait_for_event(), Ok(PeerEvent::OfferedDraw));
+    white.peer.answer_offer(&mut white.board, true).unwrap();
+    assert_eq!(black.wait_for_event(), Ok(PeerEvent::AcceptedDraw));
+    assert_eq!(white.board.result, Some(GameResult::DrawAgreement));
+    assert_eq!(black.board.result, Some(GameResult::DrawAgreement));
+}
+
+#[test]
+fn takebacks() {
+    let (mut white, mut black) = connect(Board::new(), Side::White);
+    assert_eq!(white.peer.request_takeback(&white.board), Ok(false));
+
+    assert!(white.play("e2", "e4"));
+    black.wait_for_event().unwrap();
+    assert!(black.play("e7", "e5"));
+    white.wait_for_event().unwrap();
+    assert!(white.play("b1", "c3"));
+    black.wait_for_event().unwrap();
+
+    // White takes back the move it has just played.
+    assert_eq!(white.peer.request_takeback(&white.board), Ok(true));
+    assert_eq!(black.wait_for_event(), Ok(PeerEvent::RequestedTakeback));
+    black.peer.answer_offer(&mut black.board, true).unwrap();
+    assert_eq!(white.wait_for_event(), Ok(PeerEvent::AcceptedTakeback));
+    assert_eq!(white.board.move_records.len(), 2);
+    assert_eq!(black.board.move_records.len(), 2);
+
+    // Black has already replied, both moves are taken back.
+    assert!(white.play("g1", "f3"));
+    black.wait_for_event().unwrap();
+    assert!(black.play("b8", "c6"));
+    white.wait_for_event().unwrap();
+    assert_eq!(white.peer.request_takeback(&white.board), Ok(true));
+    // No moves while waiting for the answer.
+    assert!(!white.play("f1", "c4"));
+    assert_eq!(black.wait_for_event(), Ok(PeerEvent::RequestedTakeback));
+    black.peer.answer_offer(&mut black.board, true).unwrap();
+    assert_eq!(white.wait_for_event(), Ok(PeerEvent::AcceptedTakeback));
+    assert_eq!(white.board.move_records.len(), 2);
+    assert_eq!(white.board.get_side_on_move(), Side::White);
+    assert_eq!(
+        white.board.get_position().get_hash(),
+        black.board.get_position().get_hash()
+    );
+
+    // A declined request changes nothing.
+    assert!(white.play("f1", "c4"));
+    black.wait_for_event().unwrap();
+    assert_eq!(white.peer.request_takeback(&white.board), Ok(true));
+    black.wait_for_event().unwrap();
+    black.peer.answer_offer(&mut black.board, false).unwrap();
+    assert_eq!(white.wait_for_event(), Ok(PeerEvent::DeclinedTakeback));
+    assert_eq!(white.board.move_records.len(), 3);
+}
+
+#[test]
+fn disconnecting() {
+    let (mut white, black) = connect(Board::new(), Side::White);
+    drop(black);
+    assert_eq!(white.wait_for_event(), Err(NetworkError::Disconnected));
+}
@@ -0,0 +1,104 @@
+use chessio::chess::{fen::STARTING_POSITION_FEN, Board};
+
+/// Board perft goes through the whole game logic and is only run up to this
+/// many nodes.
+const BOARD_PERFT_NODES: u64 = 100_000;
+
+/// Checks the leaf counts of `fen` for depths 1, 2, ... against `expected`.
+fn assert_perft(fen: &str, expected: &[u64]) {
+    let mut position = Board::from_fen(fen).unwrap().get_position();
+    let mut board = Board::from_fen(fen).unwrap();
+
+    for (depth, nodes) in expected.iter().enumerate() {
+        let depth = depth as u32 + 1;
+        assert_eq!(position.perft(depth), *nodes, "perft({}) of {}", depth, fen);
+        if *nodes <= BOARD_PERFT_NODES {
+            assert_eq!(
+                board.perft(depth),
+                *nodes,
+                "board perft({}) of {}",
+                depth,
+                fen
+            );
+        }
+    }
+
+    assert_eq!(position, Board::from_fen(fen).unwrap().get_position());
+    assert_eq!(board.to_fen(), Board::from_fen(fen).unwrap().to_fen());
+}
+
+#[test]
+fn initial_position() {
+    assert_perft(STARTING_POSITION_FEN, &[20, 400, 8902, 197281, 4865609]);
+}
+
+#[test]
+fn kiwipete() {
+    assert_perft(
+        "r3k2r/p1ppqpb1/bn2pnp1/3PN3/1p2P3/2N2Q1p/PPPBBPPP/R3K2R w KQkq - 0 1",
+        &[48, 2039, 97862, 4085603],
+    );
+}
+
+#[test]
+fn position_3() {
+    assert_perft(
+        "8/2p5/3p4/KP5r/1R3p1k/8/4P1P1/8 w - - 0 1",
+        &[14, 191, 2812, 43238, 674624],
+    );
+}
+
+#[test]
+fn position_4() {
+    assert_perft(
+        "r3k2r/Pppp1ppp/1b3nbN/nP6/BBP1P3/q4N2/Pp1P2PP/R2Q1RK1 w kq - 0 1",
+        &[6, 264, 9467, 422333],
+    );
+}
+
+#[test]
+fn position_4_mirrored() {
+    assert_perft(
+        "r2q1rk1/pP1p2pp/Q4n2/bbp1p3/Np6/1B3NBn/pPPP1PPP/R3K2R b KQ - 0 1",
+        &[6, 264, 9467, 422333],
+    );
+}
+
+#[test]
+fn position_5() {
+    assert_perft(
+        "rnbq1k1r/pp1Pbppp/2p5/8/2B5/8/PPP1NnPP/RNBQK2R w KQ - 1 8",
+        &[44, 1486, 62379, 2103487],
+    );
+}
+
+#[test]
+fn position_6() {
+    assert_perft(
+        "r4rk1/1pp1qppp/p1np1n2/2b1p1B1/2B1P1b1/P1NP1N2/1PP1QPPP/R4RK1 w - - 0 10",
+        &[46, 2079, 89890, 3894594],
+    );
+}
+
+#[test]
+fn divide_adds_up_to_perft() {
+    let mut board =
+        Board::from_fen("r3k2r/p1ppqpb1/bn2pnp1/3PN3/1p2P3/2N2Q1p/PPPBBPPP/R3K2R w KQkq - 0 1")
+            .unwrap();
+    let divided = board.divide(2);
+
+    assert_eq!(divided.len(), 48);
+    assert_eq!(divided.iter().map(|(_, nodes)| nodes).sum::<u64>(), 2039);
+    assert!(divided.contains(&("e1g1".to_string(), 43)));
+    assert!(divided.contains(&("e1c1".to_string(), 43)));
+}
+
+#[test]
+fn divide_names_promotions() {
+    let mut board = Board::from_fen("8/P7/8/8/8/8/8/k6K w - - 0 1").unwrap();
+    let names: Vec<String> = board.divide(1).into_iter().map(|(name, _)| name).collect();
+
+    for name in ["a7a8q", "a7a8r", "a7a8b", "a7a8n"] {
+        assert!(names.contains(&name.to_string()));
+    }
+}
@@ -0,0 +1,192 @@
+use chessio::chess::{
+    pgn::{parse_pgn, PgnError, PgnGame},
+    piece::Side,
+    Board,
+};
+
+fn play(board: &mut Board, moves: &[&str]) {
+    for san in moves {
+        board.move_piece_san(san).unwrap();
+    }
+}
+
+#[test]
+fn export_short_game() {
+    let mut board = Board::new();
+    play(
+        &mut board,
+        &["e4", "e5", "Bc4", "Nc6", "Qh5", "Nf6", "Qxf7#"],
+    );
+
+    let mut game = PgnGame::from_board(&board);
+    game.set_tag("Annotator", "chessio");
+    game.set_tag("White", "Scholar");
+    assert_eq!(
+        game.to_pgn(),
+        "[Event \"?\"]\n\
+         [Site \"?\"]\n\
+         [Date \"????.??.??\"]\n\
+         [Round \"?\"]\n\
+         [White \"Scholar\"]\n\
+         [Black \"?\"]\n\
+         [Result \"1-0\"]\n\
+         [Annotator \"chessio\"]\n\
+         \n\
+         1. e4 e5 2. Bc4 Nc6 3. Qh5 Nf6 4. Qxf7# 1-0\n"
+    );
+
+    // An unfinished game from a custom position.
+    let fen = "4k3/8/8/8/8/8/4P3/4K3 b - - 0 30";
+    let mut board = Board::from_fen(fen).unwrap();
+    play(&mut board, &["Kd7", "e4"]);
+    let pgn = board.to_pgn();
+    assert!(pgn.contains("[Result \"*\"]\n[SetUp \"1\"]\n"));
+    assert!(pgn.contains(&format!("[FEN \"{}\"]\n", fen)));
+    assert!(pgn.ends_with("\n30... Kd7 31. e4 *\n"));
+}
+
+#[test]
+fn import_annotated_game() {
+    let pgn = "[Event \"Annotated\"]\n\
+               [Result \"1/2-1/2\"]\n\
+               \n\
+               {Opening} 1. e4 e5 $1 2. Nf3 {Develops} (2. f4 exf4 (2... d5) 3. Nf3) \
+               2... Nc6?! ; a line comment\n\
+               3. Bb5 1/2-1/2\n";
+    let games = parse_pgn(pgn).unwrap();
+    assert_eq!(games.len(), 1);
+    let game = &games[0];
+
+    assert_eq!(game.get_tag("Event"), Some("Annotated"));
+    assert_eq!(game.result, "1/2-1/2");
+    assert_eq!(game.comments, ["Opening"]);
+
+    let sans: Vec<&str> = game.moves.iter().map(|mv| mv.san.as_str()).collect();
+    assert_eq!(sans, ["e4", "e5", "Nf3", "Nc6", "Bb5"]);
+    assert_eq!(game.moves[1].nags, [1]);
+    assert_eq!(game.moves[3].nags, [6]);
+    assert_eq!(game.moves[3].comments, ["a line comment"]);
+    assert_eq!(
+        (game.moves[3].move_number, game.moves[3].side),
+        (2, Side::Black)
+    );
+
+    let nf3 = &game.moves[2];
+    assert_eq!(nf3.comments, ["Develops"]);
+    assert_eq!(nf3.variations.len(), 1);
+    let variation = &nf3.variations[0].moves;
+    let sans: Vec<&str> = variation.iter().map(|mv| mv.san.as_str()).collect();
+    assert_eq!(sans, ["f4", "exf4", "Nf3"]);
+    // The nested variation replaces Black's reply to f4.
+    let nested = &variation[1].variations[0].moves;
+    assert_eq!(nested[0].san, "d5");
+    assert_eq!((nested[0].move_number, nested[0].side), (2, Side::Black));
+
+    // The board follows the mainline only.
+    assert_eq!(game.board.move_records.len(), 5);
+    assert_eq!(game.board.get_side_on_move(), Side::Black);
+
+    // Move numbers without a period.
+    let game = &parse_pgn("1 e4 e5 2 Nf3 (2 f4) *").unwrap()[0];
+    let sans: Vec<&str> = game.moves.iter().map(|mv| mv.san.as_str()).collect();
+    assert_eq!(sans, ["e4", "e5", "Nf3"]);
+    assert_eq!(game.moves[2].variations[0].moves[0].san, "f4");
+}
+
+#[test]
+fn import_from_black_move_number() {
+    let pgn = "[FEN \"rnbqkbnr/pppppppp/8/8/4P3/8/PPPP1PPP/RNBQKBNR b KQkq - 0 12\"]\n\
+               \n\
+               12... e5 13. Nf3 *\n";
+    let game = &parse_pgn(pgn).unwrap()[0];
+    assert_eq!(game.result, "*");
+    assert_eq!(
+        (game.moves[0].move_number, game.moves[0].side),
+        (12, Side::Black)
+    );
+    assert_eq!(
+        (game.moves[1].move_number, game.moves[1].side),
+        (13, Side::White)
+    );
+    assert_eq!(game.board.fullmove_number, 13);
+}
+
+#[test]
+fn import_errors() {
+    assert_eq!(
+        parse_pgn("1. e4 e5 2. Ke3 *").unwrap_err(),
+        PgnError::IllegalMove {
+            game: 1,
+            move_number: 2,
+            side: Side::White,
+            san: "Ke3".to_string(),
+        }
+    );
+    // Illegal moves inside variations are found too.
+    assert!(matches!(
+        parse_pgn("1. e4 (1. e5) e5 *"),
+        Err(PgnError::IllegalMove { .. })
+    ));
+    assert_eq!(
+        parse_pgn("[Result \"1-0\"]\n\n1. e4 e5").unwrap_err(),
+        PgnError::MissingResult { game: 1 }
+    );
+    assert_eq!(
+        parse_pgn("1. e4 *\n\n1. d4 d5").unwrap_err(),
+        PgnError::MissingResult { game: 2 }
+    );
+    assert_eq!(
+        parse_pgn("1. e4 {unfinished\n*").unwrap_err(),
+        PgnError::UnterminatedComment { line: 1 }
+    );
+    assert_eq!(
+        parse_pgn("1. e4 (1. d4 *").unwrap_err(),
+        PgnError::UnbalancedVariation { line: 1 }
+    );
+    assert_eq!(
+        parse_pgn("(1. d4) 1. e4 *").unwrap_err(),
+        PgnError::VariationWithoutMove { line: 1 }
+    );
+    assert_eq!(
+        parse_pgn("1. e4 e5\n2. -- Nc6 *").unwrap_err(),
+        PgnError::NullMove { line: 2 }
+    );
+    assert_eq!(
+        PgnError::NullMove { line: 2 }.to_string(),
+        "line 2: unsupported null move"
+    );
+    assert!(matches!(
+        parse_pgn("[FEN \"8/8/8/8/8/8/8/8 w - - 0 1\"]\n\n*"),
+        Err(PgnError::InvalidFen { game: 1, .. })
+    ));
+}
+
+#[test]
+fn round_trip() {
+    let mut board = Board::new();
+    play(
+        &mut board,
+        &[
+            "d4", "d5", "c4", "dxc4", "e4", "b5", "a4", "c6", "axb5", "cxb5", "Qf3",
+        ],
+    );
+    let mut game = PgnGame::from_board(&board);
+    game.set_tag("Event", "Round trip");
+    game.comments.push("Queen's Gambit Accepted".to_string());
+    game.moves[3].nags.push(5);
+    game.moves[3].comments.push("Holding the pawn".to_string());
+
+    let pgn = game.to_pgn();
+    let parsed = parse_pgn(&pgn).unwrap();
+    assert_eq!(parsed.len(), 1);
+    assert_eq!(parsed[0].tags, game.tags);
+    assert_eq!(parsed[0].comments, game.comments);
+    assert_eq!(parsed[0].moves, game.moves);
+    assert_eq!(parsed[0].result, game.result);
+    assert_eq!(parsed[0].board.to_fen(), board.to_fen());
+    assert_eq!(parsed[0].to_pgn(), pgn);
+
+    // Several games in one file.
+    let both = format!("{}\n{}", pgn, Board::new().to_pgn());
+    assert_eq!(parse_pgn(&both).unwrap().len(), 2);
+}
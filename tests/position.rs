@@ -0,0 +1,105 @@
+use chessio::chess::{fen::STARTING_POSITION_FEN, moves::Move, piece::PieceType, Board};
+
+const KIWIPETE_FEN: &str = "r3k2r/p1ppqpb1/bn2pnp1/3PN3/1p2P3/2N2Q1p/PPPBBPPP/R3K2R w KQkq - 0 1";
+
+fn find_move(board: &Board, name: &str) -> Move {
+    board
+        .get_position()
+        .legal_moves()
+        .into_iter()
+        .find(|mv| mv.to_string() == name)
+        .unwrap_or_else(|| panic!("{} is not legal", name))
+}
+
+#[test]
+fn unmake_restores_every_position() {
+    let mut position = Board::from_fen(KIWIPETE_FEN).unwrap().get_position();
+    let original = position.clone();
+
+    for mv in position.legal_moves() {
+        position.make_move(mv);
+        for reply in position.legal_moves() {
+            let before_reply = position.clone();
+            position.make_move(reply);
+            assert_eq!(position.unmake_move(), Some(reply));
+            assert_eq!(position, before_reply, "after unmaking {} {}", mv, reply);
+        }
+        assert_eq!(position.unmake_move(), Some(mv));
+        assert_eq!(position, original, "after unmaking {}", mv);
+    }
+
+    assert_eq!(position.unmake_move(), None);
+}
+
+#[test]
+fn make_move_matches_board() {
+    let mut board = Board::from_fen(KIWIPETE_FEN).unwrap();
+    let mut position = board.get_position();
+
+    for name in ["e1g1", "b4c3", "d5e6", "e8c8", "e6f7", "c3b2", "f7f8q"] {
+        let mv = find_move(&board, name);
+        position.make_move(mv);
+        assert!(board.play_move(mv));
+
+        let expected = board.get_position();
+        assert_eq!(
+            position.legal_moves(),
+            expected.legal_moves(),
+            "after {}",
+            name
+        );
+        assert_eq!(
+            position.get_castling_rights(),
+            expected.get_castling_rights()
+        );
+        assert_eq!(position.get_halfmove_clock(), expected.get_halfmove_clock());
+        assert_eq!(
+            position.get_fullmove_number(),
+            expected.get_fullmove_number()
+        );
+        assert_eq!(position.get_move_history().last(), Some(mv));
+    }
+}
+
+#[test]
+fn move_flags() {
+    let board = Board::from_fen(KIWIPETE_FEN).unwrap();
+
+    let castle = find_move(&board, "e1c1");
+    assert!(castle.is_castle() && !castle.is_capture());
+
+    let capture = find_move(&board, "e5f7");
+    assert!(capture.is_capture() && capture.get_flags() == Move::CAPTURE);
+
+    let double_push = find_move(&board, "a2a4");
+    assert!(double_push.is_double_push());
+
+    let mut board = Board::new();
+    for name in ["e2e4", "a7a6", "e4e5", "d7d5"] {
+        board.play_move(find_move(&board, name));
+    }
+    let en_passant = find_move(&board, "e5d6");
+    assert!(en_passant.is_en_passant() && en_passant.is_capture());
+
+    let board = Board::from_fen("1n2k3/P7/8/8/8/8/8/4K3 w - - 0 1").unwrap();
+    let promotion = find_move(&board, "a7b8n");
+    assert_eq!(promotion.get_promotion(), Some(PieceType::Knight));
+    assert!(promotion.is_capture());
+    assert_eq!(
+        find_move(&board, "a7a8q").get_promotion(),
+        Some(PieceType::Queen)
+    );
+}
+
+#[test]
+fn legal_moves_of_the_initial_position() {
+    let position = Board::from_fen(STARTING_POSITION_FEN)
+        .unwrap()
+        .get_position();
+    let mut names: Vec<String> = position.legal_moves().iter().map(Move::to_string).collect();
+    names.sort();
+
+    assert_eq!(names.len(), 20);
+    assert_eq!(names[0], "a2a3");
+    assert!(names.contains(&"g1f3".to_string()));
+}
@@ -0,0 +1,144 @@
+use chessio::chess::{
+    piece::PieceType,
+    san::{move_to_san, parse_move, parse_san, SanError},
+    tile::get_tile_coords,
+    Board,
+};
+
+/// SAN of moving from `from` to `to` in the position of `fen`.
+fn get_san(fen: &str, from: &str, to: &str, promotion: Option<PieceType>) -> Option<String> {
+    let board = Board::from_fen(fen).unwrap();
+    move_to_san(
+        &board,
+        get_tile_coords(from).unwrap(),
+        get_tile_coords(to).unwrap(),
+        promotion,
+    )
+}
+
+#[test]
+fn disambiguation() {
+    let knights = "4k3/8/8/8/8/8/8/1N2KN2 w - - 0 1";
+    assert_eq!(get_san(knights, "b1", "d2", None).as_deref(), Some("Nbd2"));
+    assert_eq!(get_san(knights, "b1", "c3", None).as_deref(), Some("Nc3"));
+
+    let rooks = "4k3/8/8/8/8/R7/8/R3K3 w - - 0 1";
+    assert_eq!(get_san(rooks, "a1", "a2", None).as_deref(), Some("R1a2"));
+    assert_eq!(get_san(rooks, "a3", "a2", None).as_deref(), Some("R3a2"));
+    assert_eq!(get_san(rooks, "a1", "b1", None).as_deref(), Some("Rb1"));
+
+    let queens = "4k3/8/8/8/8/Q7/8/Q1Q1K3 w - - 0 1";
+    assert_eq!(get_san(queens, "a1", "b2", None).as_deref(), Some("Qa1b2"));
+    assert_eq!(get_san(queens, "c1", "b2", None).as_deref(), Some("Qcb2"));
+    assert_eq!(get_san(queens, "a3", "b2", None).as_deref(), Some("Q3b2"));
+}
+
+#[test]
+fn special_moves() {
+    let promotion = "4k3/P7/8/8/8/8/8/4K3 w - - 0 1";
+    assert_eq!(
+        get_san(promotion, "a7", "a8", Some(PieceType::Queen)).as_deref(),
+        Some("a8=Q+")
+    );
+    assert_eq!(
+        get_san(promotion, "a7", "a8", Some(PieceType::Knight)).as_deref(),
+        Some("a8=N")
+    );
+    assert_eq!(get_san(promotion, "a7", "a8", None), None);
+
+    let en_passant = "4k3/8/8/3pP3/8/8/8/4K3 w - d6 0 1";
+    assert_eq!(
+        get_san(en_passant, "e5", "d6", None).as_deref(),
+        Some("exd6")
+    );
+
+    let castling = "r3k2r/8/8/8/8/8/8/R3K2R w KQkq - 0 1";
+    assert_eq!(get_san(castling, "e1", "g1", None).as_deref(), Some("O-O"));
+    assert_eq!(
+        get_san(castling, "e1", "c1", None).as_deref(),
+        Some("O-O-O")
+    );
+    assert_eq!(
+        get_san(castling, "h1", "h8", None).as_deref(),
+        Some("Rxh8+")
+    );
+
+    let scholars_mate = "r1bqkb1r/pppp1ppp/2n2n2/4p2Q/2B1P3/8/PPPP1PPP/RNB1K1NR w KQkq - 4 4";
+    assert_eq!(
+        get_san(scholars_mate, "h5", "f7", None).as_deref(),
+        Some("Qxf7#")
+    );
+    assert_eq!(get_san(scholars_mate, "h5", "h8", None), None);
+}
+
+#[test]
+fn parse_san_errors() {
+    let knights = Board::from_fen("4k3/8/8/8/8/8/8/1N2KN2 w - - 0 1").unwrap();
+    assert_eq!(
+        parse_san(&knights, "Nd2"),
+        Err(SanError::AmbiguousMove("Nd2".to_string()))
+    );
+    assert_eq!(
+        parse_san(&knights, "N1d2"),
+        Err(SanError::AmbiguousMove("N1d2".to_string()))
+    );
+    assert_eq!(parse_san(&knights, "Nfd2"), Ok(((5, 7), (3, 6), None)));
+
+    let board = Board::new();
+    for san in ["Ke2", "O-O", "Nd4", "e5", "exd3"] {
+        assert_eq!(
+            parse_san(&board, san),
+            Err(SanError::IllegalMove(san.to_string()))
+        );
+    }
+    // A promotion needs the piece and other moves must not have one.
+    let promotion = Board::from_fen("4k3/P7/8/8/8/8/8/4K3 w - - 0 1").unwrap();
+    assert!(matches!(
+        parse_san(&promotion, "a8"),
+        Err(SanError::IllegalMove(_))
+    ));
+    assert!(matches!(
+        parse_san(&board, "e4=Q"),
+        Err(SanError::IllegalMove(_))
+    ));
+
+    for san in ["", "e", "e9", "Zf3", "a8=", "a8=QQ", "Ni3", "N@f3"] {
+        assert_eq!(
+            parse_san(&promotion, san),
+            Err(SanError::InvalidSyntax(san.to_string())),
+            "{}",
+            san
+        );
+    }
+}
+
+#[test]
+fn moves_in_san_or_coordinates() {
+    let board = Board::new();
+    assert_eq!(parse_move(&board, "Nf3"), Ok(((6, 7), (5, 5), None)));
+    assert_eq!(parse_move(&board, " g1f3 "), Ok(((6, 7), (5, 5), None)));
+    assert_eq!(parse_move(&board, "e4"), parse_move(&board, "e2e4"));
+
+    assert_eq!(
+        parse_move(&board, "g1g3"),
+        Err(SanError::IllegalMove("g1g3".to_string()))
+    );
+    assert_eq!(
+        parse_move(&board, "Nf6"),
+        Err(SanError::IllegalMove("Nf6".to_string()))
+    );
+    assert!(matches!(
+        parse_move(&board, "hello"),
+        Err(SanError::InvalidSyntax(_))
+    ));
+
+    let board = Board::from_fen("4k3/P7/8/8/8/8/8/4K3 w - - 0 1").unwrap();
+    assert_eq!(
+        parse_move(&board, "a7a8n"),
+        Ok(((0, 1), (0, 0), Some(PieceType::Knight)))
+    );
+    assert_eq!(parse_move(&board, "a8=N"), parse_move(&board, "a7a8n"));
+    // A promotion needs the piece.
+    assert!(parse_move(&board, "a7a8").is_err());
+    assert!(parse_move(&board, "e1e2q").is_err());
+}
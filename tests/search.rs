@@ -0,0 +1,119 @@
+use std::{
+    sync::atomic::Ordering,
+    thread,
+    time::{Duration, Instant},
+};
+
+use chessio::chess::{
+    fen::STARTING_POSITION_FEN,
+    search::{SearchLimits, Searcher, MATE_SCORE},
+    Board,
+};
+
+fn search(fen: &str, limits: SearchLimits) -> (String, i32) {
+    let board = Board::from_fen(fen).unwrap();
+    let mut position = board.get_position();
+    let before = position.clone();
+
+    let result = Searcher::default().search(&mut position, limits);
+    assert_eq!(position, before, "the search gives the position back");
+
+    let best_move = result
+        .best_move
+        .map(|mv| mv.to_string())
+        .unwrap_or_default();
+    (best_move, result.score)
+}
+
+#[test]
+fn finds_mate_in_one() {
+    let (best_move, score) = search("6k1/5ppp/8/8/8/8/8/R5K1 w - - 0 1", SearchLimits::depth(3));
+    assert_eq!(best_move, "a1a8");
+    assert_eq!(score, MATE_SCORE - 1);
+}
+
+#[test]
+fn finds_mate_in_two() {
+    // 1. Qd8+ Rxd8 2. Rxd8#
+    let (best_move, score) = search(
+        "r5k1/5ppp/8/8/8/8/3Q1PPP/3R2K1 w - - 0 1",
+        SearchLimits::depth(4),
+    );
+    assert_eq!(best_move, "d2d8");
+    assert_eq!(score, MATE_SCORE - 3);
+}
+
+#[test]
+fn takes_the_hanging_queen() {
+    let (best_move, score) = search(
+        "rnb1kbnr/pppp1ppp/8/4p1q1/3P4/2N5/PPP1PPPP/R1BQKBNR w KQkq - 0 1",
+        SearchLimits::depth(3),
+    );
+    assert_eq!(best_move, "c1g5");
+    assert!(score > 500, "score {}", score);
+}
+
+#[test]
+fn does_not_take_a_defended_pawn_with_the_queen() {
+    let (best_move, _) = search("4k3/8/2p5/3p4/8/8/8/3QK3 w - - 0 1", SearchLimits::depth(4));
+    assert_ne!(best_move, "d1d5");
+}
+
+#[test]
+fn no_move_when_the_game_is_over() {
+    // Black is checkmated.
+    let (best_move, score) = search("R5k1/5ppp/8/8/8/8/8/6K1 b - - 0 1", SearchLimits::depth(3));
+    assert_eq!(best_move, "");
+    assert_eq!(score, -MATE_SCORE);
+
+    // Black is stalemated.
+    let (best_move, score) = search("7k/5Q2/6K1/8/8/8/8/8 b - - 0 1", SearchLimits::depth(3));
+    assert_eq!(best_move, "");
+    assert_eq!(score, 0);
+}
+
+#[test]
+fn repetitions_of_the_game_are_draws() {
+    // Black is a queen and a rook up, but White has a perpetual check.
+    let mut board = Board::from_fen("5r1k/5p1p/5Q2/8/8/1q6/5PPP/6K1 b - - 0 1").unwrap();
+    for san in ["Kg8", "Qg5+", "Kh8", "Qf6+", "Kg8"] {
+        board.move_piece_san(san).unwrap();
+    }
+
+    let mut position = board.get_position_with_history();
+    assert_eq!(position.get_hash(), board.get_position().get_hash());
+    let result = Searcher::default().search(&mut position, SearchLimits::depth(4));
+    assert_eq!(result.best_move.unwrap().to_string(), "f6g5");
+    assert_eq!(result.score, 0);
+}
+
+#[test]
+fn time_limit_stops_the_search() {
+    let mut position = Board::from_fen(STARTING_POSITION_FEN)
+        .unwrap()
+        .get_position();
+    let started = Instant::now();
+    let result = Searcher::default().search(
+        &mut position,
+        SearchLimits::time(Duration::from_millis(200)),
+    );
+
+    assert!(started.elapsed() < Duration::from_secs(2));
+    assert!(result.best_move.is_some());
+    assert!(result.depth >= 1);
+}
+
+#[test]
+fn stop_flag_stops_an_unlimited_search() {
+    let mut searcher = Searcher::default();
+    let stop = searcher.get_stop_flag();
+    let handle = thread::spawn(move || {
+        let mut position = Board::new().get_position();
+        searcher.search(&mut position, SearchLimits::default())
+    });
+
+    thread::sleep(Duration::from_millis(200));
+    stop.store(true, Ordering::Relaxed);
+    let result = handle.join().unwrap();
+    assert!(result.best_move.is_some());
+}
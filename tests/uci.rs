@@ -0,0 +1,291 @@
+use std::{
+    io::{BufRead, BufReader, Write},
+    process::{Child, ChildStdin, ChildStdout, Command, Stdio},
+    sync::mpsc::{self, Receiver},
+    thread,
+    time::{Duration, Instant},
+};
+
+const TIMEOUT: Duration = Duration::from_secs(20);
+
+/// `chessio-uci` process talked to over its standard input and output.
+struct Engine {
+    child: Child,
+    stdin: ChildStdin,
+    lines: Receiver<String>,
+}
+
+impl Engine {
+    fn spawn() -> Self {
+        let mut child = Command::new(env!("CARGO_BIN_EXE_chessio-uci"))
+            .stdin(Stdio::piped())
+            .stdout(Stdio::piped())
+            .spawn()
+            .unwrap();
+        let stdin = child.stdin.take().unwrap();
+        let stdout: ChildStdout = child.stdout.take().unwrap();
+
+        // Reading on a thread, so a silent engine fails the test instead of
+        // hanging it.
+        let (sender, lines) = mpsc::channel();
+        thread::spawn(move || {
+            for line in BufReader::new(stdout).lines() {
+                if sender.send(line.unwrap()).is_err() {
+                    break;
+                }
+            }
+        });
+
+        Engine {
+            child,
+            stdin,
+            lines,
+        }
+    }
+    fn send(&mut self, command: &str) {
+        writeln!(self.stdin, "{}", command).unwrap();
+        self.stdin.flush().unwrap();
+    }
+    /// Lines up to and including the first one starting with `prefix`.
+    fn read_until(&mut self, prefix: &str) -> Vec<String> {
+        let mut lines = Vec::new();
+        loop {
+            let line = self
+                .lines
+                .recv_timeout(TIMEOUT)
+                .unwrap_or_else(|_| panic!("no '{}' in {:?}", prefix, lines));
+            let done = line.starts_with(prefix);
+            lines.push(line);
+            if done {
+                return lines;
+            }
+        }
+    }
+    /// Lines sent within `duration`.
+    fn read_for(&mut self, duration: Duration) -> Vec<String> {
+        let deadline = Instant::now() + duration;
+        let mut lines = Vec::new();
+        while let Ok(line) = self
+            .lines
+            .recv_timeout(deadline.saturating_duration_since(Instant::now()))
+        {
+            lines.push(line);
+        }
+        lines
+    }
+    fn get_best_move(&mut self) -> (String, Vec<String>) {
+        let lines = self.read_until("bestmove");
+        let best_move = lines.last().unwrap()["bestmove ".len()..].to_string();
+        (best_move, lines)
+    }
+}
+
+impl Drop for Engine {
+    fn drop(&mut self) {
+        let _ = self.child.kill();
+        let _ = self.child.wait();
+    }
+}
+
+#[test]
+fn handshake() {
+    let mut engine = Engine::spawn();
+    engine.send("uci");
+    let lines = engine.read_until("uciok");
+    assert!(lines[0].starts_with("id name "), "{:?}", lines);
+    assert!(lines.iter().any(|line| line.starts_with("id author ")));
+    assert!(lines
+        .iter()
+        .any(|line| line.starts_with("option name Hash type spin")));
+
+    engine.send("isready");
+    assert_eq!(engine.read_until("readyok"), ["readyok"]);
+}
+
+#[test]
+fn searches_a_position_after_moves() {
+    let mut engine = Engine::spawn();
+    engine.send("ucinewgame");
+    engine.send("position startpos moves e2e4 e7e5 g1f3");
+    engine.send("go depth 3");
+
+    let (best_move, lines) = engine.get_best_move();
+    let info: Vec<&String> = lines
+        .iter()
+        .filter(|line| line.starts_with("info depth"))
+        .collect();
+    assert_eq!(info.len(), 3, "{:?}", lines);
+    assert!(info[2].contains(" score cp "), "{}", info[2]);
+    assert!(
+        info[2].contains(&format!(" pv {}", best_move)),
+        "{}",
+        info[2]
+    );
+    // Black is on move, and all its pieces are still on the last two ranks.
+    assert!(matches!(&best_move[1..2], "7" | "8"), "{}", best_move);
+}
+
+#[test]
+fn reports_mate_scores() {
+    let mut engine = Engine::spawn();
+    engine.send("position fen 6k1/5ppp/8/8/8/8/8/R5K1 w - - 0 1");
+    engine.send("go depth 3");
+
+    let (best_move, lines) = engine.get_best_move();
+    assert_eq!(best_move, "a1a8");
+    assert!(
+        lines.iter().any(|line| line.contains(" score mate 1 ")),
+        "{:?}",
+        lines
+    );
+}
+
+#[test]
+fn no_move_in_a_finished_game() {
+    let mut engine = Engine::spawn();
+    engine.send("position fen R5k1/5ppp/8/8/8/8/8/6K1 b - - 0 1");
+    engine.send("go depth 2");
+    assert_eq!(engine.get_best_move().0, "0000");
+}
+
+#[test]
+fn stop_ends_an_infinite_search() {
+    let mut engine = Engine::spawn();
+    engine.send("position startpos");
+    engine.send("go infinite");
+    thread::sleep(Duration::from_millis(200));
+    engine.send("stop");
+
+    let (best_move, _) = engine.get_best_move();
+    assert_eq!(best_move.len(), 4, "{}", best_move);
+}
+
+#[test]
+fn infinite_search_waits_for_stop() {
+    let mut engine = Engine::spawn();
+    // The search is over at once in a finished game, but must not say so.
+    engine.send("position fen R5k1/5ppp/8/8/8/8/8/6K1 b - - 0 1");
+    engine.send("go infinite");
+    let lines = engine.read_for(Duration::from_millis(300));
+    assert!(
+        !lines.iter().any(|line| line.starts_with("bestmove")),
+        "{:?}",
+        lines
+    );
+
+    // Neither does it after `ponderhit`.
+    engine.send("ponderhit");
+    let lines = engine.read_for(Duration::from_millis(100));
+    assert!(
+        !lines.iter().any(|line| line.starts_with("bestmove")),
+        "{:?}",
+        lines
+    );
+
+    engine.send("stop");
+    assert_eq!(engine.get_best_move().0, "0000");
+}
+
+#[test]
+fn options_stop_an_infinite_search() {
+    for option in [
+        "setoption name Clear Hash",
+        "setoption name Hash value 8",
+        "setoption name EvalFile value <empty>",
+    ] {
+        let mut engine = Engine::spawn();
+        engine.send("position startpos");
+        engine.send("go infinite");
+        thread::sleep(Duration::from_millis(100));
+        engine.send(option);
+
+        let (best_move, _) = engine.get_best_move();
+        assert_eq!(best_move.len(), 4, "{}: {}", option, best_move);
+        engine.send("stop");
+        engine.send("isready");
+        assert_eq!(engine.read_until("readyok"), ["readyok"], "{}", option);
+    }
+}
+
+#[test]
+fn ponders_until_ponderhit_or_stop() {
+    let mut engine = Engine::spawn();
+    engine.send("uci");
+    let lines = engine.read_until("uciok");
+    assert!(lines
+        .iter()
+        .any(|line| line.starts_with("option name Ponder type check")));
+
+    engine.send("setoption name Ponder value true");
+    engine.send("position startpos moves e2e4 e7e5");
+    engine.send("go ponder wtime 2000 btime 2000");
+    // Ponder is a known option, and the search keeps its move.
+    let lines = engine.read_for(Duration::from_millis(300));
+    assert!(
+        !lines
+            .iter()
+            .any(|line| line.starts_with("bestmove") || line.starts_with("info string")),
+        "{:?}",
+        lines
+    );
+
+    // The move comes on the clock once the ponder move is played.
+    engine.send("ponderhit");
+    let (best_move, _) = engine.get_best_move();
+    assert_eq!(best_move.len(), 4, "{}", best_move);
+
+    // Pondering is not limited by the clock, the opponent playing another
+    // move ends it.
+    engine.send("position startpos moves e2e4 e7e5 g1f3");
+    engine.send("go ponder movetime 50");
+    let lines = engine.read_for(Duration::from_millis(200));
+    assert!(
+        !lines.iter().any(|line| line.starts_with("bestmove")),
+        "{:?}",
+        lines
+    );
+    engine.send("stop");
+    let (best_move, _) = engine.get_best_move();
+    assert_eq!(best_move.len(), 4, "{}", best_move);
+
+    engine.send("isready");
+    assert_eq!(engine.read_until("readyok"), ["readyok"]);
+}
+
+#[test]
+fn thinks_on_the_clock() {
+    let mut engine = Engine::spawn();
+    engine.send("position startpos moves d2d4");
+    engine.send("go wtime 1000 btime 1000 winc 0 binc 0");
+    let (best_move, _) = engine.get_best_move();
+    assert_eq!(best_move.len(), 4, "{}", best_move);
+
+    engine.send("go movetime 100");
+    let (best_move, _) = engine.get_best_move();
+    assert_eq!(best_move.len(), 4, "{}", best_move);
+}
+
+#[test]
+fn options_and_bad_input() {
+    let mut engine = Engine::spawn();
+    engine.send("setoption name Hash value 4");
+    engine.send("setoption name Clear Hash");
+    engine.send("setoption name EvalFile value /nonexistent/weights.cfg");
+    let lines = engine.read_until("info string");
+    assert!(lines[0].contains("EvalFile"), "{:?}", lines);
+
+    engine.send("position startpos moves e2e5");
+    let lines = engine.read_until("info string");
+    assert!(lines[0].contains("e2e5"), "{:?}", lines);
+
+    engine.send("not a command");
+    engine.send("isready");
+    assert_eq!(engine.read_until("readyok"), ["readyok"]);
+}
+
+#[test]
+fn quit_exits() {
+    let mut engine = Engine::spawn();
+    engine.send("quit");
+    assert!(engine.child.wait().unwrap().success());
+}
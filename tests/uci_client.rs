@@ -0,0 +1,302 @@
+// The stand-in engine is a shell script.
+#![cfg(unix)]
+
+use std::{
+    path::PathBuf,
+    thread,
+    time::{Duration, Instant},
+};
+
+use chessio::chess::{
+    clock::{ChessClock, TimeControl, WallTime},
+    moves::{get_uci_move_name, parse_uci_move},
+    piece::PieceType,
+    san::SanMove,
+    search::SearchLimits,
+    uci_client::{get_go_command, get_position_command, ClockTimes, UciClient, UciClientError},
+    Board,
+};
+
+/// Logs every command to the file given as its argument and answers like an
+/// engine that always plays e7e5. An infinite search only ends with `stop`,
+/// `go depth 99` makes it crash.
+const STAND_IN_ENGINE: &str = r#"
+log="$1"
+while read -r line; do
+    [ "$line" = "quit" ] && exit 0
+    echo "$line" >> "$log"
+    case "$line" in
+        uci)
+            echo "id name Stand-in"
+            echo "id author Nobody"
+            echo "option name Skill Level type spin default 10 min 0 max 20"
+            echo "option name Clear Hash type button"
+            echo "uciok"
+            ;;
+        isready) echo "readyok" ;;
+        "go infinite") ;;
+        "go depth 99") exit 1 ;;
+        go*)
+            echo "info depth 1 score cp 0 pv e7e5"
+            echo "bestmove e7e5"
+            ;;
+        stop) echo "bestmove a7a6" ;;
+    esac
+done
+"#;
+
+/// Stand-in engine together with the log of the commands it received.
+struct StandIn {
+    client: UciClient,
+    script: PathBuf,
+    log: PathBuf,
+}
+
+impl StandIn {
+    fn spawn(name: &str) -> Self {
+        let dir = std::env::temp_dir();
+        let id = format!("chessio-stand-in-{}-{}", std::process::id(), name);
+        let script = dir.join(format!("{}.sh", id));
+        let log = dir.join(format!("{}.log", id));
+        std::fs::write(&script, STAND_IN_ENGINE).unwrap();
+        let _ = std::fs::remove_file(&log);
+
+        let args = [
+            script.to_str().unwrap().to_string(),
+            log.to_str().unwrap().to_string(),
+        ];
+        let client = UciClient::spawn("sh", &args).unwrap();
+        StandIn {
+            client,
+            script,
+            log,
+        }
+    }
+    /// Commands the engine has received, once it has processed all of them.
+    fn get_log(&mut self) -> Vec<String> {
+        self.client.wait_until_ready().unwrap();
+        std::fs::read_to_string(&self.log)
+            .unwrap()
+            .lines()
+            .map(str::to_string)
+            .collect()
+    }
+}
+
+impl Drop for StandIn {
+    fn drop(&mut self) {
+        let _ = std::fs::remove_file(&self.script);
+        let _ = std::fs::remove_file(&self.log);
+    }
+}
+
+fn wait_for_move(client: &mut UciClient) -> Result<Option<SanMove>, UciClientError> {
+    let started = Instant::now();
+    while started.elapsed() < Duration::from_secs(20) {
+        if let Some(best_move) = client.poll_best_move()? {
+            return Ok(best_move);
+        }
+        thread::sleep(Duration::from_millis(5));
+    }
+
+    panic!("the engine did not reply with a move");
+}
+
+#[test]
+fn handshake() {
+    let stand_in = StandIn::spawn("handshake");
+
+    assert_eq!(stand_in.client.get_name(), Some("Stand-in"));
+    let options = stand_in.client.get_options();
+    assert_eq!(options.len(), 2);
+    assert_eq!(options[0].name, "Skill Level");
+    assert_eq!(options[0].kind, "spin");
+    assert_eq!(options[0].default.as_deref(), Some("10"));
+    assert_eq!(options[1].name, "Clear Hash");
+    assert_eq!(options[1].kind, "button");
+    assert_eq!(options[1].default, None);
+}
+
+#[test]
+fn sends_the_game_and_reads_the_reply() {
+    let mut stand_in = StandIn::spawn("reply");
+    let mut board = Board::new();
+    board.move_piece_san("e4").unwrap();
+
+    stand_in
+        .client
+        .start_search(&board, SearchLimits::time(Duration::from_millis(100)), None)
+        .unwrap();
+    assert!(stand_in.client.is_searching());
+    let best_move = wait_for_move(&mut stand_in.client).unwrap();
+    assert_eq!(best_move, Some(((4, 1), (4, 3), None)));
+    assert!(!stand_in.client.is_searching());
+
+    let (from, to, promotion) = best_move.unwrap();
+    assert!(board.move_piece(from, to, promotion));
+
+    let log = stand_in.get_log();
+    assert!(
+        log.ends_with(&[
+            "position startpos moves e2e4".to_string(),
+            "go movetime 100".to_string(),
+            "isready".to_string(),
+        ]),
+        "{:?}",
+        log
+    );
+}
+
+#[test]
+fn sets_declared_options() {
+    let mut stand_in = StandIn::spawn("options");
+
+    stand_in
+        .client
+        .set_option("skill level", Some("3"))
+        .unwrap();
+    stand_in.client.set_option("Clear Hash", None).unwrap();
+    assert_eq!(
+        stand_in.client.set_option("Threads", Some("4")),
+        Err(UciClientError::UnknownOption("Threads".to_string()))
+    );
+
+    let log = stand_in.get_log();
+    assert!(log.contains(&"setoption name Skill Level value 3".to_string()));
+    assert!(log.contains(&"setoption name Clear Hash".to_string()));
+    assert!(!log.iter().any(|line| line.contains("Threads")));
+}
+
+#[test]
+fn timed_games_send_the_clocks() {
+    let mut stand_in = StandIn::spawn("clock");
+    let clock = ChessClock::new(TimeControl::parse("5+3").unwrap(), WallTime::default());
+    let mut board = Board::new();
+    board.move_piece_san("e4").unwrap();
+
+    stand_in
+        .client
+        .start_search(
+            &board,
+            SearchLimits::time(Duration::from_millis(100)),
+            Some(ClockTimes::from_clock(&clock)),
+        )
+        .unwrap();
+    let best_move = wait_for_move(&mut stand_in.client).unwrap();
+    assert_eq!(best_move, parse_uci_move("e7e5"));
+
+    let log = stand_in.get_log();
+    assert!(log.contains(&"go wtime 300000 btime 300000 winc 3000 binc 3000".to_string()));
+    assert!(!log.iter().any(|line| line.contains("movetime")));
+}
+
+#[test]
+fn moves_of_stopped_searches_are_dropped() {
+    let mut stand_in = StandIn::spawn("stop");
+    let board = Board::new();
+
+    stand_in
+        .client
+        .start_search(&board, SearchLimits::default(), None)
+        .unwrap();
+    stand_in.client.stop().unwrap();
+    assert!(!stand_in.client.is_searching());
+    stand_in
+        .client
+        .start_search(&board, SearchLimits::depth(1), None)
+        .unwrap();
+
+    // a7a6 answers the stopped search.
+    let best_move = wait_for_move(&mut stand_in.client).unwrap();
+    assert_eq!(best_move, parse_uci_move("e7e5"));
+}
+
+#[test]
+fn engine_that_crashes() {
+    let mut stand_in = StandIn::spawn("crash");
+    stand_in
+        .client
+        .start_search(&Board::new(), SearchLimits::depth(99), None)
+        .unwrap();
+
+    assert_eq!(
+        wait_for_move(&mut stand_in.client),
+        Err(UciClientError::Exited)
+    );
+}
+
+#[test]
+fn engine_that_does_not_exist() {
+    assert!(matches!(
+        UciClient::spawn("/nonexistent/engine", &[]),
+        Err(UciClientError::Spawn(_))
+    ));
+}
+
+#[test]
+fn plays_against_the_built_in_engine() {
+    let mut client = UciClient::spawn(env!("CARGO_BIN_EXE_chessio-uci"), &[]).unwrap();
+    assert_eq!(client.get_name(), Some("Chessio"));
+    client.set_option("Hash", Some("8")).unwrap();
+    client.new_game().unwrap();
+
+    let mut board = Board::new();
+    for _ in 0..4 {
+        client
+            .start_search(&board, SearchLimits::depth(2), None)
+            .unwrap();
+        let (from, to, promotion) = wait_for_move(&mut client).unwrap().unwrap();
+        assert!(board.move_piece(from, to, promotion));
+    }
+    assert_eq!(board.move_records.len(), 4);
+}
+
+#[test]
+fn commands() {
+    let mut board = Board::from_fen("8/4P1k1/8/8/8/8/8/4K3 w - - 0 1").unwrap();
+    board.move_piece_san("e8=N+").unwrap();
+    assert_eq!(
+        get_position_command(&board),
+        "position fen 8/4P1k1/8/8/8/8/8/4K3 w - - 0 1 moves e7e8n"
+    );
+    assert_eq!(get_position_command(&Board::new()), "position startpos");
+
+    assert_eq!(get_go_command(SearchLimits::depth(5), None), "go depth 5");
+    assert_eq!(
+        get_go_command(SearchLimits::time(Duration::from_millis(250)), None),
+        "go movetime 250"
+    );
+    assert_eq!(get_go_command(SearchLimits::default(), None), "go infinite");
+
+    // The clocks replace the time per move.
+    let clock = ChessClock::new(TimeControl::parse("15d5").unwrap(), WallTime::default());
+    let times = ClockTimes::from_clock(&clock);
+    assert_eq!(
+        get_go_command(SearchLimits::time(Duration::from_millis(250)), Some(times)),
+        "go wtime 900000 btime 900000 winc 5000 binc 5000"
+    );
+    let times = ClockTimes {
+        remaining: [Duration::from_millis(61500), Duration::from_millis(800)],
+        increments: [Duration::ZERO; 2],
+    };
+    assert_eq!(
+        get_go_command(SearchLimits::depth(5), Some(times)),
+        "go depth 5 wtime 61500 btime 800 winc 0 binc 0"
+    );
+}
+
+#[test]
+fn move_notation() {
+    assert_eq!(
+        parse_uci_move("e7e8q"),
+        Some(((4, 1), (4, 0), Some(PieceType::Queen)))
+    );
+    assert_eq!(
+        get_uci_move_name((4, 1), (4, 0), Some(PieceType::Queen)),
+        "e7e8q"
+    );
+    assert_eq!(get_uci_move_name((6, 7), (5, 5), None), "g1f3");
+    for invalid in ["", "e2", "e2e9", "e7e8k", "e7e8qq", "e2-e4"] {
+        assert_eq!(parse_uci_move(invalid), None, "{}", invalid);
+    }
+}
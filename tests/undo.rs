@@ -0,0 +1,144 @@
+use chessio::chess::{fen::STARTING_POSITION_FEN as START, piece::Side, Board, GameMoveRecord};
+
+/// Everything about the game that undoing a move has to restore.
+fn get_state(board: &Board) -> String {
+    format!(
+        "{} {:?} {:?} {:?} {:?} {}",
+        board.to_fen(),
+        board.get_legal_moves(),
+        board.result,
+        board.is_check,
+        board.position_history,
+        board.move_records.len()
+    )
+}
+
+/// Plays `moves` from `fen`, undoes all of them and plays them again,
+/// checking the game after every step.
+fn undo_and_redo(fen: &str, moves: &[&str]) -> Board {
+    let mut board = Board::from_fen(fen).unwrap();
+    let mut states = vec![get_state(&board)];
+    for san in moves {
+        board.move_piece_san(san).unwrap();
+        states.push(get_state(&board));
+    }
+
+    let mut undone: Vec<GameMoveRecord> = Vec::new();
+    for index in (0..moves.len()).rev() {
+        let record = board.undo_move().unwrap();
+        assert_eq!(record.san, moves[index]);
+        assert_eq!(get_state(&board), states[index], "undoing {}", moves[index]);
+        undone.push(record);
+    }
+    assert!(board.undo_move().is_none());
+
+    for (index, record) in undone.into_iter().rev().enumerate() {
+        assert!(board.move_piece(record.from, record.to, record.promotion));
+        assert_eq!(
+            get_state(&board),
+            states[index + 1],
+            "redoing {}",
+            moves[index]
+        );
+    }
+
+    board
+}
+
+#[test]
+fn captures_and_castling() {
+    undo_and_redo(
+        START,
+        &[
+            "e4", "d5", "exd5", "Nf6", "Nf3", "Nxd5", "Bc4", "e6", "O-O", "Be7", "Re1", "O-O",
+        ],
+    );
+    undo_and_redo(
+        "r3k2r/pppq1ppp/8/8/8/8/PPPQ1PPP/R3K2R w KQkq - 4 12",
+        &["O-O-O", "O-O-O", "Qxd7+", "Rxd7"],
+    );
+}
+
+#[test]
+fn en_passant() {
+    undo_and_redo(START, &["e4", "a6", "e5", "d5", "exd6", "cxd6"]);
+    // An en passant target that is not taken.
+    undo_and_redo(START, &["e4", "Nf6", "e5", "d5", "Nc3", "Nbd7"]);
+}
+
+#[test]
+fn promotions() {
+    let board = undo_and_redo(
+        "1r2k3/P7/8/8/8/8/6p1/4K3 w - - 0 40",
+        &["axb8=Q+", "Kd7", "Qb7+", "Ke6", "Ke2", "g1=N+"],
+    );
+    assert_eq!(board.fullmove_number, 43);
+}
+
+#[test]
+fn game_results() {
+    let board = undo_and_redo(START, &["e4", "e5", "Bc4", "Nc6", "Qh5", "Nf6", "Qxf7#"]);
+    assert!(board.result.is_some());
+
+    let board = undo_and_redo("4k3/8/8/8/8/8/3p4/4K3 w - - 0 1", &["Kxd2"]);
+    assert!(board.result.is_some());
+
+    let board = undo_and_redo(
+        START,
+        &[
+            "Nf3", "Nf6", "Ng1", "Ng8", "Nf3", "Nf6", "Ng1", "Ng8", "Nf3", "Nf6", "Ng1", "Ng8",
+            "Nf3", "Nf6", "Ng1", "Ng8",
+        ],
+    );
+    assert!(board.result.is_some());
+}
+
+#[test]
+fn undo_and_redo_turns_against_the_computer() {
+    let is_computer = |side| side == Side::Black;
+    let mut board = Board::new();
+    for san in ["e4", "e5", "Nf3", "Nc6"] {
+        board.move_piece_san(san).unwrap();
+    }
+    let fen = board.to_fen();
+
+    // The reply of the computer is taken back with the move it answered.
+    let mut redo_moves = Vec::new();
+    board.undo_turn(&mut redo_moves, is_computer);
+    assert_eq!(board.move_records.len(), 2);
+    assert_eq!(board.get_side_on_move(), Side::White);
+    board.undo_turn(&mut redo_moves, is_computer);
+    board.undo_turn(&mut redo_moves, is_computer);
+    assert!(board.move_records.is_empty());
+    assert_eq!(redo_moves.len(), 4);
+
+    assert!(board.redo_turn(&mut redo_moves, is_computer));
+    assert_eq!(board.move_records.len(), 2);
+    assert!(board.redo_turn(&mut redo_moves, is_computer));
+    assert_eq!(board.to_fen(), fen);
+    assert!(redo_moves.is_empty());
+
+    // Without a computer every move is a turn of its own.
+    board.undo_turn(&mut redo_moves, |_| false);
+    assert_eq!(board.move_records.len(), 3);
+    assert!(board.redo_turn(&mut redo_moves, |_| false));
+    assert_eq!(board.move_records.len(), 4);
+}
+
+#[test]
+fn redo_stops_at_a_move_that_is_not_legal() {
+    let mut board = Board::new();
+    for san in ["e4", "e5", "Nf3", "Nc6"] {
+        board.move_piece_san(san).unwrap();
+    }
+    let mut redo_moves = Vec::new();
+    board.undo_turn(&mut redo_moves, |_| false);
+    board.undo_turn(&mut redo_moves, |_| false);
+
+    // The knight that Nf3 would move is gone from g1.
+    board.move_piece_san("Nh3").unwrap();
+    board.move_piece_san("d6").unwrap();
+    assert!(!board.redo_turn(&mut redo_moves, |_| false));
+    assert!(redo_moves.is_empty());
+    assert_eq!(board.move_records.len(), 4);
+}
@@ -0,0 +1,148 @@
+use chessio::chess::{
+    fen::STARTING_POSITION_FEN,
+    position::Position,
+    zobrist::{ZobristKeys, POLYGLOT_KEY_COUNT},
+    Board,
+};
+
+const KIWIPETE_FEN: &str = "r3k2r/p1ppqpb1/bn2pnp1/3PN3/1p2P3/2N2Q1p/PPPBBPPP/R3K2R w KQkq - 0 1";
+
+fn play(position: &mut Position, names: &[&str]) {
+    for name in names {
+        let mv = position
+            .legal_moves()
+            .into_iter()
+            .find(|mv| mv.to_string() == *name)
+            .unwrap_or_else(|| panic!("{} is not legal", name));
+        position.make_move(mv);
+    }
+}
+
+fn get_position(fen: &str) -> Position {
+    Board::from_fen(fen).unwrap().get_position()
+}
+
+#[test]
+fn incremental_hash_matches_hash_from_scratch() {
+    let mut position = get_position(KIWIPETE_FEN);
+    let keys = position.get_zobrist_keys();
+    let original = position.get_hash();
+    assert_eq!(original, keys.get_hash(&position));
+
+    for mv in position.legal_moves() {
+        position.make_move(mv);
+        let after_move = position.get_hash();
+        assert_eq!(after_move, keys.get_hash(&position), "after {}", mv);
+
+        for reply in position.legal_moves() {
+            position.make_move(reply);
+            assert_eq!(
+                position.get_hash(),
+                keys.get_hash(&position),
+                "after {} {}",
+                mv,
+                reply
+            );
+            assert_eq!(position.get_hash_history().last(), Some(after_move));
+            position.unmake_move();
+            assert_eq!(position.get_hash(), after_move);
+        }
+
+        position.unmake_move();
+        assert_eq!(position.get_hash(), original);
+    }
+}
+
+#[test]
+fn transpositions_share_a_hash() {
+    let mut position = get_position(STARTING_POSITION_FEN);
+    let start = position.get_hash();
+
+    play(&mut position, &["g1f3", "g8f6", "f3g1", "f6g8"]);
+    assert_eq!(position.get_hash(), start);
+
+    let mut first = get_position(STARTING_POSITION_FEN);
+    let mut second = get_position(STARTING_POSITION_FEN);
+    play(&mut first, &["e2e4", "e7e5", "g1f3"]);
+    play(&mut second, &["g1f3", "e7e5", "e2e4"]);
+    assert_ne!(
+        first.get_en_passant_target(),
+        second.get_en_passant_target()
+    );
+    assert_eq!(first.get_hash(), second.get_hash());
+}
+
+#[test]
+fn hash_tells_side_castling_and_en_passant_apart() {
+    let hash = |fen: &str| get_position(fen).get_hash();
+
+    let base = hash("4k3/8/8/8/8/8/8/R3K2R w KQ - 0 1");
+    assert_ne!(base, hash("4k3/8/8/8/8/8/8/R3K2R b KQ - 0 1"));
+    assert_ne!(base, hash("4k3/8/8/8/8/8/8/R3K2R w K - 0 1"));
+    assert_ne!(base, hash("4k3/8/8/8/8/8/8/R3K2R w - - 0 1"));
+
+    // The en passant file only counts when a pawn can take on it.
+    assert_eq!(
+        hash("4k3/8/8/8/4P3/8/8/4K3 b - e3 0 1"),
+        hash("4k3/8/8/8/4P3/8/8/4K3 b - - 0 1")
+    );
+    assert_ne!(
+        hash("4k3/8/8/8/3pP3/8/8/4K3 b - e3 0 1"),
+        hash("4k3/8/8/8/3pP3/8/8/4K3 b - - 0 1")
+    );
+}
+
+#[test]
+fn polyglot_layout() {
+    // Every key gets its own bit pattern, so the hash tells which keys were used.
+    let mut random64 = [0; POLYGLOT_KEY_COUNT];
+    for (index, key) in random64.iter_mut().enumerate() {
+        *key = (index as u64 + 1).wrapping_mul(0x9e37_79b9_7f4a_7c15);
+    }
+    let keys: &'static ZobristKeys = Box::leak(Box::new(ZobristKeys::from_polyglot(&random64)));
+
+    // Pieces are numbered black pawn, white pawn, black knight, white knight
+    // and so on, squares from a1. Then come the castling rights, the en
+    // passant files and White on move.
+    let fen = "4k2r/8/8/3pP3/8/8/8/4K3 w k d6 0 1";
+    let expected = random64[64 * 11 + 4]
+        ^ random64[64 * 10 + 60]
+        ^ random64[64 * 6 + 63]
+        ^ random64[64 + 36]
+        ^ random64[35]
+        ^ random64[770]
+        ^ random64[772 + 3]
+        ^ random64[780];
+
+    let board = Board::from_fen(fen).unwrap();
+    let position = Position::from_board_with_keys(&board, keys);
+    assert_eq!(position.get_hash(), expected);
+    assert_eq!(keys.get_hash(&position), expected);
+}
+
+#[test]
+fn board_history_holds_the_hashes() {
+    let mut board = Board::new();
+    for san in ["e4", "e5", "Nf3", "Nc6", "Bb5", "a6", "O-O"] {
+        board.move_piece_san(san).unwrap();
+        assert_eq!(board.move_records.last().unwrap().hash, board.get_hash());
+    }
+
+    let position = board.get_position_with_history();
+    let mut hashes: Vec<u64> = position.get_hash_history().collect();
+    hashes.push(position.get_hash());
+    assert_eq!(board.position_history, hashes);
+
+    // The position after 2. Nf3 comes back with 4. Nf3 and also arises after
+    // 1. Nf3 e5 2. e4.
+    let mut board = Board::new();
+    for san in ["e4", "e5", "Nf3", "Nf6", "Ng1", "Ng8", "Nf3"] {
+        board.move_piece_san(san).unwrap();
+    }
+    let mut transposed = Board::new();
+    for san in ["Nf3", "e5", "e4"] {
+        transposed.move_piece_san(san).unwrap();
+    }
+    assert_eq!(board.get_hash(), transposed.get_hash());
+    assert_eq!(board.get_repetition_count(), 2);
+}